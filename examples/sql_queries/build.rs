@@ -3,6 +3,10 @@ fn main() {
     tomplate_build::Builder::new()
         .add_pattern("**/*.tomplate.toml")
         .add_pattern("templates/*.toml")
+        .handlebars_helper("sql_ident", "helpers/sql_ident.rhai")
+        .render_to_rust("codegen", ["table_names"])
+        .global("report_table", "users")
+        .render_files([("templates/report.sql.tmpl", "report.sql")])
         .build()
         .expect("Failed to build templates");
 }
\ No newline at end of file
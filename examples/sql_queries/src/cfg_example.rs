@@ -116,4 +116,25 @@ mod tests {
         // This test verifies that the code compiles with cfg attributes
         setup_queries();
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_cfg_let_bindings() {
+        tomplate! {
+            // Alternative definitions of the same helper fragment
+            #[cfg(feature = "handlebars")]
+            let placeholder = "$1";
+            #[cfg(not(feature = "handlebars"))]
+            let placeholder = "?";
+            
+            const GET_USER = tomplate!(
+                "SELECT * FROM users WHERE id = {ph}",
+                ph = placeholder
+            );
+        }
+        
+        #[cfg(feature = "handlebars")]
+        assert_eq!(GET_USER, "SELECT * FROM users WHERE id = $1");
+        #[cfg(not(feature = "handlebars"))]
+        assert_eq!(GET_USER, "SELECT * FROM users WHERE id = ?");
+    }
+}
@@ -55,4 +55,102 @@ mod tests {
         
         assert_eq!(RESULT, "SELECT id, name, email FROM users WHERE active = true");
     }
-}
+    
+    #[test]
+    fn test_composition_with_plain_string_let() {
+        tomplate! {
+            let my_fields = "id, email";
+            const RESULT = tomplate!(
+                "select_user",
+                fields = my_fields,
+                condition = "active = true"
+            );
+        }
+        
+        assert_eq!(RESULT, "SELECT id, email FROM users WHERE active = true");
+    }
+    
+    #[test]
+    fn test_composition_enum() {
+        tomplate! {
+            let my_fields = tomplate!("user_fields");
+            
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            enum Queries {
+                GetUser = tomplate!(
+                    "select_user",
+                    fields = my_fields,
+                    condition = "id = 1"
+                ),
+                CountUsers = tomplate!("SELECT COUNT(*) FROM users"),
+            }
+        }
+        
+        assert_eq!(Queries::GetUser.as_str(), "SELECT id, name, email FROM users WHERE id = 1");
+        assert_eq!(Queries::CountUsers.as_str(), "SELECT COUNT(*) FROM users");
+        assert_eq!(Queries::CountUsers.name(), "CountUsers");
+    }
+    
+    #[test]
+    fn test_expression_block() {
+        const RESULT: &str = tomplate!({
+            let my_fields = "id, name";
+            let active = tomplate!("active = {flag}", flag = "true");
+            tomplate!("select_user", fields = my_fields, condition = active)
+        });
+        
+        assert_eq!(RESULT, "SELECT id, name FROM users WHERE active = true");
+    }
+    
+    #[test]
+    fn test_matrix_expansion() {
+        tomplate::tomplate_matrix!(
+            "COUNT_{dialect}_{table}",
+            tomplate!("SELECT COUNT(*) FROM {table} -- {dialect}", table = table, dialect = dialect),
+            dialect = ["postgres", "sqlite"],
+            table = ["users", "posts"],
+        );
+        
+        assert_eq!(COUNT_POSTGRES_USERS, "SELECT COUNT(*) FROM users -- postgres");
+        assert_eq!(COUNT_POSTGRES_POSTS, "SELECT COUNT(*) FROM posts -- postgres");
+        assert_eq!(COUNT_SQLITE_USERS, "SELECT COUNT(*) FROM users -- sqlite");
+        assert_eq!(COUNT_SQLITE_POSTS, "SELECT COUNT(*) FROM posts -- sqlite");
+    }
+    
+    #[test]
+    fn test_matrix_in_block() {
+        tomplate! {
+            let my_fields = "id, name";
+            
+            #[allow(dead_code)]
+            tomplate_matrix!(
+                "SELECT_{status}",
+                tomplate!(
+                    "select_user",
+                    fields = my_fields,
+                    condition = tomplate!("status = '{status}'", status = status)
+                ),
+                status = ["active", "banned"],
+            );
+        }
+        
+        assert_eq!(SELECT_ACTIVE, "SELECT id, name FROM users WHERE status = 'active'");
+    }
+    
+    #[test]
+    fn test_fn_statements() {
+        tomplate! {
+            let columns = "id, name";
+            
+            fn greet(name) = tomplate!("Hello {name}!");
+            
+            fn find_by(column, table) = tomplate!(
+                "SELECT {columns} FROM {table} WHERE {column} = $1",
+                columns = columns
+            );
+        }
+        
+        assert_eq!(greet("World"), "Hello World!");
+        assert_eq!(find_by("email", "users"), "SELECT id, name FROM users WHERE email = $1");
+    }
+}
\ No newline at end of file
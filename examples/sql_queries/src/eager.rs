@@ -67,6 +67,14 @@ pub fn simulated_sqlx_example() {
     println!("Query would be: {:?}", query.sql);
 }
 
+/// Project-local wrapper around tomplate!
+#[allow(unused_macros)]
+macro_rules! query_template {
+    ($($args:tt)*) => {
+        tomplate!($($args)*)
+    };
+}
+
 /// Example with nested template composition
 pub fn nested_composition_example() {
     tomplate_eager! {
@@ -142,4 +150,99 @@ mod tests {
         
         assert_eq!(NESTED, "SELECT id, name, email FROM users WHERE active = true");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_path_qualified_macros_in_eager() {
+        tomplate_eager! {
+            const QUALIFIED: &str = ::core::concat!(
+                tomplate::tomplate!("select_user", fields = "id", condition = "1=1"),
+                ";"
+            );
+        }
+        
+        assert_eq!(QUALIFIED, "SELECT id FROM users WHERE 1=1;");
+    }
+    
+    #[test]
+    fn test_expand_alias_in_eager() {
+        tomplate_eager! {
+            #[expand(query_template)]
+            let query = fake_query!(query_template!("select_user",
+                fields = "id",
+                condition = "id = 1"
+            ));
+        }
+        
+        assert_eq!(query.sql, "SELECT id FROM users WHERE id = 1");
+    }
+    
+    #[test]
+    fn test_env_and_stringify_in_eager() {
+        tomplate_eager! {
+            const PACKAGE: &str = concat!("-- ", env!("CARGO_PKG_NAME"), " ", stringify!(users));
+        }
+        
+        assert_eq!(PACKAGE, "-- sql-queries-example users");
+    }
+    
+    #[test]
+    fn test_include_str_in_eager() {
+        tomplate_eager! {
+            let query = fake_query!(concat!(include_str!("sql/active_users.sql"), " LIMIT 10"));
+        }
+        
+        assert_eq!(query.sql, "SELECT id FROM users WHERE active = true LIMIT 10");
+    }
+    
+    #[tomplate::eager]
+    fn attribute_query() -> &'static str {
+        let query = fake_query!(tomplate!("select_user", fields = "name", condition = "id = 2"));
+        query.sql
+    }
+    
+    #[test]
+    fn test_eager_attribute() {
+        assert_eq!(attribute_query(), "SELECT name FROM users WHERE id = 2");
+    }
+    
+    fn parse_limit() -> Result<u32, std::num::ParseIntError> {
+        let limit = tomplate_eager!(tomplate!("{n}0", n = "5").parse::<u32>()?);
+        Ok(limit)
+    }
+    
+    #[test]
+    fn test_eager_expression_position() {
+        let sql = tomplate_eager!(fake_query!(tomplate!("select_user",
+            fields = "id",
+            condition = "id = 3"
+        )).sql);
+        
+        assert_eq!(sql, "SELECT id FROM users WHERE id = 3");
+        assert_eq!(parse_limit(), Ok(50));
+    }
+    
+    #[test]
+    fn test_format_in_eager() {
+        let user_id = 42;
+        let condition = format!("id = {}", user_id);
+        let query = tomplate_eager! {
+            format!(
+                "{} WHERE {} -- {:>4}",
+                tomplate!("SELECT {fields} FROM users", fields = "id, name"),
+                condition,
+                user_id
+            )
+        };
+        
+        assert_eq!(query, "SELECT id, name FROM users WHERE id = 42 --   42");
+    }
+    
+    #[test]
+    fn test_concat_literal_types_in_eager() {
+        tomplate_eager! {
+            const MIXED: &str = concat!("v", 1u8, '.', 2.5f32, " ", -3, " ", true);
+        }
+        
+        assert_eq!(MIXED, concat!("v", 1u8, '.', 2.5f32, " ", -3, " ", true));
+    }
+}
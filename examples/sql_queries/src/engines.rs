@@ -85,4 +85,296 @@ mod tests {
         assert!(QUERY.contains("SELECT id, username"));
         assert!(QUERY.contains("WHERE role = 'admin'"));
     }
+    
+    #[test]
+    fn test_simple_engine_filters() {
+        const RESULT: &str = tomplate!(
+            "SELECT {cols|join:, } FROM {table|snake} -- {name|upper}",
+            cols = "id,name , email",
+            table = "UserAccounts",
+            name = "report"
+        );
+        
+        assert_eq!(RESULT, "SELECT id, name, email FROM user_accounts -- REPORT");
+    }
+    
+    #[test]
+    fn test_simple_engine_dotted_keys() {
+        const RESULT: &str = tomplate!(
+            "{user.name} <{user.email}>",
+            user.name = "Alice",
+            user.email = "alice@example.com"
+        );
+        
+        assert_eq!(RESULT, "Alice <alice@example.com>");
+    }
+    
+    #[test]
+    fn test_simple_engine_indent() {
+        const INLINE: &str = tomplate!(
+            #[indent]
+            "WHERE {conditions}",
+            conditions = "active = true\nAND deleted_at IS NULL"
+        );
+        assert_eq!(INLINE, "WHERE active = true\n      AND deleted_at IS NULL");
+        
+        const YAML: &str = tomplate!("yaml_service", name = "api", env = "PORT: 80\nHOST: 0.0.0.0");
+        assert_eq!(YAML, "service:\n  name: api\n  env:\n    PORT: 80\n    HOST: 0.0.0.0");
+    }
+    
+    #[test]
+    fn test_simple_engine_lists() {
+        const SELECT: &str = tomplate!(
+            "SELECT {cols} FROM users WHERE {conditions*\" AND \"}",
+            cols = ["id", "name"],
+            conditions = ["active = true", "age > 18"]
+        );
+        assert_eq!(SELECT, "SELECT id, name FROM users WHERE active = true AND age > 18");
+        
+        const IN_LIST: &str = tomplate!(
+            "WHERE id IN ({ids|repeat:?}) OR code IN ({n|repeat:$*\",\"})",
+            ids = [1, 2, 3],
+            n = 2
+        );
+        assert_eq!(IN_LIST, "WHERE id IN (?, ?, ?) OR code IN ($,$)");
+        
+        const UPPER: &str = tomplate!("{cols|upper|join:/}", cols = ["id", "name"]);
+        assert_eq!(UPPER, "ID/NAME");
+    }
+    
+    #[cfg(feature = "fluent")]
+    #[test]
+    fn test_fluent_engine() {
+        const ONE: &str = tomplate!("fluent_unread", name = "Alice", count = 1);
+        const MANY: &str = tomplate!("fluent_unread", name = "Bob", count = 3);
+        
+        assert_eq!(ONE, "Alice has one unread message.");
+        assert_eq!(MANY, "Bob has 3 unread messages.");
+    }
+    
+    #[cfg(feature = "handlebars-scripts")]
+    #[test]
+    fn test_handlebars_script_helper() {
+        const QUERY: &str = tomplate!("handlebars_quoted_select", table = "user \"accounts\"");
+        assert_eq!(QUERY, "SELECT * FROM \"user \"\"accounts\"\"\"");
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_inheritance() {
+        const QUERY: &str = tomplate!("tera_user_query", table = "users");
+        assert_eq!(QUERY, "SELECT id, name FROM users");
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_import() {
+        const QUERY: &str = tomplate!("minijinja_aliased_select", field = "total", table = "orders");
+        assert_eq!(QUERY, "SELECT total AS value FROM orders");
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_typed_parameters() {
+        const DISABLED: &str = tomplate!("handlebars_flags", enabled = false, count = 10);
+        const ENABLED: &str = tomplate!("handlebars_flags", enabled = true, count = 2);
+        
+        assert_eq!(DISABLED, "off, many");
+        assert_eq!(ENABLED, "on, few");
+    }
+    
+    #[test]
+    fn test_negative_parameters() {
+        const RANGE: &str = tomplate!(
+            "WHERE balance BETWEEN {min} AND {max} AND id IN ({ids})",
+            min = -9223372036854775808,
+            max = -0.5,
+            ids = [-1, 2]
+        );
+        assert_eq!(RANGE, "WHERE balance BETWEEN -9223372036854775808 AND -0.5 AND id IN (-1, 2)");
+    }
+    
+    #[test]
+    fn test_structured_parameters() {
+        const USER: &str = tomplate!(
+            "{user.name} ({user.roles.0}), {db.host}:{db.port}",
+            user = json!({ "name": "Alice", "roles": ["admin", "dev"] }),
+            db = toml!("host = \"localhost\"\nport = 5432")
+        );
+        assert_eq!(USER, "Alice (admin), localhost:5432");
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_structured_loop() {
+        const SETTINGS: &str = tomplate!(
+            "tera_settings",
+            settings = json!({ "debug": false, "retries": -1 })
+        );
+        assert_eq!(SETTINGS, "debug=false;retries=-1;");
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_whitespace_control() {
+        const QUERY: &str = tomplate!("tera_column_list", columns = ["id", "name"]);
+        assert_eq!(QUERY, "SELECT\n    id,\n    name,\nFROM users");
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_whitespace_control() {
+        const QUERY: &str = tomplate!("minijinja_column_list", columns = ["id", "name"]);
+        assert_eq!(QUERY, "SELECT\n    id,\n    name,\nFROM users");
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_custom_delimiters() {
+        const QUERY: &str = tomplate!(
+            "minijinja_json_object",
+            table = "users",
+            column = "status",
+            value = "active"
+        );
+        assert_eq!(QUERY, r#"{"table": "users", "filter": {"status": "active"}}"#);
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_html_escaping() {
+        const GREETING: &str = tomplate!("handlebars_html_greeting", name = "<Tom & \"Jerry\">");
+        assert_eq!(GREETING, "<p>Hello, &lt;Tom &amp; &quot;Jerry&quot;&gt;!</p>");
+        
+        const UNESCAPED: &str = tomplate!(
+            #[escape = "none"]
+            "handlebars_html_greeting",
+            name = "<b>Tom</b>"
+        );
+        assert_eq!(UNESCAPED, "<p>Hello, <b>Tom</b>!</p>");
+        
+        const SIMPLE: &str = tomplate!(#[escape = "html"] "<td>{value}</td>", value = "a < b");
+        assert_eq!(SIMPLE, "<td>a &lt; b</td>");
+    }
+    
+    #[test]
+    fn test_sql_quoting_filters() {
+        const QUERY: &str = tomplate!(
+            "SELECT {cols|sql_ident} FROM {table|sql_ident} WHERE name = {name|sql_str} AND status IN {statuses|sql_in}",
+            cols = ["id", "full name"],
+            table = "my\"table",
+            name = "O'Brien",
+            statuses = ["active", "pending"]
+        );
+        assert_eq!(
+            QUERY,
+            r#"SELECT "id", "full name" FROM "my""table" WHERE name = 'O''Brien' AND status IN ('active', 'pending')"#
+        );
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_sql_quoting() {
+        const QUERY: &str = tomplate!("tera_quoted_lookup", table = "users", name = "O'Brien", ids = [1, 2]);
+        assert_eq!(QUERY, r#"SELECT * FROM "users" WHERE name = 'O''Brien' AND id IN (1, 2)"#);
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_sql_quoting() {
+        const QUERY: &str = tomplate!("minijinja_quoted_lookup", table = "users", name = "O'Brien", ids = []);
+        assert_eq!(QUERY, r#"SELECT * FROM "users" WHERE name = 'O''Brien' AND id IN (NULL)"#);
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_handlebars_plurals() {
+        const ONE: &str = tomplate!("handlebars_cart_summary", count = 1);
+        const MANY: &str = tomplate!("handlebars_cart_summary", count = 3);
+        
+        assert_eq!(ONE, "1 item in your cart");
+        assert_eq!(MANY, "3 items in your cart");
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_plurals() {
+        const FEW: &str = tomplate!("tera_cart_summary", count = 22);
+        const MANY: &str = tomplate!("tera_cart_summary", count = 5);
+        
+        assert_eq!(FEW, "22 pliki");
+        assert_eq!(MANY, "5 plików");
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_plurals() {
+        const ONE: &str = tomplate!("minijinja_cart_summary", count = 1);
+        const MANY: &str = tomplate!("minijinja_cart_summary", count = 12);
+        
+        assert_eq!(ONE, "1 plik (one)");
+        assert_eq!(MANY, "12 plików (many)");
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_handlebars_number_formatting() {
+        const TOTAL: &str = tomplate!("handlebars_invoice_total", total = 1234.5, count = 12000, due = "2024-03-05");
+        assert_eq!(TOTAL, "Total: $1,234.50 (12,000 units, due March 5, 2024)");
+    }
+    
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_tera_number_formatting() {
+        const TOTAL: &str = tomplate!("tera_invoice_total", total = 1234.5, due = "2024-03-05");
+        assert_eq!(TOTAL, "Summe: 1.234,50\u{a0}€, fällig am 5. März 2024");
+    }
+    
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_minijinja_number_formatting() {
+        const TOTAL: &str = tomplate!("minijinja_invoice_total", total = -1234.5, due = "2024-03-05T09:00:00Z");
+        assert_eq!(TOTAL, "-1\u{202f}234,50\u{a0}€ le 5 mars 2024 (5 March 2024)");
+    }
+    
+    #[test]
+    fn test_json_escaping() {
+        const PAYLOAD: &str = tomplate!(
+            r#"{"message": "{message|json}"}"#,
+            message = "say \"hi\"\nthen leave"
+        );
+        assert_eq!(PAYLOAD, r#"{"message": "say \"hi\"\nthen leave"}"#);
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_handlebars_json_escaping() {
+        const PAYLOAD: &str = tomplate!(
+            "handlebars_json_event",
+            name = "login",
+            detail = "C:\\Users\\admin"
+        );
+        assert_eq!(PAYLOAD, r#"{"event": "login", "detail": "C:\\Users\\admin"}"#);
+    }
+    
+    #[test]
+    fn test_shell_quoting() {
+        const COMMAND: &str = tomplate!(
+            "psql {args|shell} -c {query|shell}",
+            args = ["-U", "app user"],
+            query = "SELECT 'x'"
+        );
+        assert_eq!(COMMAND, r#"psql -U 'app user' -c 'SELECT '\''x'\'''"#);
+    }
+    
+    #[cfg(feature = "handlebars")]
+    #[test]
+    fn test_shell_escape_mode() {
+        const UNIT: &str = tomplate!(
+            "handlebars_exec_start",
+            source = "/srv/my data",
+            label = "nightly"
+        );
+        assert_eq!(UNIT, "ExecStart=/usr/bin/backup --source '/srv/my data' --label nightly");
+    }
 }
\ No newline at end of file
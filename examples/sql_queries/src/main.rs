@@ -94,4 +94,431 @@ mod tests {
         );
         assert_eq!(QUERY, "SELECT id, name, email FROM users WHERE active = true");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_registry_references() {
+        const QUERY: &str = tomplate!("select_user_fields", condition = "active = true");
+        assert_eq!(QUERY, "SELECT id, name, email FROM users WHERE active = true");
+    }
+    
+    #[test]
+    fn test_indirect_names() {
+        const QUERY: &str = tomplate!(concat!("select_", "user"), fields = "id", condition = "id = $1");
+        assert_eq!(QUERY, "SELECT id FROM users WHERE id = $1");
+        
+        const FROM_ENV: &str = tomplate!(concat!("{greeting} from ", env!("CARGO_PKG_NAME")), greeting = "Hi");
+        assert_eq!(FROM_ENV, "Hi from sql-queries-example");
+        
+        macro_rules! select_user {
+            ($name:expr) => {
+                tomplate!($name, fields = "*", condition = "true")
+            };
+        }
+        const ALL: &str = select_user!(concat!("select", "_user"));
+        assert_eq!(ALL, "SELECT * FROM users WHERE true");
+    }
+    
+    #[test]
+    fn test_dedent_and_trim() {
+        const QUERY: &str = tomplate!("indented_report", fields = "id, title");
+        assert_eq!(QUERY, "SELECT id, title\nFROM reports\n  WHERE archived = false");
+        
+        const INLINE: &str = tomplate!(#[trim] "  {name}  ", name = "padded");
+        assert_eq!(INLINE, "padded");
+    }
+    
+    #[test]
+    fn test_partial_application() {
+        const TENANT: &str = tomplate!("tenant_query", schema = "acme");
+        assert_eq!(
+            TENANT,
+            "SELECT {fields} FROM acme.orders WHERE customer_id = {customer_id|default:$1}"
+        );
+        
+        const STAGED: &str = tomplate!(#[partial] "{greeting}, {name}!", greeting = "Hello");
+        assert_eq!(STAGED, "Hello, {name}!");
+    }
+    
+    #[test]
+    fn test_default_filter() {
+        const MISSING: &str = tomplate!("WHERE customer_id = {customer_id|default:$1}");
+        assert_eq!(MISSING, "WHERE customer_id = $1");
+        
+        const EMPTY: &str = tomplate!("LIMIT {limit|default:50}", limit = "");
+        assert_eq!(EMPTY, "LIMIT 50");
+        
+        const PASSED: &str = tomplate!("LIMIT {limit|default:50}", limit = 10);
+        assert_eq!(PASSED, "LIMIT 10");
+    }
+    
+    #[test]
+    fn test_conditional_params() {
+        const QUERY: &str = tomplate!("SELECT {audit}id FROM users",
+            #[cfg(test)] audit = "created_by, ",
+            #[cfg(not(test))] audit = ""
+        );
+        assert_eq!(QUERY, "SELECT created_by, id FROM users");
+        
+        const FIELDS: &str = tomplate!("select_user",
+            fields = "id",
+            #[cfg(not(test))] fields = "id, secret",
+            condition = "true"
+        );
+        assert_eq!(FIELDS, "SELECT id FROM users WHERE true");
+    }
+    
+    #[test]
+    fn test_template_names() {
+        const SELECTS: &[&str] = tomplate::tomplate_names!("select_");
+        assert_eq!(SELECTS, ["select_posts", "select_user", "select_user_fields"]);
+        
+        const TERA_QUERIES: &[&str] = tomplate::tomplate_names!("tera_*_query");
+        assert_eq!(TERA_QUERIES, ["tera_base_query", "tera_user_query"]);
+    }
+    
+    #[test]
+    fn test_for_each_consts() {
+        tomplate::tomplate_for_each!("[pu]*_fields", "COLUMNS_{name}");
+        assert_eq!(COLUMNS_POST_FIELDS, "id, title, content, user_id");
+        assert_eq!(COLUMNS_USER_FIELDS, "id, name, email");
+    }
+    
+    #[test]
+    fn test_for_each_table() {
+        const FIELDS: &[(&str, &str)] =
+            &tomplate::tomplate_for_each!("[pu]*_fields", |name, columns| [(name, columns)]);
+        assert_eq!(
+            FIELDS,
+            [("post_fields", "id, title, content, user_id"), ("user_fields", "id, name, email")]
+        );
+    }
+    
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_runtime_registry() {
+        let registry = tomplate::registry!();
+        
+        let template = registry.get("select_user").expect("select_user is in the registry");
+        assert_eq!(template.template, "SELECT {fields} FROM users WHERE {condition}");
+        assert_eq!(template.engine.as_deref(), Some("simple"));
+        assert!(registry.names().any(|name| name == "user_fields"));
+        assert_eq!(registry.len(), registry.iter().count());
+    }
+    
+    tomplate::tomplate_fn!(fn select_user);
+    tomplate::tomplate_fn!(
+        /// Tenant-scoped user lookup
+        fn find_tenant_user = "select_user"
+    );
+    
+    #[test]
+    fn test_template_fn() {
+        assert_eq!(select_user("id, name", "id = $1"), "SELECT id, name FROM users WHERE id = $1");
+        assert_eq!(
+            find_tenant_user("{email}", "tenant_id = 7"),
+            "SELECT {email} FROM users WHERE tenant_id = 7"
+        );
+    }
+    
+    #[derive(tomplate::Tomplate)]
+    #[allow(dead_code)]
+    enum Query {
+        #[tomplate("select_user", fields = "id, name", condition = "id = $1")]
+        UserById,
+        #[tomplate("SELECT COUNT(*) FROM {table}", table = "users")]
+        Count { table: &'static str },
+    }
+    
+    #[test]
+    fn test_derive_tomplate() {
+        assert_eq!(Query::UserById.as_str(), "SELECT id, name FROM users WHERE id = $1");
+        assert_eq!(Query::Count { table: "users" }.as_str(), "SELECT COUNT(*) FROM users");
+    }
+    
+    #[tomplate::consts(prefix = "table_")]
+    mod tables {}
+    
+    #[tomplate::consts(pattern = "[pu]*_fields")]
+    mod fields {
+        pub const ALL: &str = "*";
+    }
+    
+    #[test]
+    fn test_consts_module() {
+        assert_eq!(fields::POST_FIELDS, "id, title, content, user_id");
+        assert_eq!(fields::USER_FIELDS, "id, name, email");
+        assert_eq!(fields::ALL, "*");
+        assert_eq!(tables::NAME, "users");
+    }
+    
+    #[tomplate::locales(prefix = "i18n/", fallback = "en")]
+    mod i18n {}
+    
+    #[test]
+    fn test_locale_modules() {
+        assert_eq!(i18n::de::GREETING, "Willkommen zurück!");
+        assert_eq!(i18n::en::FAREWELL, "See you soon.");
+        assert_eq!(i18n::get(i18n::Locale::De, i18n::Message::Greeting), "Willkommen zurück!");
+        assert_eq!(i18n::get(i18n::Locale::De, i18n::Message::Farewell), "See you soon.");
+        assert_eq!(i18n::Locale::from_code("en"), Some(i18n::Locale::En));
+        assert_eq!(i18n::Locale::ALL.len(), 2);
+    }
+    
+    #[cfg(feature = "phf")]
+    #[test]
+    fn test_template_map() {
+        static FIELDS: tomplate::phf::Map<&str, &str> = tomplate::tomplate_map!("[pu]*_fields");
+        assert_eq!(FIELDS.get("user_fields"), Some(&"id, name, email"));
+        assert_eq!(FIELDS.len(), 2);
+        
+        static QUERIES: tomplate::phf::Map<&str, &str> = tomplate::tomplate_map! {
+            "user" => tomplate!("select_user", fields = "*", condition = "id = $1"),
+            "count" => tomplate!("SELECT COUNT(*) FROM {table}", table = "users"),
+        };
+        assert_eq!(QUERIES.get("user"), Some(&"SELECT * FROM users WHERE id = $1"));
+        assert_eq!(QUERIES.get("count"), Some(&"SELECT COUNT(*) FROM users"));
+        assert_eq!(QUERIES.get("missing"), None);
+    }
+    
+    #[test]
+    fn test_bind_count() {
+        use tomplate::tomplate_binds;
+        
+        const NUMBERED: usize = tomplate_binds!("SELECT * FROM users WHERE id = $1 AND org = $2 OR owner = $1");
+        const POSITIONAL: usize = tomplate_binds!("INSERT INTO logs (level, message) VALUES (?, ?)");
+        const QUOTED: usize = tomplate_binds!("SELECT '$3?' FROM users -- $5\n WHERE id = $1");
+        
+        assert_eq!(NUMBERED, 2);
+        assert_eq!(POSITIONAL, 2);
+        assert_eq!(QUOTED, 1);
+    }
+    
+    #[test]
+    fn test_compile_time_assertions() {
+        use tomplate::{tomplate_assert, tomplate_assert_eq};
+        
+        tomplate_assert!(starts_with("SELECT") && !contains(";"), "select_user", fields = "*", condition = "id = $1");
+        tomplate_assert!(matches(r"WHERE id = \$\d+$"), "select_user", fields = "*", condition = "id = $1");
+        tomplate_assert_eq!(
+            "select_user",
+            fields = "id, name",
+            condition = "id = $1",
+            expected = "SELECT id, name FROM users WHERE id = $1"
+        );
+    }
+    
+    #[test]
+    fn test_placeholder_rewriting() {
+        const MYSQL: &str = tomplate!(#[placeholders = "mysql"] "select_user",
+            fields = "*",
+            condition = "id = $1 AND org = $2"
+        );
+        const POSTGRES: &str = tomplate!(#[placeholders = "postgres"] "select_user",
+            fields = "*",
+            condition = "id = :id AND (owner = :id OR org = :org) AND created::date = '2024-01-01'"
+        );
+        const NAMED: &str = tomplate!(#[placeholders = "named"] "select_user",
+            fields = "*",
+            condition = "id = ? AND org = ?"
+        );
+        
+        assert_eq!(MYSQL, "SELECT * FROM users WHERE id = ? AND org = ?");
+        assert_eq!(
+            POSTGRES,
+            "SELECT * FROM users WHERE id = $1 AND (owner = $1 OR org = $2) AND created::date = '2024-01-01'"
+        );
+        assert_eq!(NAMED, "SELECT * FROM users WHERE id = :p1 AND org = :p2");
+    }
+    
+    // Generated code holds items, so it's included at module level
+    mod codegen {
+        include!(concat!(env!("OUT_DIR"), "/codegen/table_names.rs"));
+    }
+    
+    #[test]
+    fn test_rust_codegen() {
+        assert_eq!(codegen::TABLE_NAMES, ["users", "posts"]);
+    }
+    
+    #[test]
+    fn test_rendered_file() {
+        // Rendered by the build script, with references into the registry
+        const REPORT: &str = include_str!(concat!(env!("OUT_DIR"), "/report.sql"));
+        assert_eq!(REPORT, "SELECT id, name, email FROM users\n");
+    }
+    
+    #[test]
+    #[cfg(feature = "testing")]
+    fn test_testing_templates() {
+        let templates = tomplate::testing::templates!();
+        assert_eq!(
+            templates.render("select_user", &[("condition", "id = $1"), ("fields", "id")]),
+            Ok("SELECT id FROM users WHERE id = $1".to_string())
+        );
+        // Values are inserted as given, without escaping
+        assert_eq!(
+            templates.render("select_user", &[("fields", "{x}"), ("condition", "a = '{'")]),
+            Ok("SELECT {x} FROM users WHERE a = '{'".to_string())
+        );
+        
+        assert_eq!(
+            templates.render("select_user", &[("fields", "id")]),
+            Err("Template 'select_user' is missing param 'condition'".to_string())
+        );
+        assert_eq!(
+            templates.render("select_user", &[("fields", "id"), ("condition", "true"), ("limit", "1")]),
+            Err("Template 'select_user' has no param 'limit'".to_string())
+        );
+        assert_eq!(templates.render("missing", &[]), Err("Template not found: missing".to_string()));
+        
+        let names: Vec<&str> = templates.names().collect();
+        assert!(names.contains(&"select_user") && names.contains(&"user_fields"));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+    
+    #[test]
+    #[cfg(feature = "compress")]
+    fn test_compressed_template() {
+        use tomplate::{compress::Compressed, tomplate_compress};
+        
+        const QUERY: Compressed = tomplate_compress!("select_user", fields = "id, name", condition = "id = $1");
+        let mut buf = [0u8; QUERY.len()];
+        
+        assert_eq!(QUERY.decompress_into(&mut buf), "SELECT id, name FROM users WHERE id = $1");
+    }
+    
+    #[test]
+    #[cfg(feature = "markdown")]
+    fn test_markdown_postprocess() {
+        const HELP: &str = tomplate!("help_page", command = "migrate", usage = "app migrate <dir>");
+        assert_eq!(HELP, "<h1>migrate</h1>\n<p>Usage: <code>app migrate &lt;dir&gt;</code></p>\n");
+        
+        const INLINE: &str = tomplate!(#[postprocess = "markdown"] "*{word}*", word = "hi");
+        assert_eq!(INLINE, "<p><em>hi</em></p>\n");
+    }
+    
+    #[test]
+    fn test_minify_postprocess() {
+        const PAGE: &str = tomplate!("status_page", service = "api", status = "up");
+        assert_eq!(
+            PAGE,
+            "<html><head><style>body{font-family:sans-serif}</style></head><body><h1>api</h1><p>Status: <b>up</b></p></body></html>"
+        );
+        
+        const SCRIPT: &str = tomplate!(#[postprocess = "js-minify"] "const {name} = 1; // counter\nlet next = {name} + 1\n", name = "count");
+        assert_eq!(SCRIPT, "const count=1;let next=count+1");
+    }
+    
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_link_section_export() {
+        tomplate! {
+            #[link_section = ".rodata.tomplate_config"]
+            const CONFIG_BLOB = tomplate!("baud={rate}", rate = 9600);
+        }
+        
+        assert_eq!(&CONFIG_BLOB, b"baud=9600");
+    }
+    
+    #[test]
+    fn test_join_templates() {
+        use tomplate::tomplate_join;
+        
+        const UNION: &str = tomplate_join!(" UNION ALL ",
+            tomplate!("select_user", fields = "id", condition = "active"),
+            tomplate!("select_user", fields = "id", condition = "invited"),
+            "SELECT id FROM admins",
+        );
+        assert_eq!(
+            UNION,
+            "SELECT id FROM users WHERE active UNION ALL SELECT id FROM users WHERE invited UNION ALL SELECT id FROM admins"
+        );
+    }
+    
+    #[test]
+    fn test_feature_selection() {
+        use tomplate::tomplate_select;
+        
+        const SELECTED: &str = tomplate_select!(
+            handlebars => "SELECT {fields} FROM hbs_users",
+            cfg(test) => "select_user",
+            _ => "SELECT {fields} FROM fallback";
+            fields = "id",
+            condition = "id = $1"
+        );
+        #[cfg(not(feature = "handlebars"))]
+        assert_eq!(SELECTED, "SELECT id FROM users WHERE id = $1");
+        #[cfg(feature = "handlebars")]
+        assert_eq!(SELECTED, "SELECT id FROM hbs_users");
+    }
+    
+    #[test]
+    fn test_file_param() {
+        // Resolved next to queries.tomplate.toml, not this source file
+        const REPORT: &str = tomplate!("report_with_footer", body = file("snippets/footer.sql"));
+        assert_eq!(
+            REPORT,
+            "SELECT id, total FROM orders;\n-- generated by tomplate, do not edit\n"
+        );
+    }
+    
+    #[test]
+    fn test_template_references() {
+        const SELECT: &str = tomplate!("select_user", fields = @user_fields, condition = "id = $1");
+        assert_eq!(SELECT, "SELECT id, name, email FROM users WHERE id = $1");
+        
+        tomplate! {
+            const LISTED = tomplate!("SELECT {cols} FROM {table}", cols = @user_fields, table = @table_name);
+        }
+        assert_eq!(LISTED, "SELECT id, name, email FROM users");
+    }
+    
+    #[test]
+    fn test_param_transforms() {
+        const SELECT: &str = tomplate!("select_user", fields = upper(["id", "name"]), condition = snake_case(IsActive));
+        assert_eq!(SELECT, "SELECT ID, NAME FROM users WHERE is_active");
+        
+        tomplate! {
+            const DECLARATION = tomplate!("{name}: {table}",
+                name = screaming_snake_case(userTable),
+                table = kebab_case(snake_case("userTable"))
+            );
+        }
+        assert_eq!(DECLARATION, "USER_TABLE: user-table");
+        
+        const NESTED: &str = tomplate!("{name}", name = upper(snake_case(OrderItems)));
+        assert_eq!(NESTED, "ORDER_ITEMS");
+        
+        // In blocks, identifiers refer to let bindings where they exist
+        tomplate! {
+            let table = "UserAccounts";
+            const BOUND = tomplate!("{t}", t = snake_case(table));
+            const UNBOUND = tomplate!("{t}", t = snake_case(OrderItems));
+            const BOUND_NESTED = tomplate!("{t}", t = upper(kebab_case(table)));
+            const COLUMNS = tomplate!("{cols}", cols = pascal_case(["user_id", "created_at"]));
+        }
+        assert_eq!(BOUND, "user_accounts");
+        assert_eq!(UNBOUND, "order_items");
+        assert_eq!(BOUND_NESTED, "USER-ACCOUNTS");
+        assert_eq!(COLUMNS, "UserId, CreatedAt");
+    }
+    
+    #[test]
+    fn test_output_modifiers() {
+        const SELECT: &str = tomplate!(dedent trim "\n    SELECT {fields}\n    FROM users\n", fields = "id");
+        assert_eq!(SELECT, "SELECT id\nFROM users");
+        
+        // The indentation comes from the parameter, not the template
+        const BODY: &str = tomplate!(dedent "{body}", body = "  WHERE active\n    AND invited");
+        assert_eq!(BODY, "WHERE active\n  AND invited");
+        
+        tomplate! {
+            const TRIMMED = tomplate!(trim "select_user", fields = " id ", condition = "true ");
+        }
+        assert_eq!(TRIMMED, "SELECT  id  FROM users WHERE true");
+        
+        // Referenced templates are left alone, only the final output changes
+        const REFERENCED: &str = tomplate!(dedent trim "{@padded_clause}\nORDER BY id");
+        assert_eq!(REFERENCED, "WHERE active\n    AND invited  \nORDER BY id");
+    }
+}
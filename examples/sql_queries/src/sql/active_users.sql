@@ -0,0 +1 @@
+SELECT id FROM users WHERE active = true
\ No newline at end of file
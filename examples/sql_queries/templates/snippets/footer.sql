@@ -0,0 +1 @@
+-- generated by tomplate, do not edit
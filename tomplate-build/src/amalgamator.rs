@@ -1,44 +1,240 @@
-use crate::types::{Engine, Error, Result, Template};
-use std::collections::HashMap;
+use crate::types::{Error, IndexEntry, Result, Template};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
+use std::thread;
 
+/// Discovers the template files matching `patterns` and amalgamates them into
+/// the registry at `path`, with its index next to it, the way
+/// [`Builder::build`](crate::Builder::build) does but without any Cargo
+/// output or builder defaults.
+///
+/// Point `TOMPLATE_TEMPLATES_PATH` at `path` to render the registry's
+/// templates with `tomplate-engines`. Returns the discovered files.
+///
+/// # Errors
+///
+/// Returns an error if a pattern is invalid, a template file can't be parsed,
+/// a template name is defined twice, or the registry can't be written.
+pub fn write_registry<P: AsRef<Path>>(patterns: &[String], path: P) -> Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let template_files = crate::discovery::discover_templates(patterns)?;
+    
+    let mut out = std::io::BufWriter::new(fs::File::create(path)?);
+    let index = amalgamate_templates(&template_files, |_| {}, &[], path, &mut out)?;
+    out.flush()?;
+    fs::write(path.with_extension("index.json"), serde_json::to_string(&index)?)?;
+    
+    Ok(template_files)
+}
+
+/// Amalgamate the templates into `out`, the registry at `registry`,
+/// returning the location of each template in what was written
+///
+/// Each file's templates are written as soon as the file is parsed, so only
+/// the files being parsed are held in memory. With retain patterns, a first
+/// pass collects just the names and references needed to decide which
+/// templates to keep.
 pub fn amalgamate_templates(
-    template_files: &[impl AsRef<Path>], 
-    default_engine: Option<Engine>
-) -> Result<String> {
-    let mut all_templates: HashMap<String, Template> = HashMap::new();
-    
-    for file_path in template_files {
-        let file_path = file_path.as_ref();
-        let content = fs::read_to_string(file_path)?;
-        
-        // Parse the TOML file
-        let mut templates: HashMap<String, Template> = toml::from_str(&content)
-            .map_err(|e| {
-                eprintln!("Error parsing {}: {}", file_path.display(), e);
-                e
-            })?;
-        
-        // Apply default engine if not specified
-        if let Some(default) = default_engine {
-            for template in templates.values_mut() {
-                if template.engine.is_none() {
-                    template.engine = Some(default.to_string());
-                }
-            }
+    template_files: &[impl AsRef<Path> + Sync], 
+    apply_defaults: impl Fn(&mut Template),
+    retain: &[glob::Pattern],
+    registry: &Path,
+    out: &mut impl Write,
+) -> Result<BTreeMap<String, IndexEntry>> {
+    let profile = build_profile();
+    // Created by the caller, so its directory can be resolved
+    let registry = fs::canonicalize(registry)?;
+    let registry_dir = registry.parent().unwrap_or(&registry);
+    // Apply the build profile's variant, then the builder's defaults to
+    // settings the template leaves unset, sorted so output is deterministic
+    let prepare = |templates: HashMap<String, Template>| -> Result<BTreeMap<String, Template>> {
+        let mut templates: BTreeMap<String, Template> = templates.into_iter().collect();
+        for template in templates.values_mut() {
+            apply_profile(template, profile.as_deref())?;
+            apply_defaults(template);
         }
-        
-        // Merge templates, checking for duplicates
-        for (name, template) in templates {
-            if all_templates.contains_key(&name) {
+        Ok(templates)
+    };
+    
+    let retained = if retain.is_empty() {
+        None
+    } else {
+        let mut references = BTreeMap::new();
+        for_each_file(template_files, registry_dir, |templates| {
+            for (name, template) in prepare(templates)? {
+                references.insert(name, References::of(&template.template));
+            }
+            Ok(())
+        })?;
+        Some(retained(&references, retain))
+    };
+    
+    // Stream to TOML one template at a time, so each template's table can be
+    // located and parsed on its own
+    let mut names = HashSet::new();
+    let mut index = BTreeMap::new();
+    let mut offset = 0;
+    for_each_file(template_files, registry_dir, |templates| {
+        for (name, template) in prepare(templates)? {
+            if !names.insert(name.clone()) {
                 return Err(Error::DuplicateTemplate(name));
             }
-            all_templates.insert(name, template);
+            if retained.as_ref().is_some_and(|retained| !retained.contains(&name)) {
+                continue;
+            }
+            
+            let engine = template.engine.clone();
+            let table = BTreeMap::from([(name.as_str(), template)]);
+            let mut toml = toml::to_string_pretty(&table)?;
+            toml.push('\n');
+            out.write_all(toml.as_bytes())?;
+            
+            index.insert(name, IndexEntry { start: offset, end: offset + toml.len(), engine });
+            offset += toml.len();
         }
+        Ok(())
+    })?;
+    Ok(index)
+}
+
+/// Parse the template files in parallel, a batch at a time, passing each
+/// file's templates to `f` in file order so duplicates are reported
+/// deterministically
+fn for_each_file(
+    template_files: &[impl AsRef<Path> + Sync],
+    registry_dir: &Path,
+    mut f: impl FnMut(HashMap<String, Template>) -> Result<()>,
+) -> Result<()> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    
+    for batch in template_files.chunks(threads) {
+        let parsed = thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|file_path| scope.spawn(move || parse_file(file_path.as_ref(), registry_dir)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("template parsing thread panicked"))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        for templates in parsed {
+            f(templates)?;
+        }
+    }
+    Ok(())
+}
+
+/// The Cargo profile being built, `debug` or `release`
+fn build_profile() -> Option<String> {
+    if let Ok(profile) = std::env::var("PROFILE") {
+        return Some(profile);
     }
+    let debug = std::env::var("DEBUG").ok()?;
+    Some(if matches!(debug.as_str(), "false" | "0" | "none") { "release" } else { "debug" }.to_string())
+}
+
+/// Replace a template's keys with those of its variant for the profile
+fn apply_profile(template: &mut Template, profile: Option<&str>) -> Result<()> {
+    let profiles = std::mem::take(&mut template.profiles);
+    let Some(variant) = profile.and_then(|profile| profiles.get(profile)) else {
+        return Ok(());
+    };
+    
+    let mut table = toml::Table::try_from(&*template)?;
+    table.extend(variant.clone());
+    *template = table.try_into()?;
+    Ok(())
+}
+
+fn parse_file(file_path: &Path, registry_dir: &Path) -> Result<HashMap<String, Template>> {
+    let content = fs::read_to_string(file_path)?;
+    
+    // Parse the TOML file
+    let mut templates: HashMap<String, Template> = toml::from_str(&content)
+        .map_err(|e| {
+            eprintln!("Error parsing {}: {}", file_path.display(), e);
+            e
+        })?;
     
-    // Serialize back to TOML
-    let amalgamated = toml::to_string_pretty(&all_templates)?;
-    Ok(amalgamated)
-}
\ No newline at end of file
+    // Relative to the registry, so `file("...")` parameters resolve the same
+    // from any crate layering this registry without the registry recording
+    // where the build ran
+    let source_file = relative_to(&fs::canonicalize(file_path)?, registry_dir);
+    for template in templates.values_mut() {
+        template.source_file = Some(source_file.clone());
+    }
+    Ok(templates)
+}
+
+/// The path of `path` relative to the directory `base`, both absolute, or
+/// `path` itself if they share no root
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let common = path.components().zip(base.components()).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return path.to_path_buf();
+    }
+    let mut relative: PathBuf = base.components().skip(common).map(|_| Component::ParentDir).collect();
+    relative.extend(path.components().skip(common));
+    relative
+}
+
+/// What a template's text may refer to other templates by
+///
+/// References are detected conservatively: a template is kept if its name
+/// starts the text after an `@`, as in a `{@name}` reference, or is a quoted
+/// string, as in `{% include "name" %}` or `{{> "name"}}`.
+struct References {
+    /// The text after each `@`, up to whitespace
+    after_at: Vec<String>,
+    /// Each single-line string between quotes
+    quoted: HashSet<String>,
+}
+
+impl References {
+    fn of(text: &str) -> Self {
+        let after_at = text
+            .split('@')
+            .skip(1)
+            .map(|rest| rest.split(char::is_whitespace).next().unwrap_or_default().to_string())
+            .collect();
+        let mut quoted = HashSet::new();
+        for quote in ['"', '\''] {
+            let pieces: Vec<&str> = text.split(quote).collect();
+            if let [_, between @ .., _] = pieces.as_slice() {
+                quoted.extend(between.iter().filter(|piece| !piece.contains('\n')).map(|piece| piece.to_string()));
+            }
+        }
+        References { after_at, quoted }
+    }
+    
+    fn mention(&self, name: &str) -> bool {
+        self.quoted.contains(name) || self.after_at.iter().any(|rest| rest.starts_with(name))
+    }
+}
+
+/// Names of the templates matching a retain pattern, along with every
+/// template they may refer to
+fn retained(references: &BTreeMap<String, References>, patterns: &[glob::Pattern]) -> BTreeSet<String> {
+    let mut retained: BTreeSet<String> = BTreeSet::new();
+    let mut pending: Vec<&str> = references
+        .keys()
+        .filter(|name| patterns.iter().any(|pattern| pattern.matches(name)))
+        .map(String::as_str)
+        .collect();
+    
+    while let Some(name) = pending.pop() {
+        if !retained.insert(name.to_string()) {
+            continue;
+        }
+        for other in references.keys() {
+            if !retained.contains(other) && references[name].mention(other) {
+                pending.push(other);
+            }
+        }
+    }
+    retained
+}
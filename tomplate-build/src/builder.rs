@@ -1,7 +1,11 @@
-use crate::{amalgamator, discovery, types::{Engine, Result}};
+use crate::{amalgamator, discovery, openapi, types::{Engine, EngineConfig, Error, Result, Template}, SqlxReport, UsageReport};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// Build mode for template amalgamation.
 ///
@@ -61,9 +65,30 @@ pub enum BuildMode {
 #[derive(Default)]
 pub struct Builder {
     patterns: Vec<String>,
+    manifest_patterns: bool,
     output_dir: Option<PathBuf>,
     mode: BuildMode,
     default_engine: Option<Engine>,
+    strict: bool,
+    trim_blocks: Option<bool>,
+    lstrip_blocks: Option<bool>,
+    keep_trailing_newline: Option<bool>,
+    engine_configs: HashMap<&'static str, EngineConfig>,
+    eager_macros: Vec<String>,
+    custom_engines: BTreeMap<String, String>,
+    handlebars_helpers: BTreeMap<String, PathBuf>,
+    usage_log: bool,
+    expansion_cache: bool,
+    registry_layers: Vec<PathBuf>,
+    retain: Vec<String>,
+    profile: bool,
+    graphql_schema: Option<PathBuf>,
+    sqlx_offline: Option<PathBuf>,
+    rust_codegen: Vec<(PathBuf, Vec<String>)>,
+    openapi_paths: Option<PathBuf>,
+    completions_file: Option<PathBuf>,
+    rendered_files: Vec<(PathBuf, PathBuf)>,
+    globals: HashMap<String, Value>,
 }
 
 impl Builder {
@@ -135,6 +160,29 @@ impl Builder {
         self
     }
 
+    /// Adds the patterns listed under `[package.metadata.tomplate]` in the
+    /// crate's `Cargo.toml`.
+    ///
+    /// Keeping the patterns in the manifest lets tools like the `tomplate`
+    /// CLI discover the same template files as the build script.
+    ///
+    /// # Examples
+    ///
+    /// ```toml
+    /// [package.metadata.tomplate]
+    /// patterns = ["templates/**/*.tomplate.toml"]
+    /// ```
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_manifest_patterns()
+    ///     .build()?;
+    /// ```
+    pub fn add_manifest_patterns(mut self) -> Self {
+        self.manifest_patterns = true;
+        self
+    }
+
     /// Sets a custom output directory for the amalgamated template file.
     ///
     /// By default, the builder uses the `OUT_DIR` environment variable set by Cargo.
@@ -200,6 +248,536 @@ impl Builder {
         self
     }
 
+    /// Makes undefined variables a compile error for every template.
+    ///
+    /// The simple and Tera engines already reject undefined variables, while
+    /// Handlebars and MiniJinja render them as empty strings unless strict
+    /// mode is enabled. Templates can still opt out with `strict = false`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .strict(true)
+    ///     .build()?;
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the default for Jinja-style `trim_blocks` in Tera and MiniJinja
+    /// templates, removing the first newline after a block tag.
+    ///
+    /// Templates can override it with a `trim_blocks` field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .trim_blocks(true)
+    ///     .lstrip_blocks(true)
+    ///     .build()?;
+    /// ```
+    pub fn trim_blocks(mut self, enabled: bool) -> Self {
+        self.trim_blocks = Some(enabled);
+        self
+    }
+
+    /// Sets the default for Jinja-style `lstrip_blocks` in Tera and MiniJinja
+    /// templates, stripping whitespace before a block tag at the start of a
+    /// line.
+    ///
+    /// Templates can override it with an `lstrip_blocks` field.
+    pub fn lstrip_blocks(mut self, enabled: bool) -> Self {
+        self.lstrip_blocks = Some(enabled);
+        self
+    }
+
+    /// Sets whether Tera and MiniJinja output keeps the template's trailing
+    /// newline.
+    ///
+    /// Templates can override it with a `keep_trailing_newline` field.
+    pub fn keep_trailing_newline(mut self, enabled: bool) -> Self {
+        self.keep_trailing_newline = Some(enabled);
+        self
+    }
+
+    /// Sets the configuration for every template of an engine.
+    ///
+    /// The settings are written into the amalgamated registry for templates
+    /// using that engine, where the macros honor them when constructing the
+    /// engine. Settings in a template's own definition take precedence, and
+    /// engine settings take precedence over global ones like
+    /// [`strict`](Self::strict).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use tomplate_build::{Builder, Delimiters, Engine, EngineConfig};
+    ///
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .engine_config(Engine::Handlebars, EngineConfig {
+    ///         strict: Some(true),
+    ///         ..Default::default()
+    ///     })
+    ///     .engine_config(Engine::MiniJinja, EngineConfig {
+    ///         delimiters: Some(Delimiters {
+    ///             variable_start: Some("${".to_string()),
+    ///             variable_end: Some("}".to_string()),
+    ///             ..Default::default()
+    ///         }),
+    ///         ..Default::default()
+    ///     })
+    ///     .build()?;
+    /// ```
+    pub fn engine_config(mut self, engine: Engine, config: EngineConfig) -> Self {
+        self.engine_configs.insert(engine.as_str(), config);
+        self
+    }
+
+    /// Registers project-local macros that `tomplate_eager!` should expand.
+    ///
+    /// Each macro is treated as an alias of `tomplate!`, which is useful for
+    /// wrapper macros that forward their arguments to `tomplate!`. The names
+    /// are passed to the macro through the `TOMPLATE_EAGER_MACROS` variable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .eager_macros(["query_template", "fragment"])
+    ///     .build()?;
+    /// ```
+    pub fn eager_macros<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.eager_macros.extend(names.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Registers a custom template engine backed by an external command.
+    ///
+    /// Templates with `engine = "<name>"` are rendered by running the command
+    /// during macro expansion. The command receives a JSON object with
+    /// `template` and `params` fields on stdin and must write the rendered
+    /// output to stdout. A non-zero exit status fails compilation with the
+    /// command's stderr.
+    ///
+    /// The command is split on whitespace into a program and its arguments.
+    /// Program paths containing a separator are resolved against the crate's
+    /// manifest directory; bare names are looked up on `PATH`. Built-in engine
+    /// names cannot be overridden.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .custom_engine("mydsl", "python3 scripts/render.py")
+    ///     .build()?;
+    /// ```
+    ///
+    /// ```toml
+    /// [report]
+    /// template = "..."
+    /// engine = "mydsl"
+    /// ```
+    pub fn custom_engine<N, C>(mut self, name: N, command: C) -> Self
+    where
+        N: AsRef<str>,
+        C: AsRef<str>,
+    {
+        self.custom_engines
+            .insert(name.as_ref().to_string(), command.as_ref().to_string());
+        self
+    }
+
+    /// Registers a Handlebars helper implemented as a Rhai script.
+    ///
+    /// Handlebars templates can then call the helper by name, e.g.
+    /// `{{sql_ident table}}`. The script sees the helper's arguments as
+    /// `params` and its hash arguments as `hash`, and its result is rendered
+    /// in place. Relative paths are resolved against the crate's manifest
+    /// directory.
+    ///
+    /// Requires the `handlebars-scripts` feature of `tomplate`; without it
+    /// only Handlebars' built-in helpers are available.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .handlebars_helper("sql_ident", "helpers/sql_ident.rhai")
+    ///     .build()?;
+    /// ```
+    pub fn handlebars_helper<N, P>(mut self, name: N, path: P) -> Self
+    where
+        N: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        self.handlebars_helpers
+            .insert(name.as_ref().to_string(), path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Records which templates the macros use, and warns about unused ones.
+    ///
+    /// Every macro expansion that renders a registry template appends its
+    /// name, crate, file and line to `OUT_DIR/tomplate_usage.log`. When the
+    /// build script next runs, templates that no logged call site references
+    /// are reported as Cargo warnings; see [`UsageReport`] for the full
+    /// report including call sites per template.
+    ///
+    /// The log accumulates across builds, so call sites that were removed
+    /// stay in it until the log is deleted, e.g. by `cargo clean`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .usage_log(true)
+    ///     .build()?;
+    /// ```
+    pub fn usage_log(mut self, enabled: bool) -> Self {
+        self.usage_log = enabled;
+        self
+    }
+
+    /// Caches rendered templates on disk across builds.
+    ///
+    /// Macro expansions store their output under `target/<profile>/tomplate-cache`,
+    /// keyed by the template, engine, parameters and options, so unchanged
+    /// expansions skip engine work across incremental builds and between
+    /// `cargo check` and `cargo build`. Any change to the registry or to a
+    /// [Handlebars helper](Self::handlebars_helper) script invalidates the
+    /// entries. Templates rendered by [custom engines](Self::custom_engine)
+    /// are never cached on disk.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .expansion_cache(true)
+    ///     .build()?;
+    /// ```
+    pub fn expansion_cache(mut self, enabled: bool) -> Self {
+        self.expansion_cache = enabled;
+        self
+    }
+
+    /// Layers a prebuilt amalgamated registry under this crate's templates.
+    ///
+    /// The macros look templates up in this crate's registry first and then
+    /// in each layer in the order added, so a shared workspace registry can
+    /// be reused without re-amalgamating its templates into every crate.
+    /// Relative paths are resolved against the crate's manifest directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("templates/*.tomplate.toml")
+    ///     .registry_layer("../shared/target/tomplate_amalgamated.toml")
+    ///     .build()?;
+    /// ```
+    pub fn registry_layer<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.registry_layers.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Prunes the registry to the templates matching the given glob patterns.
+    ///
+    /// Templates referenced by a retained template, through `{@name}` or a
+    /// quoted name as in `{% include "name" %}`, are kept as well. Everything
+    /// else is left out of the amalgamated registry, which speeds up
+    /// expansion and keeps unused templates out of build artifacts. Without
+    /// any retain patterns, all templates are kept.
+    ///
+    /// The templates in use can be taken from a [`UsageReport`] of an earlier
+    /// build:
+    ///
+    /// ```rust,ignore
+    /// use tomplate_build::{Builder, UsageReport};
+    ///
+    /// let report = UsageReport::read("usage/tomplate_usage.log", "usage/tomplate_amalgamated.toml")?;
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .retain(report.used())
+    ///     .build()?;
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .retain(["public/*", "select_user"])
+    ///     .build()?;
+    /// ```
+    pub fn retain<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.retain.extend(patterns.into_iter().map(|s| s.as_ref().to_string()));
+        self
+    }
+
+    /// Reports how long template processing takes.
+    ///
+    /// The build script prints the time spent discovering and amalgamating
+    /// templates as Cargo warnings, and points the macros'
+    /// `TOMPLATE_PROFILE` variable at `OUT_DIR/tomplate_profile.txt`. There
+    /// the macros keep a summary of the expansions per macro, registry loads,
+    /// and renders per engine along with how many were served from a cache.
+    /// Setting `TOMPLATE_PROFILE` to a file path directly profiles the macros
+    /// without this option.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .profile(true)
+    ///     .build()?;
+    /// ```
+    pub fn profile(mut self, enabled: bool) -> Self {
+        self.profile = enabled;
+        self
+    }
+
+    /// Sets the GraphQL schema that templates with `validate = "graphql"`
+    /// are validated against.
+    ///
+    /// Rendered queries are then checked for unknown fields, bad arguments
+    /// and other errors at compile time; without a schema they are only
+    /// parsed. The path is resolved against the crate's manifest directory.
+    /// Requires the `graphql` feature of `tomplate`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .graphql_schema("schema.graphql")
+    ///     .build()?;
+    /// ```
+    pub fn graphql_schema<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.graphql_schema = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Checks that sqlx offline data covers the queries rendered into sqlx
+    /// macros.
+    ///
+    /// `tomplate_eager!` and `#[eager]` then log the query string of every
+    /// `query!`, `query_as!` and `query_scalar!` call they expand to
+    /// `OUT_DIR/tomplate_sqlx.log`. When the build script next runs, it
+    /// writes each logged query to `OUT_DIR/tomplate-sqlx/query-<hash>.sql`
+    /// and reports call sites whose `query-<hash>.json` is missing from
+    /// `dir` as Cargo warnings; see [`SqlxReport`]. The directory is resolved
+    /// against the crate's manifest directory, usually `.sqlx`.
+    ///
+    /// Because the macros receive the rendered string, `cargo sqlx prepare`
+    /// records offline data for templated queries like any other:
+    ///
+    /// 1. Run `cargo sqlx prepare` against a database and commit `.sqlx`.
+    /// 2. Build with `SQLX_OFFLINE=true` where no database is available.
+    /// 3. After editing a template, the warnings name the queries whose
+    ///    offline data is stale, before sqlx fails on them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .sqlx_offline(".sqlx")
+    ///     .build()?;
+    /// ```
+    pub fn sqlx_offline<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.sqlx_offline = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Generates a Rust source file under `OUT_DIR/dir` for each template
+    /// matching the given names or glob patterns, ready for `include!`.
+    ///
+    /// Each `dir/<name>.rs` holds a `tomplate_rust!` call, which renders the
+    /// template without parameters and parses its output as Rust, so
+    /// templates that generate enum tables, impls or match arms from data
+    /// can use any engine. Files are only rewritten when their contents
+    /// change.
+    ///
+    /// Include the files at item level, such as a module's top level or a
+    /// `mod` block. Inside a function body `include!` parses the file as an
+    /// expression, which items like `pub const` aren't.
+    ///
+    /// # Errors
+    ///
+    /// [`build`](Self::build) returns an error if a name or pattern matches
+    /// no template.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // build.rs
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .render_to_rust("codegen", ["status_codes", "codegen/*"])
+    ///     .build()?;
+    ///
+    /// // src/lib.rs
+    /// include!(concat!(env!("OUT_DIR"), "/codegen/status_codes.rs"));
+    /// ```
+    pub fn render_to_rust<P, I, S>(mut self, dir: P, templates: I) -> Self
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.rust_codegen.push((
+            dir.as_ref().to_path_buf(),
+            templates.into_iter().map(|s| s.as_ref().to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Writes an OpenAPI `paths` object built from templates marked as routes
+    /// to `OUT_DIR/path`.
+    ///
+    /// Each template with a [`route`](crate::Route) table becomes an
+    /// operation at its path, with its placeholders as path and query
+    /// parameters, so the spec follows the URL templates the code uses. The
+    /// output is JSON, for embedding in a served spec with `include_str!` or
+    /// comparing against a checked-in spec in a test.
+    ///
+    /// # Errors
+    ///
+    /// [`build`](Self::build) returns an error if two templates declare the
+    /// same method and path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // build.rs
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .openapi_paths("openapi_paths.json")
+    ///     .build()?;
+    ///
+    /// // src/main.rs
+    /// const PATHS: &str = include_str!(concat!(env!("OUT_DIR"), "/openapi_paths.json"));
+    /// ```
+    pub fn openapi_paths<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.openapi_paths = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Writes template names and parameters to a JSON file for editor
+    /// completions.
+    ///
+    /// The file maps each template name to its `engine`, its `params` and,
+    /// when the template has a `description` field, its `description`.
+    /// Simple templates without declared `params` list their placeholders.
+    /// The path is resolved against the crate's manifest directory, so an
+    /// editor extension can find it at a fixed location.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .completions_file("target/tomplate-completions.json")
+    ///     .build()?;
+    /// ```
+    pub fn completions_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.completions_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Renders whole files as templates during the build.
+    ///
+    /// Each source file, relative to the crate's manifest directory, is
+    /// rendered and written to its destination under `OUT_DIR` (or the
+    /// [`output_dir`](Self::output_dir)); absolute destinations are written
+    /// as given. Files are rendered with the values set by
+    /// [`global`](Self::global) and with the build script's environment as
+    /// `env`, e.g. `{env.CARGO_PKG_VERSION}`, and can refer to registry
+    /// templates.
+    ///
+    /// The engine follows the source's extension: `.hbs` and `.handlebars`
+    /// for Handlebars, `.tera` for Tera, `.jinja`, `.jinja2` and `.j2` for
+    /// MiniJinja, and otherwise the [`default_engine`](Self::default_engine)
+    /// or the simple engine. Engine settings apply as they do to registry
+    /// templates.
+    ///
+    /// Cargo reruns the build script when a source file or one of Cargo's
+    /// own variables changes; emit `cargo:rerun-if-env-changed` for other
+    /// variables the files read.
+    ///
+    /// # Errors
+    ///
+    /// [`build`](Self::build) returns an error if a source file can't be
+    /// read or fails to render.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // build.rs
+    /// Builder::new()
+    ///     .add_pattern("**/*.tomplate.toml")
+    ///     .global("server_name", "example.com")
+    ///     .render_files([("config/nginx.conf.tomplate", "nginx.conf")])
+    ///     .build()?;
+    ///
+    /// // src/main.rs
+    /// const NGINX_CONF: &str = include_str!(concat!(env!("OUT_DIR"), "/nginx.conf"));
+    /// ```
+    pub fn render_files<I, S, D>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = (S, D)>,
+        S: AsRef<Path>,
+        D: AsRef<Path>,
+    {
+        self.rendered_files.extend(
+            files
+                .into_iter()
+                .map(|(source, dest)| (source.as_ref().to_path_buf(), dest.as_ref().to_path_buf())),
+        );
+        self
+    }
+
+    /// Sets a value that files rendered with
+    /// [`render_files`](Self::render_files) can use.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// Builder::new()
+    ///     .global("workers", 4)
+    ///     .global("upstreams", ["10.0.0.1", "10.0.0.2"])
+    ///     .render_files([("nginx.conf.hbs", "nginx.conf")])
+    ///     .build()?;
+    /// ```
+    pub fn global<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<Value>,
+    {
+        self.globals.insert(key.into(), value.into());
+        self
+    }
+
     /// Builds and processes all discovered templates.
     ///
     /// This method:
@@ -208,7 +786,8 @@ impl Builder {
     /// 3. Applies the default engine if configured
     /// 4. Checks for duplicate template names
     /// 5. Amalgamates all templates into a single TOML file
-    /// 6. Writes the result to `OUT_DIR/tomplate_amalgamated.toml`
+    /// 6. Writes the result to `OUT_DIR/tomplate_amalgamated.toml`, with an
+    ///    index of where each template is in it
     ///
     /// # Errors
     ///
@@ -231,46 +810,386 @@ impl Builder {
     ///     }
     /// }
     /// ```
-    pub fn build(self) -> Result<()> {
+    pub fn build(mut self) -> Result<()> {
         let out_dir = self
             .output_dir
+            .take()
             .or_else(|| env::var_os("OUT_DIR").map(PathBuf::from))
             .expect("OUT_DIR not set and no output_dir specified");
 
+        // Settings passed to the macros, which files rendered here use too
+        let mut settings = HashMap::new();
+
+        if self.manifest_patterns {
+            let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default();
+            self.patterns.extend(crate::manifest::patterns(&manifest_dir)?);
+            println!("cargo:rerun-if-changed={}", manifest_dir.join("Cargo.toml").display());
+        }
+
         // Tell Cargo to rerun if any tomplate files change
         for pattern in &self.patterns {
             println!("cargo:rerun-if-changed={}", pattern);
         }
 
+        if !self.eager_macros.is_empty() {
+            println!(
+                "cargo:rustc-env=TOMPLATE_EAGER_MACROS={}",
+                self.eager_macros.join(",")
+            );
+        }
+
+        if !self.custom_engines.is_empty() {
+            rustc_env(&mut settings, "TOMPLATE_CUSTOM_ENGINES", &serde_json::to_string(&self.custom_engines)?);
+        }
+
+        if !self.handlebars_helpers.is_empty() {
+            let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default();
+            let helpers: BTreeMap<&String, PathBuf> = self
+                .handlebars_helpers
+                .iter()
+                .map(|(name, path)| (name, manifest_dir.join(path)))
+                .collect();
+            for path in helpers.values() {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+            rustc_env(&mut settings, "TOMPLATE_HANDLEBARS_HELPERS", &serde_json::to_string(&helpers)?);
+        }
+
+        if self.profile {
+            let profile_path = out_dir.join("tomplate_profile.txt");
+            println!("cargo:rustc-env=TOMPLATE_PROFILE={}", profile_path.display());
+            println!("cargo:warning=expansion profile: {}", profile_path.display());
+        }
+
+        if let Some(schema) = &self.graphql_schema {
+            let schema = env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(schema);
+            println!("cargo:rerun-if-changed={}", schema.display());
+            println!("cargo:rustc-env=TOMPLATE_GRAPHQL_SCHEMA={}", schema.display());
+        }
+
+        if let Some(offline_dir) = &self.sqlx_offline {
+            let offline_dir = env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(offline_dir);
+            let log_path = out_dir.join("tomplate_sqlx.log");
+            let report = SqlxReport::read(&log_path, &offline_dir)?;
+            report.write_queries(out_dir.join("tomplate-sqlx"))?;
+            if !report.missing.is_empty() {
+                println!(
+                    "cargo:warning={} sqlx quer{} without offline data in {}, run `cargo sqlx prepare`: {}",
+                    report.missing.len(),
+                    if report.missing.len() == 1 { "y" } else { "ies" },
+                    offline_dir.display(),
+                    report.missing.join(", ")
+                );
+            }
+            println!("cargo:rerun-if-changed={}", offline_dir.display());
+            println!("cargo:rustc-env=TOMPLATE_SQLX_LOG={}", log_path.display());
+        }
+
+        // Snippet files read by `file("...")` parameters, listed by the macros
+        // as they expand so the next build reruns when one changes
+        let tracked_path = out_dir.join("tomplate_tracked_files.txt");
+        if !tracked_path.exists() {
+            fs::write(&tracked_path, "")?;
+        }
+        for path in fs::read_to_string(&tracked_path)?.lines() {
+            if Path::new(path).exists() {
+                println!("cargo:rerun-if-changed={}", path);
+            }
+        }
+        println!("cargo:rerun-if-changed={}", tracked_path.display());
+        rustc_env(&mut settings, "TOMPLATE_TRACKED_FILES", &tracked_path.display().to_string());
+
         // Discover all template files
+        let start = Instant::now();
         let template_files = discovery::discover_templates(&self.patterns)?;
+        if self.profile {
+            println!(
+                "cargo:warning=discovered {} template file(s) in {:.2?}",
+                template_files.len(),
+                start.elapsed()
+            );
+        }
 
         if template_files.is_empty() {
             // No templates found, create empty constants
             Self::write_empty_templates(&out_dir)?;
+            if let Some(path) = &self.openapi_paths {
+                fs::write(out_dir.join(path), "{}")?;
+            }
+            if !self.registry_layers.is_empty() {
+                self.emit_templates_path(&mut settings, &out_dir.join("tomplate_amalgamated.toml"))?;
+            }
+            self.write_rendered_files(&out_dir, &settings)?;
             return Ok(());
         }
 
-        // Amalgamate all templates into a single TOML structure
-        let amalgamated = amalgamator::amalgamate_templates(&template_files, self.default_engine)?;
+        let retain = self
+            .retain
+            .iter()
+            .map(|pattern| glob::Pattern::new(pattern))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
 
-        // Write the amalgamated TOML file
+        // Amalgamate all templates into a single TOML file, streamed to
+        // disk, then write its index
+        let start = Instant::now();
         let toml_path = out_dir.join("tomplate_amalgamated.toml");
-        fs::write(&toml_path, &amalgamated)?;
+        let mut out = BufWriter::new(fs::File::create(&toml_path)?);
+        let index = amalgamator::amalgamate_templates(
+            &template_files,
+            |template| self.apply_defaults(template),
+            &retain,
+            &toml_path,
+            &mut out,
+        )?;
+        out.flush()?;
+        fs::write(
+            out_dir.join("tomplate_amalgamated.index.json"),
+            serde_json::to_string(&index)?,
+        )?;
+        if self.profile {
+            println!(
+                "cargo:warning=amalgamated {} template(s) in {:.2?}",
+                index.len(),
+                start.elapsed()
+            );
+        }
 
-        println!(
-            "cargo:rustc-env=TOMPLATE_TEMPLATES_PATH={}",
-            toml_path.display()
-        );
+        self.emit_templates_path(&mut settings, &toml_path)?;
+
+        for (dir, patterns) in &self.rust_codegen {
+            Self::write_rust_codegen(&out_dir.join(dir), patterns, index.keys())?;
+        }
+        self.write_rendered_files(&out_dir, &settings)?;
+
+        if self.openapi_paths.is_some() || self.completions_file.is_some() {
+            let templates: HashMap<String, Template> = toml::from_str(&fs::read_to_string(&toml_path)?)?;
+            if let Some(path) = &self.openapi_paths {
+                fs::write(
+                    out_dir.join(path),
+                    serde_json::to_string_pretty(&openapi::paths(&templates)?)?,
+                )?;
+            }
+            if let Some(path) = &self.completions_file {
+                Self::write_completions(path, &templates)?;
+            }
+        }
+
+        if self.expansion_cache {
+            // OUT_DIR is `target/<profile>/build/<crate>-<hash>/out`; cache in
+            // the profile directory so `check` and `build` share entries
+            let cache_dir = out_dir.ancestors().nth(3).unwrap_or(&out_dir).join("tomplate-cache");
+            println!("cargo:rustc-env=TOMPLATE_CACHE_DIR={}", cache_dir.display());
+        }
+
+        if self.usage_log {
+            let log_path = out_dir.join("tomplate_usage.log");
+            if log_path.exists() {
+                let report = UsageReport::read(&log_path, &toml_path)?;
+                if !report.unused.is_empty() {
+                    println!(
+                        "cargo:warning={} unused template(s): {}",
+                        report.unused.len(),
+                        report.unused.join(", ")
+                    );
+                }
+            }
+            println!("cargo:rustc-env=TOMPLATE_USAGE_LOG={}", log_path.display());
+        }
+
+        Ok(())
+    }
+
+    /// Pass the registry and its layers to the macros, in lookup order
+    fn emit_templates_path(&self, settings: &mut HashMap<String, String>, toml_path: &Path) -> Result<()> {
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        for layer in &self.registry_layers {
+            println!("cargo:rerun-if-changed={}", manifest_dir.join(layer).display());
+        }
+        
+        let paths = env::join_paths(
+            std::iter::once(toml_path.to_path_buf())
+                .chain(self.registry_layers.iter().map(|layer| manifest_dir.join(layer))),
+        )
+        .map_err(|e| Error::InvalidTemplate(format!("Invalid registry path: {}", e)))?;
+        rustc_env(settings, "TOMPLATE_TEMPLATES_PATH", &paths.to_string_lossy());
+        Ok(())
+    }
+
+    /// Fill in the settings a template leaves unset from the builder
+    fn apply_defaults(&self, template: &mut Template) {
+        if let Some(engine) = self.default_engine {
+            template.engine.get_or_insert_with(|| engine.to_string());
+        }
+        
+        let engine = template.engine.as_deref().unwrap_or("simple");
+        if let Some(config) = self.engine_configs.get(engine).cloned() {
+            template.strict = template.strict.or(config.strict);
+            template.trim_blocks = template.trim_blocks.or(config.trim_blocks);
+            template.lstrip_blocks = template.lstrip_blocks.or(config.lstrip_blocks);
+            template.keep_trailing_newline =
+                template.keep_trailing_newline.or(config.keep_trailing_newline);
+            template.delimiters = template.delimiters.take().or(config.delimiters);
+            template.escape = template.escape.or(config.escape);
+        }
+        
+        if self.strict {
+            template.strict.get_or_insert(true);
+        }
+        if template.trim_blocks.is_none() {
+            template.trim_blocks = self.trim_blocks;
+        }
+        if template.lstrip_blocks.is_none() {
+            template.lstrip_blocks = self.lstrip_blocks;
+        }
+        if template.keep_trailing_newline.is_none() {
+            template.keep_trailing_newline = self.keep_trailing_newline;
+        }
+    }
+
+    /// Render the files given to `render_files` into the output directory
+    fn write_rendered_files(&self, out_dir: &Path, settings: &HashMap<String, String>) -> Result<()> {
+        if self.rendered_files.is_empty() {
+            return Ok(());
+        }
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+
+        let mut params = self.globals.clone();
+        params.insert("env".to_string(), Value::Object(env::vars().map(|(key, value)| (key, value.into())).collect()));
+
+        for (source, dest) in &self.rendered_files {
+            let source_path = manifest_dir.join(source);
+            println!("cargo:rerun-if-changed={}", source_path.display());
+            if !source_path.is_file() {
+                return Err(Error::FileNotFound(source_path));
+            }
+
+            // Engines are named rather than matched as variants, since they may
+            // be disabled here; `process` reports a disabled one
+            let engine = match source.extension().and_then(|extension| extension.to_str()) {
+                Some("hbs" | "handlebars") => "handlebars",
+                Some("tera") => "tera",
+                Some("jinja" | "jinja2" | "j2") => "minijinja",
+                _ => self.default_engine.unwrap_or_default().as_str(),
+            };
+            let mut template: Template = serde_json::from_value(serde_json::json!({
+                "template": fs::read_to_string(&source_path)?,
+                "engine": engine,
+            }))?;
+            self.apply_defaults(&mut template);
+
+            let output = tomplate_engines::engines::process_with_settings(
+                engine,
+                &template.template,
+                &params,
+                &tomplate_engines::engines::Options::from_template(&template),
+                settings,
+            )
+            .map_err(|e| Error::EngineError(format!("{}: {}", source.display(), e)))?;
+
+            let path = out_dir.join(dest);
+            if fs::read_to_string(&path).ok().as_deref() != Some(output.as_str()) {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, output)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a `tomplate_rust!` call for each template matching the patterns
+    fn write_rust_codegen<'a>(
+        dir: &Path,
+        patterns: &[String],
+        names: impl Iterator<Item = &'a String> + Clone,
+    ) -> Result<()> {
+        for pattern in patterns {
+            let glob = glob::Pattern::new(pattern)?;
+            let mut matched = false;
+            for name in names.clone().filter(|name| glob.matches(name)) {
+                matched = true;
+                let path = dir.join(format!("{}.rs", name));
+                let source = format!(
+                    "// Generated by tomplate from the `{}` template\n::tomplate::tomplate_rust! {{ {:?} }}\n",
+                    name, name
+                );
+                if fs::read_to_string(&path).ok().as_deref() != Some(source.as_str()) {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(&path, source)?;
+                }
+            }
+            if !matched {
+                return Err(Error::TemplateNotFound(pattern.clone()));
+            }
+        }
 
         Ok(())
     }
 
+    /// Write the names, engines and parameters of the templates as JSON
+    fn write_completions(path: &Path, templates: &HashMap<String, Template>) -> Result<()> {
+        let completions: BTreeMap<&String, serde_json::Value> = templates
+            .iter()
+            .map(|(name, template)| {
+                let engine = template.engine.as_deref().unwrap_or("simple");
+                let params = if template.params.is_empty() && engine == "simple" {
+                    // Leave out `{@name}` references to other templates
+                    openapi::placeholders(&template.template)
+                        .1
+                        .into_iter()
+                        .filter(|param| !param.starts_with('@'))
+                        .collect()
+                } else {
+                    template.params.clone()
+                };
+                let mut entry = serde_json::json!({ "engine": engine, "params": params });
+                if let Some(description) = template.metadata.get("description").and_then(|d| d.as_str()) {
+                    entry["description"] = description.into();
+                }
+                (name, entry)
+            })
+            .collect();
+
+        let path = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&completions)?)?;
+        Ok(())
+    }
+
     fn write_empty_templates(out_dir: &Path) -> Result<()> {
-        // Write empty TOML file
+        // Write empty TOML file and index
         let toml_path = out_dir.join("tomplate_amalgamated.toml");
         fs::write(&toml_path, "")?;
+        fs::write(out_dir.join("tomplate_amalgamated.index.json"), "{}")?;
 
         Ok(())
     }
 }
+
+/// Pass a setting to the macros, and to the engines rendering files in this
+/// build script
+fn rustc_env(settings: &mut HashMap<String, String>, key: &str, value: &str) {
+    println!("cargo:rustc-env={}={}", key, value);
+    settings.insert(key.to_string(), value.to_string());
+}
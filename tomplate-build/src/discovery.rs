@@ -1,27 +1,30 @@
 use crate::types::Result;
 use glob::glob;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Build output, skipped unless a pattern names it explicitly
+const TARGET_DIR: &str = "target";
 
 pub fn discover_templates(patterns: &[String]) -> Result<Vec<PathBuf>> {
+    // Walk each pattern on its own thread, merging in pattern order
+    let matches = thread::scope(|scope| {
+        let handles: Vec<_> = patterns
+            .iter()
+            .map(|pattern| scope.spawn(move || discover_pattern(pattern)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("template discovery thread panicked"))
+            .collect::<Result<Vec<_>>>()
+    })?;
+    
     let mut template_files = Vec::new();
     let mut seen_paths = std::collections::HashSet::new();
-    
-    for pattern in patterns {
-        for entry in glob(pattern)? {
-            match entry {
-                Ok(path) => {
-                    // Only include files, not directories
-                    if path.is_file() {
-                        // Deduplicate paths
-                        if seen_paths.insert(path.clone()) {
-                            template_files.push(path);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Warning: Error reading path matching pattern '{}': {}", pattern, e);
-                }
-            }
+    for path in matches.into_iter().flatten() {
+        // Deduplicate paths
+        if seen_paths.insert(path.clone()) {
+            template_files.push(path);
         }
     }
     
@@ -29,4 +32,27 @@ pub fn discover_templates(patterns: &[String]) -> Result<Vec<PathBuf>> {
     template_files.sort();
     
     Ok(template_files)
-}
\ No newline at end of file
+}
+
+/// Files matching a single pattern
+fn discover_pattern(pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut template_files = Vec::new();
+    let skip_target = !Path::new(pattern.trim_start_matches("./")).starts_with(TARGET_DIR);
+    for entry in glob(pattern)? {
+        match entry {
+            Ok(path) => {
+                if skip_target && path.strip_prefix(".").unwrap_or(&path).starts_with(TARGET_DIR) {
+                    continue;
+                }
+                // Only include files, not directories
+                if path.is_file() {
+                    template_files.push(path);
+                }
+            }
+            Err(e) => {
+                eprintln!("Warning: Error reading path matching pattern '{}': {}", pattern, e);
+            }
+        }
+    }
+    Ok(template_files)
+}
@@ -35,7 +35,7 @@
 //! ```toml
 //! [template_name]
 //! template = "The template string with {placeholders}"
-//! engine = "simple"  # Optional: "simple", "handlebars", "tera", or "minijinja"
+//! engine = "simple"  # Optional: "simple", "handlebars", "tera", "minijinja", or "fluent"
 //!
 //! [another_template]
 //! template = """
@@ -47,7 +47,7 @@
 //! ## Advanced Configuration
 //!
 //! ```rust,ignore
-//! use tomplate_build::{Builder, Engine};
+//! use tomplate_build::{Builder, Engine, EngineConfig};
 //!
 //! fn main() {
 //!     Builder::new()
@@ -58,12 +58,51 @@
 //!         .add_patterns(vec!["config/*.toml", "queries/*.toml"])
 //!         // Set a default engine for templates without explicit engine
 //!         .default_engine(Engine::Handlebars)
+//!         // Configure every template of an engine
+//!         .engine_config(Engine::Handlebars, EngineConfig {
+//!             strict: Some(true),
+//!             ..Default::default()
+//!         })
 //!         // Build and generate the amalgamated file
 //!         .build()
 //!         .expect("Failed to build templates");
 //! }
 //! ```
 //!
+//! ## Custom Engines
+//!
+//! Templates can be rendered by an in-house engine through an external
+//! command that reads the template and parameters as JSON on stdin:
+//!
+//! ```rust,ignore
+//! Builder::new()
+//!     .add_pattern("**/*.tomplate.toml")
+//!     .custom_engine("mydsl", "python3 scripts/render.py")
+//!     .build()
+//!     .expect("Failed to build templates");
+//! ```
+//!
+//! ## Usage Reports
+//!
+//! With [`Builder::usage_log`] enabled, every macro expansion that uses a
+//! registry template appends to a log in `OUT_DIR`, and the next build warns
+//! about templates that no call site references. [`UsageReport`] reads the
+//! log for a full report, including how many call sites use each template.
+//!
+//! ## Rendered Files
+//!
+//! [`Builder::render_files`] renders whole files, such as configuration for
+//! other tools, into `OUT_DIR` during the build, with values set by
+//! [`Builder::global`] and the build script's environment:
+//!
+//! ```rust,ignore
+//! Builder::new()
+//!     .global("server_name", "example.com")
+//!     .render_files([("config/nginx.conf.tomplate", "nginx.conf")])
+//!     .build()
+//!     .expect("Failed to build templates");
+//! ```
+//!
 //! ## Error Handling
 //!
 //! The builder will fail if:
@@ -81,12 +120,16 @@
 mod amalgamator;
 mod builder;
 mod discovery;
+mod manifest;
+mod openapi;
+mod sqlx;
+mod usage;
 
 /// Types used throughout the build system.
 ///
 /// This module contains the core types used by the build system including
 /// template definitions, error types, and engine specifications.
-pub mod types;
+pub use tomplate_engines::types;
 
 /// The main builder for discovering and processing templates.
 ///
@@ -111,6 +154,67 @@ pub use types::Error;
 /// Result type alias for build operations.
 pub use types::Result;
 
+/// Settings applied to every template of an engine.
+///
+/// See [`EngineConfig`] for the available settings.
+pub use types::EngineConfig;
+
+/// Custom delimiters for Jinja-style templates.
+pub use types::Delimiters;
+
+/// Escaping applied to substituted values.
+pub use types::Escape;
+
+/// Bind placeholder style of rendered SQL.
+pub use types::Placeholders;
+
+/// Checks applied to rendered output.
+pub use types::Validate;
+
+/// An HTTP route described by a template.
+pub use types::Route;
+
+/// Formats rendered output can be validated against.
+pub use types::Format;
+
+/// Unused templates and call sites per template, from the usage log.
+///
+/// See [`UsageReport`] for details.
+pub use usage::UsageReport;
+
+/// Rendered sqlx queries and which of them lack offline data.
+///
+/// See [`SqlxReport`] for details.
+pub use sqlx::SqlxReport;
+
+/// Discovers templates and writes them to a registry, for tools that render
+/// templates outside a build script.
+///
+/// See [`write_registry`] for details.
+pub use amalgamator::write_registry;
+
+/// Reads the discovery patterns a crate lists in its `Cargo.toml`.
+///
+/// Templates are discovered with the patterns under
+/// `[package.metadata.tomplate]`, so build scripts using
+/// [`Builder::add_manifest_patterns`] and the `tomplate` CLI agree on which
+/// files make up the registry:
+///
+/// ```toml
+/// [package.metadata.tomplate]
+/// patterns = ["templates/**/*.tomplate.toml"]
+/// ```
+///
+/// Returns no patterns if the table doesn't list any.
+///
+/// # Errors
+///
+/// Returns an error if `Cargo.toml` can't be read or parsed, or `patterns`
+/// isn't an array of strings.
+pub fn manifest_patterns<P: AsRef<std::path::Path>>(manifest_dir: P) -> Result<Vec<String>> {
+    manifest::patterns(manifest_dir.as_ref())
+}
+
 /// Template definition structure.
 ///
 /// See [`Template`] for template structure details.
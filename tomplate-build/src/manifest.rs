@@ -0,0 +1,33 @@
+//! Template discovery settings read from the crate manifest.
+
+use crate::types::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+/// Read the patterns listed under `[package.metadata.tomplate]` in the
+/// `Cargo.toml` of `manifest_dir`, or none if the table doesn't set them
+pub fn patterns(manifest_dir: &Path) -> Result<Vec<String>> {
+    let manifest: toml::Value = toml::from_str(&fs::read_to_string(manifest_dir.join("Cargo.toml"))?)?;
+    let Some(patterns) = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("tomplate"))
+        .and_then(|tomplate| tomplate.get("patterns"))
+    else {
+        return Ok(Vec::new());
+    };
+    
+    patterns
+        .as_array()
+        .and_then(|patterns| {
+            patterns
+                .iter()
+                .map(|pattern| pattern.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| {
+            Error::InvalidTemplate(
+                "`package.metadata.tomplate.patterns` must be an array of strings".to_string(),
+            )
+        })
+}
@@ -0,0 +1,97 @@
+//! OpenAPI paths generated from templates marked as routes.
+
+use crate::types::{Error, Result, Template};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Build an OpenAPI `paths` object from the templates with a `route` table
+pub fn paths(templates: &HashMap<String, Template>) -> Result<Value> {
+    let mut names: Vec<&String> = templates.keys().collect();
+    names.sort();
+
+    let mut paths = Map::new();
+    for name in names {
+        let template = &templates[name];
+        let Some(route) = &template.route else {
+            continue;
+        };
+
+        let (path, query) = template
+            .template
+            .split_once('?')
+            .unwrap_or((template.template.as_str(), ""));
+        let (path, path_params) = placeholders(path);
+        let (_, query_params) = placeholders(query);
+
+        let mut parameters = Vec::new();
+        for (location, params) in [("path", path_params), ("query", query_params)] {
+            for param in params {
+                parameters.push(json!({
+                    "name": param,
+                    "in": location,
+                    "required": location == "path",
+                    "schema": { "type": "string" },
+                }));
+            }
+        }
+
+        let mut operation = match serde_json::to_value(&route.operation)? {
+            Value::Object(operation) => operation,
+            _ => Map::new(),
+        };
+        operation.entry("operationId").or_insert_with(|| name.as_str().into());
+        if let Some(summary) = &route.summary {
+            operation.insert("summary".to_string(), summary.as_str().into());
+        }
+        if !route.tags.is_empty() {
+            operation.insert("tags".to_string(), json!(route.tags));
+        }
+        if !parameters.is_empty() {
+            operation.entry("parameters").or_insert(Value::Array(parameters));
+        }
+
+        let method = route.method.to_lowercase();
+        let item = paths
+            .entry(path.clone())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .expect("path items are objects");
+        if item.contains_key(&method) {
+            return Err(Error::InvalidTemplate(format!(
+                "Duplicate route {} {} in template '{}'",
+                method.to_uppercase(),
+                path,
+                name
+            )));
+        }
+        item.insert(method, Value::Object(operation));
+    }
+
+    Ok(Value::Object(paths))
+}
+
+/// Strip filters from the `{name|filter}` placeholders of a path, returning
+/// it with the placeholder names in order of first appearance
+pub(crate) fn placeholders(text: &str) -> (String, Vec<String>) {
+    let mut stripped = String::with_capacity(text.len());
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let name = rest[start + 1..start + end].split('|').next().unwrap_or_default().trim();
+        stripped.push_str(&rest[..start]);
+        stripped.push('{');
+        stripped.push_str(name);
+        stripped.push('}');
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + end + 1..];
+    }
+    stripped.push_str(rest);
+
+    (stripped, names)
+}
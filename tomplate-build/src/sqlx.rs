@@ -0,0 +1,123 @@
+//! Checks of sqlx offline data against the queries rendered into sqlx macros.
+
+use crate::types::Result;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Queries rendered into `sqlx::query!` and related macros, and which of
+/// them have no offline data.
+///
+/// Built from the log that `tomplate_eager!` and `#[eager]` append to when
+/// [`Builder::sqlx_offline`](crate::Builder::sqlx_offline) is enabled. Each
+/// log line records a call site and the query string sqlx received there.
+/// sqlx names offline data `query-<hash>.json` after the SHA-256 of that
+/// string, so a query is missing when no file with its hash exists.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate_build::SqlxReport;
+///
+/// let report = SqlxReport::read(
+///     "target/debug/build/app-1234/out/tomplate_sqlx.log",
+///     ".sqlx",
+/// )?;
+/// println!("{}", report);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SqlxReport {
+    /// Rendered queries by call site, written as `file:line:index`.
+    pub queries: BTreeMap<String, String>,
+
+    /// Call sites whose query has no offline data, sorted.
+    pub missing: Vec<String>,
+}
+
+impl SqlxReport {
+    /// Builds a report from the contents of a query log and the directory
+    /// `cargo sqlx prepare` writes to.
+    ///
+    /// Later entries for a call site replace earlier ones, so a log that
+    /// accumulates over incremental builds reports each call site's current
+    /// query. Malformed lines are ignored.
+    pub fn new(log: &str, offline_dir: &Path) -> Self {
+        let mut queries = BTreeMap::new();
+        for line in log.lines() {
+            if let Ok((site, query)) = serde_json::from_str::<(String, String)>(line) {
+                queries.insert(site, query);
+            }
+        }
+
+        let missing = queries
+            .iter()
+            .filter(|(_, query)| {
+                !offline_dir
+                    .join(format!("query-{}.json", query_hash(query)))
+                    .exists()
+            })
+            .map(|(site, _)| site.clone())
+            .collect();
+
+        Self { queries, missing }
+    }
+
+    /// Reads a query log and checks it against an offline data directory.
+    ///
+    /// A missing log is treated as empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log exists but cannot be read.
+    pub fn read<L: AsRef<Path>, D: AsRef<Path>>(log_path: L, offline_dir: D) -> Result<Self> {
+        let log = match fs::read_to_string(log_path) {
+            Ok(log) => log,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self::new(&log, offline_dir.as_ref()))
+    }
+
+    /// Writes each query to `dir/query-<hash>.sql`, named like the offline
+    /// data sqlx keeps for it, replacing the directory's previous contents.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be recreated or written.
+    pub fn write_queries<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+        fs::create_dir_all(dir)?;
+        for query in self.queries.values() {
+            fs::write(dir.join(format!("query-{}.sql", query_hash(query))), query)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SqlxReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rendered sqlx queries ({}):", self.queries.len())?;
+        for (site, query) in &self.queries {
+            writeln!(f, "  {}  {}", query_hash(query), site)?;
+        }
+        writeln!(f, "Without offline data ({}):", self.missing.len())?;
+        for site in &self.missing {
+            writeln!(f, "  {}", site)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the hash sqlx names a query's offline data by: the hex SHA-256
+/// of the query string.
+pub fn query_hash(query: &str) -> String {
+    Sha256::digest(query.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
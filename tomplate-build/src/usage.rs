@@ -0,0 +1,112 @@
+//! Reports over the template usage log written during macro expansion.
+
+use crate::types::{Result, Template};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Which registry templates are referenced, and from how many call sites.
+///
+/// Built from the usage log that the macros append to when
+/// [`Builder::usage_log`](crate::Builder::usage_log) is enabled. Each log
+/// line records a template name, the crate, and the file and line of the
+/// macro call that used it.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate_build::UsageReport;
+///
+/// let report = UsageReport::read(
+///     "target/debug/build/app-1234/out/tomplate_usage.log",
+///     "target/debug/build/app-1234/out/tomplate_amalgamated.toml",
+/// )?;
+/// println!("{}", report);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UsageReport {
+    /// Registry templates never referenced by a macro call, sorted by name.
+    pub unused: Vec<String>,
+    
+    /// Referenced templates with their number of distinct call sites, most
+    /// used first.
+    pub call_sites: Vec<(String, usize)>,
+}
+
+impl UsageReport {
+    /// Builds a report from the contents of a usage log and the registry's
+    /// template names.
+    ///
+    /// Repeated entries for the same call site are counted once, so a log
+    /// that accumulates over incremental builds still reports call sites
+    /// rather than expansions. Templates in the log but not in the registry
+    /// are ignored.
+    pub fn new<'a, I>(log: &str, names: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut sites: BTreeMap<&str, BTreeSet<(&str, &str, &str)>> = BTreeMap::new();
+        for line in log.lines() {
+            let mut fields = line.split('\t');
+            if let (Some(name), Some(krate), Some(file), Some(line)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            {
+                sites.entry(name).or_default().insert((krate, file, line));
+            }
+        }
+        
+        let mut unused = Vec::new();
+        let mut call_sites = Vec::new();
+        for name in names {
+            match sites.get(name) {
+                Some(sites) => call_sites.push((name.to_string(), sites.len())),
+                None => unused.push(name.to_string()),
+            }
+        }
+        unused.sort();
+        call_sites.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        
+        Self { unused, call_sites }
+    }
+    
+    /// Returns the names of the referenced templates, e.g. to
+    /// [`retain`](crate::Builder::retain) only those.
+    pub fn used(&self) -> impl Iterator<Item = &str> {
+        self.call_sites.iter().map(|(name, _)| name.as_str())
+    }
+    
+    /// Reads a usage log and the amalgamated registry it should be compared
+    /// against.
+    ///
+    /// A missing log is treated as empty, so every template is reported as
+    /// unused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either file cannot be read, or if the registry is
+    /// not valid TOML.
+    pub fn read<L: AsRef<Path>, R: AsRef<Path>>(log_path: L, registry_path: R) -> Result<Self> {
+        let log = match fs::read_to_string(log_path) {
+            Ok(log) => log,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let registry: HashMap<String, Template> = toml::from_str(&fs::read_to_string(registry_path)?)?;
+        Ok(Self::new(&log, registry.keys().map(String::as_str)))
+    }
+}
+
+impl fmt::Display for UsageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Call sites per template:")?;
+        for (name, count) in &self.call_sites {
+            writeln!(f, "  {:>5}  {}", count, name)?;
+        }
+        writeln!(f, "Unused templates ({}):", self.unused.len())?;
+        for name in &self.unused {
+            writeln!(f, "  {}", name)?;
+        }
+        Ok(())
+    }
+}
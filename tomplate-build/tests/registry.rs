@@ -0,0 +1,272 @@
+//! Builds registries from scratch template files and reads them back the way
+//! the macros do.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tomplate_build::Builder;
+use tomplate_engines::{settings, templates};
+
+/// A fresh scratch directory holding the given template files
+fn scratch(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("registry").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        fs::write(dir.join(file), contents).unwrap();
+    }
+    dir
+}
+
+/// Amalgamate the template files of a scratch directory into its `out`
+/// directory, returning the registry's path
+fn build(dir: &Path, configure: impl FnOnce(Builder) -> Builder) -> PathBuf {
+    fs::create_dir_all(dir.join("out")).unwrap();
+    let builder = Builder::new()
+        .add_pattern(format!("{}/*.tomplate.toml", dir.display()))
+        .output_dir(dir.join("out"));
+    configure(builder).build().unwrap();
+    dir.join("out/tomplate_amalgamated.toml")
+}
+
+/// Run `f` with the registries the macros would see, in lookup order
+fn with_registries<T>(registries: &[&Path], f: impl FnOnce() -> T) -> T {
+    let paths = std::env::join_paths(registries).unwrap();
+    let settings = HashMap::from([(
+        "TOMPLATE_TEMPLATES_PATH".to_string(),
+        paths.to_string_lossy().into_owned(),
+    )]);
+    settings::scoped(&settings, f)
+}
+
+/// The body of a registry template
+fn body(name: &str) -> Option<String> {
+    templates::get(name).map(|template| template.template.clone())
+}
+
+#[test]
+fn test_usage_report() {
+    let log = "select_user\tapp\tsrc/main.rs\t10\n\
+               select_user\tapp\tsrc/main.rs\t10\n\
+               select_user\tapp\tsrc/db.rs\t4\n\
+               user_fields\tapp\tsrc/main.rs\t12\n\
+               removed\tapp\tsrc/main.rs\t20\n\
+               truncated line\n";
+    let report = tomplate_build::UsageReport::new(log, ["user_fields", "select_user", "unused_b", "unused_a"]);
+    // Repeated call sites count once, and names missing from the registry are ignored
+    assert_eq!(report.call_sites, [("select_user".to_string(), 2), ("user_fields".to_string(), 1)]);
+    assert_eq!(report.unused, ["unused_a", "unused_b"]);
+    assert_eq!(report.used().collect::<Vec<_>>(), ["select_user", "user_fields"]);
+    
+    // Without a log, every template is unused
+    let dir = scratch("usage", &[("a.tomplate.toml", "[one]\ntemplate = \"1\"\n[two]\ntemplate = \"2\"")]);
+    let registry = build(&dir, |builder| builder.usage_log(true));
+    let report = tomplate_build::UsageReport::read(dir.join("out/tomplate_usage.log"), &registry).unwrap();
+    assert_eq!(report.unused, ["one", "two"]);
+    assert!(report.call_sites.is_empty());
+}
+
+#[test]
+fn test_templates_are_shared() {
+    let dir = scratch("shared", &[("a.tomplate.toml", "[one]\ntemplate = \"1\"")]);
+    let registry = build(&dir, |builder| builder);
+    with_registries(&[&registry], || {
+        let first = templates::get("one").unwrap();
+        let second = templates::get("one").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&first, &second));
+    });
+}
+
+#[test]
+fn test_indexed_registry() {
+    let dir = scratch(
+        "indexed",
+        &[(
+            "a.tomplate.toml",
+            "[\"users/select\"]\ntemplate = \"SELECT 1\"\n\
+             [\"users/delete\"]\ntemplate = \"DELETE\"\n\
+             [greeting]\ntemplate = \"Hi {{name}}\"\nengine = \"handlebars\"",
+        )],
+    );
+    let registry = build(&dir, |builder| builder);
+    let index: HashMap<String, tomplate_build::types::IndexEntry> =
+        serde_json::from_str(&fs::read_to_string(dir.join("out/tomplate_amalgamated.index.json")).unwrap()).unwrap();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index["greeting"].engine.as_deref(), Some("handlebars"));
+    
+    with_registries(&[&registry], || {
+        assert_eq!(templates::names(), ["greeting", "users/delete", "users/select"]);
+        assert_eq!(templates::names_for_engine("handlebars"), ["greeting"]);
+        assert_eq!(templates::matching_names("users/").unwrap(), ["users/delete", "users/select"]);
+        assert_eq!(templates::matching_names("*/sel*").unwrap(), ["users/select"]);
+        assert_eq!(body("users/select").as_deref(), Some("SELECT 1"));
+        assert_eq!(body("missing"), None);
+    });
+}
+
+#[test]
+fn test_layered_registries() {
+    let shared = scratch(
+        "layers-shared",
+        &[("shared.tomplate.toml", "[header]\ntemplate = \"shared header\"\n[footer]\ntemplate = \"shared footer\"")],
+    );
+    let shared = build(&shared, |builder| builder);
+    let local = scratch("layers-local", &[("local.tomplate.toml", "[header]\ntemplate = \"local header\"")]);
+    let local = build(&local, |builder| builder);
+    
+    // Earlier registries shadow later ones
+    with_registries(&[&local, &shared], || {
+        assert_eq!(templates::names(), ["footer", "header"]);
+        assert_eq!(body("header").as_deref(), Some("local header"));
+        assert_eq!(body("footer").as_deref(), Some("shared footer"));
+    });
+    with_registries(&[&shared, &local], || {
+        assert_eq!(body("header").as_deref(), Some("shared header"));
+    });
+    
+    // A missing layer leaves the registry unavailable
+    let missing = local.with_file_name("missing.toml");
+    with_registries(&[&local, &missing], || assert!(!templates::available()));
+    with_registries(&[&local, &shared], || assert!(templates::available()));
+}
+
+#[test]
+fn test_registry_reloads_when_changed() {
+    let dir = scratch("reload", &[("a.tomplate.toml", "[query]\ntemplate = \"SELECT 1\"")]);
+    let registry = build(&dir, |builder| builder);
+    let fingerprint = with_registries(&[&registry], || {
+        assert_eq!(body("query").as_deref(), Some("SELECT 1"));
+        templates::fingerprint()
+    });
+    
+    fs::write(dir.join("a.tomplate.toml"), "[query]\ntemplate = \"SELECT id, name FROM users\"\n[added]\ntemplate = \"\"").unwrap();
+    build(&dir, |builder| builder);
+    with_registries(&[&registry], || {
+        assert_eq!(body("query").as_deref(), Some("SELECT id, name FROM users"));
+        assert_eq!(templates::names(), ["added", "query"]);
+        assert_ne!(templates::fingerprint(), fingerprint);
+    });
+}
+
+#[test]
+fn test_retain() {
+    let dir = scratch(
+        "retain",
+        &[(
+            "a.tomplate.toml",
+            "[\"public/users\"]\ntemplate = \"SELECT {@fields} FROM users\"\n\
+             [\"public/page\"]\ntemplate = \"{% include \\\"layout\\\" %}\"\nengine = \"minijinja\"\n\
+             [fields]\ntemplate = \"{@more_fields}, name\"\n\
+             [more_fields]\ntemplate = \"id\"\n\
+             [layout]\ntemplate = \"<html></html>\"\n\
+             [internal]\ntemplate = \"unused\"",
+        ), (
+            "b.tomplate.toml",
+            "[\"public/footer\"]\ntemplate = \"{@copyright}\"\n\
+             [copyright]\ntemplate = \"(c) {@more_fields}\"",
+        )],
+    );
+    // Templates referenced by retained ones are kept, transitively and
+    // across files
+    let registry = build(&dir, |builder| builder.retain(["public/*"]));
+    with_registries(&[&registry], || {
+        assert_eq!(
+            templates::names(),
+            ["copyright", "fields", "layout", "more_fields", "public/footer", "public/page", "public/users"]
+        );
+    });
+    
+    // Without patterns everything is kept
+    let registry = build(&dir, |builder| builder);
+    with_registries(&[&registry], || assert_eq!(templates::names().len(), 8));
+    
+    // Templates left out are still checked for duplicates
+    fs::write(dir.join("c.tomplate.toml"), "[internal]\ntemplate = \"again\"").unwrap();
+    let error = Builder::new()
+        .add_pattern(format!("{}/*.tomplate.toml", dir.display()))
+        .output_dir(dir.join("out"))
+        .retain(["public/*"])
+        .build()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Duplicate template name: internal");
+}
+
+#[test]
+fn test_discovery_of_many_files() {
+    let files: Vec<(String, String)> = (0..40)
+        .map(|file| {
+            let templates: String = (0..5)
+                .map(|index| format!("[t{}_{}]\ntemplate = \"{}\"\n", file, index, file * 5 + index))
+                .collect();
+            (format!("f{:02}.tomplate.toml", file), templates)
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = files.iter().map(|(name, body)| (name.as_str(), body.as_str())).collect();
+    let dir = scratch("many", &files);
+    
+    // Overlapping patterns find each file once
+    let registry = build(&dir, |builder| builder.add_pattern(format!("{}/f0*.tomplate.toml", dir.display())));
+    with_registries(&[&registry], || {
+        assert_eq!(templates::names().len(), 200);
+        assert_eq!(body("t37_4").as_deref(), Some("189"));
+    });
+    
+    // Duplicates are reported whichever thread parses them
+    fs::write(dir.join("g.tomplate.toml"), "[t12_3]\ntemplate = \"again\"").unwrap();
+    let error = Builder::new()
+        .add_pattern(format!("{}/*.tomplate.toml", dir.display()))
+        .output_dir(dir.join("out"))
+        .build()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Duplicate template name: t12_3");
+}
+
+#[test]
+fn test_streamed_registry() {
+    // Bodies that look like TOML tables must not confuse the index
+    let source = "[tricky]\ntemplate = \"\"\"\n[not_a_table]\ntemplate = \"x\"\n\"\"\"\n\
+                  [unicode]\ntemplate = \"Grüße, {name} ✓\"\n\
+                  [\"dotted.name\"]\ntemplate = \"quoted key\"\n";
+    let dir = scratch("streamed", &[("a.tomplate.toml", source)]);
+    let registry = dir.join("out/registry.toml");
+    fs::create_dir_all(registry.parent().unwrap()).unwrap();
+    let files = tomplate_build::write_registry(&[format!("{}/*.tomplate.toml", dir.display())], &registry).unwrap();
+    assert_eq!(files, [dir.join("a.tomplate.toml")]);
+    
+    // Each indexed range holds exactly its template's table
+    let text = fs::read_to_string(&registry).unwrap();
+    let index: HashMap<String, tomplate_build::types::IndexEntry> =
+        serde_json::from_str(&fs::read_to_string(registry.with_extension("index.json")).unwrap()).unwrap();
+    assert_eq!(index.len(), 3);
+    for (name, entry) in &index {
+        let table: toml::Table = toml::from_str(&text[entry.start..entry.end]).unwrap();
+        assert_eq!(table.keys().collect::<Vec<_>>(), [name]);
+    }
+    
+    with_registries(&[&registry], || {
+        assert_eq!(body("tricky").as_deref(), Some("[not_a_table]\ntemplate = \"x\"\n"));
+        assert_eq!(body("unicode").as_deref(), Some("Grüße, {name} ✓"));
+        assert_eq!(body("dotted.name").as_deref(), Some("quoted key"));
+        assert_eq!(body("not_a_table"), None);
+    });
+}
+
+#[test]
+fn test_source_file_is_relative() {
+    let dir = scratch("source_file", &[("a.tomplate.toml", "[greeting]\ntemplate = \"Hi\"\n")]);
+    let registry = build(&dir, |builder| builder);
+    
+    // The registry doesn't record where it was built
+    let text = fs::read_to_string(&registry).unwrap();
+    assert!(text.contains("source_file = \"../a.tomplate.toml\""), "{}", text);
+    assert!(!text.contains(env!("CARGO_TARGET_TMPDIR")), "{}", text);
+    
+    // Templates resolve it against the registry they're read from
+    with_registries(&[&registry], || {
+        let template = templates::get("greeting").unwrap();
+        assert_eq!(
+            fs::canonicalize(template.source_file.as_ref().unwrap()).unwrap(),
+            fs::canonicalize(dir.join("a.tomplate.toml")).unwrap()
+        );
+    });
+}
@@ -0,0 +1,156 @@
+//! `tomplate check`: lint the registry without compiling the crate.
+//!
+//! Discovery and duplicate detection happen while the project loads. Each
+//! template is then parsed by its engine, its declared `params` are compared
+//! with the placeholders it uses, and templates that render without
+//! parameters are run through their `validate` checks. Templates that need
+//! parameters are validated where they're called, when the crate compiles.
+
+use crate::project::Project;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use tomplate_build::Template;
+use tomplate_engines::engines::{self, simple, Options};
+use tomplate_engines::{templates, validate};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Fail on warnings as well as errors
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+/// How serious a finding is
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Error,
+    Warning,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let (errors, warnings) = report(project);
+    if errors > 0 || (args.deny_warnings && warnings > 0) {
+        bail!("check failed");
+    }
+    Ok(())
+}
+
+/// Lint every template, printing the findings and a summary to stderr, and
+/// return the number of errors and warnings
+pub fn report(project: &Project) -> (usize, usize) {
+    let mut findings = Vec::new();
+    for name in templates::names() {
+        let Some(template) = templates::get(&name) else {
+            continue;
+        };
+        for (level, message) in check_template(&name, &template) {
+            findings.push((level, name.clone(), message));
+        }
+    }
+
+    for (level, name, message) in &findings {
+        let level = match level {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        };
+        match project.sources.get(name) {
+            Some(file) => eprintln!("{}: {} ({}): {}", level, name, file.display(), message),
+            None => eprintln!("{}: {}: {}", level, name, message),
+        }
+    }
+
+    let errors = findings.iter().filter(|(level, ..)| *level == Level::Error).count();
+    let warnings = findings.len() - errors;
+    eprintln!(
+        "Checked {} template(s) in {} file(s): {} error(s), {} warning(s)",
+        project.sources.len(),
+        project.files.len(),
+        errors,
+        warnings
+    );
+    (errors, warnings)
+}
+
+/// Lint a single template
+fn check_template(name: &str, template: &Template) -> Vec<(Level, String)> {
+    let mut findings = Vec::new();
+    let engine = template.engine.as_deref().unwrap_or("simple");
+    let options = Options::from_template(template);
+
+    if let Err(e) = engines::check(engine, &template.template, &options) {
+        findings.push((Level::Error, e));
+        return findings;
+    }
+
+    for (index, param) in template.params.iter().enumerate() {
+        if template.params[..index].contains(param) {
+            findings.push((Level::Error, format!("param '{}' is declared twice", param)));
+        }
+    }
+
+    if engine == "simple" {
+        let placeholders = simple::placeholders(&template.template);
+        for placeholder in &placeholders {
+            if let Some(reference) = placeholder.strip_prefix('@')
+                && templates::get(reference).is_none()
+            {
+                findings.push((Level::Error, format!("referenced template not found: {}", placeholder)));
+            }
+        }
+
+        let used: Vec<&str> = placeholders
+            .iter()
+            .filter(|name| !name.starts_with('@'))
+            .copied()
+            .collect();
+        if !template.params.is_empty() {
+            for placeholder in &used {
+                if !template.params.iter().any(|param| covers(param, placeholder)) {
+                    findings.push((Level::Warning, format!("placeholder '{}' is not a declared param", placeholder)));
+                }
+            }
+        }
+        for param in &template.params {
+            if !used.iter().any(|placeholder| covers(param, placeholder)) {
+                findings.push((Level::Warning, format!("declared param '{}' is not used", param)));
+            }
+        }
+    } else {
+        for param in &template.params {
+            if !mentions(&template.template, param) {
+                findings.push((Level::Warning, format!("declared param '{}' is not used", param)));
+            }
+        }
+    }
+
+    // Templates that need parameters can't be rendered here
+    if template.validate.is_some() {
+        let params = HashMap::new();
+        if let Ok(output) = engines::process(engine, &template.template, &params, &options)
+            && let Err(e) = validate::check(name, template, &output, &params)
+        {
+            findings.push((Level::Error, e));
+        }
+    }
+
+    findings
+}
+
+/// Whether a declared param supplies a placeholder, directly or as the
+/// parent of a dotted name like `user.name`
+fn covers(param: &str, placeholder: &str) -> bool {
+    placeholder == param
+        || placeholder
+            .strip_prefix(param)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Whether a name appears in a template as a whole word
+fn mentions(template: &str, name: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    template.match_indices(name).any(|(start, _)| {
+        let before = template[..start].chars().next_back();
+        let after = template[start + name.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
@@ -0,0 +1,152 @@
+//! `tomplate diff`: how rendered output changed since a git revision.
+//!
+//! The template files matching the project's patterns are read from the
+//! revision with git, amalgamated into a second registry, and every template
+//! is rendered from both. Templates are rendered with each of their params
+//! set to its own name in braces, e.g. `{fields}`, so the diff shows how the
+//! text around the params changed.
+
+use crate::import::detect_params;
+use crate::project::Project;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use similar::TextDiff;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tomplate_engines::engines::{self, Options};
+use tomplate_engines::{settings, templates};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Git revision to compare against
+    #[arg(long)]
+    base: String,
+
+    /// Lines of context around each change
+    #[arg(long, short = 'U', default_value_t = 3)]
+    context: usize,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let mut settings = project.settings();
+    let current = settings::scoped(&settings, render_all);
+
+    let registry = base_registry(project, &args.base)?;
+    settings.insert("TOMPLATE_TEMPLATES_PATH".to_string(), registry.to_string_lossy().into_owned());
+    let base = settings::scoped(&settings, render_all);
+
+    let mut names: Vec<&String> = current.keys().chain(base.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut changed = 0;
+    for name in names {
+        let old = base.get(name).map(String::as_str).unwrap_or_default();
+        let new = current.get(name).map(String::as_str).unwrap_or_default();
+        if old == new && base.contains_key(name) == current.contains_key(name) {
+            continue;
+        }
+        changed += 1;
+
+        let old_header = if base.contains_key(name) {
+            format!("a/{} ({})", name, args.base)
+        } else {
+            "/dev/null".to_string()
+        };
+        let new_header = if current.contains_key(name) {
+            format!("b/{}", name)
+        } else {
+            "/dev/null".to_string()
+        };
+        print!(
+            "{}",
+            TextDiff::from_lines(old, new)
+                .unified_diff()
+                .context_radius(args.context)
+                .header(&old_header, &new_header)
+        );
+    }
+    eprintln!("{} template(s) render differently than at {}", changed, args.base);
+    Ok(())
+}
+
+/// Render every template of the current registry, with errors as output so
+/// a template that starts or stops failing shows up in the diff
+fn render_all() -> BTreeMap<String, String> {
+    let mut rendered = BTreeMap::new();
+    for name in templates::names() {
+        let Some(template) = templates::get(&name) else {
+            continue;
+        };
+        let engine = template.engine.as_deref().unwrap_or("simple");
+        let mut params: HashMap<String, Value> = HashMap::new();
+        for param in template.params.iter().cloned().chain(detect_params(engine, &template.template)) {
+            let value = format!("{{{}}}", param);
+            params.insert(param, Value::String(value));
+        }
+
+        let output = engines::process(engine, &template.template, &params, &Options::from_template(&template))
+            .unwrap_or_else(|e| format!("error: {}\n", e));
+        rendered.insert(name, output);
+    }
+    rendered
+}
+
+/// Amalgamate the template files of a git revision into a registry under
+/// the target directory, returning its path
+///
+/// The files are copied out of git into a temporary directory, outside the
+/// crate so the project's patterns never discover the copies, and removed
+/// once amalgamated.
+fn base_registry(project: &Project, revision: &str) -> Result<PathBuf> {
+    let patterns = project
+        .patterns
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern.trim_start_matches("./")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let files: Vec<String> = git(&["ls-tree", "-r", "--name-only", revision, "."])?
+        .lines()
+        .filter(|path| patterns.iter().any(|pattern| pattern.matches_path(Path::new(path))))
+        .map(str::to_string)
+        .collect();
+
+    let dir = project.registry.with_file_name("base");
+    fs::create_dir_all(&dir)?;
+    let copies_dir = env::temp_dir().join(format!("tomplate-diff-{}", std::process::id()));
+    let result = copy_files(&files, revision, &copies_dir).and_then(|copies| {
+        let registry = dir.join("tomplate_amalgamated.toml");
+        tomplate_build::write_registry(&copies, &registry)
+            .with_context(|| format!("Failed to load the templates at {}", revision))?;
+        Ok(registry)
+    });
+    let _ = fs::remove_dir_all(&copies_dir);
+    result
+}
+
+/// Write each file as it is at the revision under `dir`, returning glob
+/// patterns matching the copies
+fn copy_files(files: &[String], revision: &str, dir: &Path) -> Result<Vec<String>> {
+    let mut copies = Vec::new();
+    for file in files {
+        let copy = dir.join(file);
+        fs::create_dir_all(copy.parent().unwrap_or(dir))?;
+        fs::write(&copy, git(&["show", &format!("{}:./{}", revision, file)])?)?;
+        copies.push(glob::Pattern::escape(&copy.to_string_lossy()));
+    }
+    Ok(copies)
+}
+
+/// Run git in the crate directory and return its output
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
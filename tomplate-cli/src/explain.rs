@@ -0,0 +1,87 @@
+//! `tomplate explain`: show how a `tomplate!` call resolves and renders.
+//!
+//! The call is parsed and rendered by the same code as the macro, so the
+//! output is what the call expands to. Besides the output, the explanation
+//! shows which registry entry the name matched, or that it was rendered as
+//! an inline template, the engine and options it was rendered with, and its
+//! params after nested `tomplate!` calls were rendered.
+
+use crate::project::Project;
+use anyhow::{bail, Result};
+use std::fmt::Write;
+use tomplate_engines::invocation::{self, TomplateInput};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// The call, e.g. `tomplate!("select_user", fields = "id")`, or just its
+    /// arguments
+    call: String,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let input = parse(&args.call)?;
+    let resolved = invocation::resolve(&input)?;
+
+    let mut out = String::new();
+    match &resolved.template {
+        Some(_) => {
+            let file = project
+                .sources
+                .get(&input.template_name)
+                .map(|file| format!(" from {}", file.display()))
+                .unwrap_or_default();
+            writeln!(out, "match:   registry template '{}'{}", input.template_name, file)?;
+        }
+        None => writeln!(
+            out,
+            "match:   inline template, no registry template is named '{}'",
+            input.template_name
+        )?,
+    }
+    writeln!(out, "engine:  {}", resolved.engine)?;
+    writeln!(out, "options: {:?}", resolved.options)?;
+
+    let mut names: Vec<&String> = resolved.params.keys().collect();
+    names.sort();
+    writeln!(out, "params:")?;
+    for name in names {
+        writeln!(out, "    {} = {}", name, resolved.params[name])?;
+    }
+    for param in &input.conditional {
+        writeln!(out, "    {} (only with cfg({}))", param.name, param.predicate)?;
+    }
+    if let Some(template) = &resolved.template {
+        for param in &template.params {
+            if !resolved.params.contains_key(param) && input.conditional.iter().all(|c| &c.name != param) {
+                writeln!(out, "    {} (declared, not given)", param)?;
+            }
+        }
+    }
+    print!("{}", out);
+
+    if input.conditional.is_empty() {
+        let output = invocation::render(input)?;
+        println!("output:");
+        println!("{}", output);
+    } else {
+        for (predicate, result) in invocation::render_variants(input)? {
+            println!("output with cfg({}):", predicate);
+            match result {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("error: {}", e),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a call written as a macro invocation or as bare arguments
+fn parse(call: &str) -> Result<TomplateInput> {
+    let Ok(mac) = syn::parse_str::<syn::Macro>(call) else {
+        return Ok(syn::parse_str::<TomplateInput>(call)?);
+    };
+    if mac.path.segments.last().is_none_or(|segment| segment.ident != "tomplate") {
+        bail!("Only tomplate! calls can be explained");
+    }
+    Ok(syn::parse2::<TomplateInput>(mac.tokens)?)
+}
@@ -0,0 +1,172 @@
+//! `tomplate fmt`: normalize the layout of template files.
+//!
+//! Formatting never changes what a template renders:
+//!
+//! - Keys of each template are ordered `template`, `engine`, `params`,
+//!   `tags`, `description`, then the rest alphabetically
+//! - Multi-line template bodies are written as `"""` strings starting on the
+//!   line after the opening quotes
+//! - Engine names are spelled the way the engines are registered, e.g.
+//!   `Handlebars` and `hbs` become `handlebars`
+//! - Bodies of templates with `dedent = true` are indented by four spaces,
+//!   since rendering strips the indentation anyway
+//!
+//! Comments and the order of templates are kept.
+
+use crate::project::Project;
+use anyhow::{bail, Context, Result};
+use std::cmp::Ordering;
+use std::fs;
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+/// Keys that come first in a template, in order
+const KEY_ORDER: &[&str] = &["template", "engine", "params", "tags", "description"];
+
+/// Indentation of dedented template bodies
+const INDENT: &str = "    ";
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// List the files that aren't formatted instead of rewriting them, and
+    /// fail if there are any
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let mut unformatted = Vec::new();
+    for file in &project.files {
+        let source = fs::read_to_string(file)?;
+        let formatted = format(&source).with_context(|| format!("Failed to format {}", file.display()))?;
+        if formatted == source {
+            continue;
+        }
+
+        if args.check {
+            println!("{}", file.display());
+        } else {
+            fs::write(file, &formatted)?;
+        }
+        unformatted.push(file);
+    }
+
+    if args.check && !unformatted.is_empty() {
+        bail!("{} template file(s) need formatting", unformatted.len());
+    }
+    Ok(())
+}
+
+/// Format the source of a template file
+fn format(source: &str) -> Result<String> {
+    let mut document: DocumentMut = source.parse()?;
+    for (_, item) in document.iter_mut() {
+        if let Item::Table(template) = item {
+            format_template(template)?;
+        }
+    }
+    Ok(document.to_string())
+}
+
+/// Normalize a single template's table
+fn format_template(template: &mut Table) -> Result<()> {
+    if let Some(engine) = template.get_mut("engine")
+        && let Some(name) = engine.as_str()
+        && let Some(canonical) = canonical_engine(name)
+        && canonical != name
+    {
+        let decor = engine.as_value().map(|value| value.decor().clone());
+        let mut value = Value::from(canonical);
+        if let Some(decor) = decor {
+            *value.decor_mut() = decor;
+        }
+        *engine = Item::Value(value);
+    }
+
+    let dedent = template.get("dedent").and_then(Item::as_bool).unwrap_or(false);
+    if let Some(body) = template.get_mut("template")
+        && let Some(text) = body.as_str()
+        && text.contains('\n')
+    {
+        let text = if dedent { reindent(text) } else { text.to_string() };
+        let decor = body.as_value().map(|value| value.decor().clone());
+        let mut value: Value = multiline(&text).parse()?;
+        if let Some(decor) = decor {
+            *value.decor_mut() = decor;
+        }
+        *body = Item::Value(value);
+    }
+
+    template.sort_values_by(|a, _, b, _| key_order(a.get(), b.get()));
+    Ok(())
+}
+
+/// The registered spelling of an engine name, or `None` for names of custom
+/// engines
+fn canonical_engine(name: &str) -> Option<&'static str> {
+    match name.trim().to_lowercase().as_str() {
+        "simple" => Some("simple"),
+        "handlebars" | "hbs" => Some("handlebars"),
+        "tera" => Some("tera"),
+        "minijinja" | "mini_jinja" | "mini-jinja" | "jinja" | "jinja2" => Some("minijinja"),
+        "fluent" | "ftl" => Some("fluent"),
+        _ => None,
+    }
+}
+
+/// Order keys with the well-known ones first
+fn key_order(a: &str, b: &str) -> Ordering {
+    let rank = |key: &str| KEY_ORDER.iter().position(|known| *known == key).unwrap_or(KEY_ORDER.len());
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+}
+
+/// Re-indent a body that is dedented when rendered, so it renders the same
+fn reindent(text: &str) -> String {
+    let common = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    text.split('\n')
+        .map(|line| {
+            let line = line.get(common..).unwrap_or_else(|| line.trim_start());
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", INDENT, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Encode text as a multi-line basic string starting on its own line
+pub fn multiline(text: &str) -> String {
+    let mut encoded = String::from("\"\"\"\n");
+    let mut quotes = 0;
+    for c in text.chars() {
+        match c {
+            '\\' => encoded.push_str("\\\\"),
+            // Break up runs of quotes that would close the string
+            '"' if quotes == 2 => {
+                encoded.push_str("\\\"");
+                quotes = 0;
+                continue;
+            }
+            '"' => encoded.push('"'),
+            '\n' | '\t' => encoded.push(c),
+            '\r' => encoded.push_str("\\r"),
+            c if c.is_control() => encoded.push_str(&format!("\\u{:04X}", c as u32)),
+            c => encoded.push(c),
+        }
+        quotes = if c == '"' { quotes + 1 } else { 0 };
+    }
+    // A quote right before the closing delimiter would extend it
+    if encoded.ends_with('"') {
+        encoded.pop();
+        encoded.push_str("\\\"");
+    }
+    encoded.push_str("\"\"\"");
+    encoded
+}
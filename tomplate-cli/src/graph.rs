@@ -0,0 +1,197 @@
+//! `tomplate graph`: the references between templates as a Graphviz or
+//! Mermaid graph.
+//!
+//! Edges run from a template to the templates it refers to. Edges on a cycle
+//! are drawn in red, references to missing templates point at dashed nodes,
+//! and orphans, templates that neither refer to nor are referred to by any
+//! other, are drawn in gray. Cycles and orphans are also listed on stderr.
+
+use crate::project::Project;
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use tomplate_engines::engines::{self, Options};
+use tomplate_engines::templates;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Dot)]
+    format: Format,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum Format {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+pub fn run(_project: &Project, args: Args) -> Result<()> {
+    let mut edges: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in templates::names() {
+        let Some(template) = templates::get(&name) else {
+            continue;
+        };
+        let engine = template.engine.as_deref().unwrap_or("simple");
+        let references = engines::references(engine, &template.template, &Options::from_template(&template));
+        edges.insert(name, references);
+    }
+
+    let missing: BTreeSet<&str> = edges
+        .values()
+        .flatten()
+        .filter(|target| !edges.contains_key(*target))
+        .map(String::as_str)
+        .collect();
+    let referenced: BTreeSet<&str> = edges.values().flatten().map(String::as_str).collect();
+    let orphans: Vec<&str> = edges
+        .iter()
+        .filter(|(name, targets)| targets.is_empty() && !referenced.contains(name.as_str()))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let cycles = cycles(&edges);
+    let cyclic = |from: &str, to: &str| {
+        cycles
+            .iter()
+            .any(|cycle| cycle.contains(&from) && cycle.contains(&to))
+    };
+
+    let mut out = String::new();
+    match args.format {
+        Format::Dot => {
+            writeln!(out, "digraph templates {{")?;
+            for name in edges.keys() {
+                let style = if orphans.contains(&name.as_str()) { " [color=gray, fontcolor=gray]" } else { "" };
+                writeln!(out, "    {:?}{};", name, style)?;
+            }
+            for name in &missing {
+                writeln!(out, "    {:?} [style=dashed, color=red];", name)?;
+            }
+            for (from, targets) in &edges {
+                for to in targets {
+                    let style = if cyclic(from, to) { " [color=red]" } else { "" };
+                    writeln!(out, "    {:?} -> {:?}{};", from, to, style)?;
+                }
+            }
+            writeln!(out, "}}")?;
+        }
+        Format::Mermaid => {
+            // Mermaid ids can't hold arbitrary names, so nodes are numbered
+            let ids: BTreeMap<&str, usize> = edges
+                .keys()
+                .map(String::as_str)
+                .chain(missing.iter().copied())
+                .enumerate()
+                .map(|(id, name)| (name, id))
+                .collect();
+            writeln!(out, "graph LR")?;
+            for (name, id) in &ids {
+                writeln!(out, "    n{}[\"{}\"]", id, name.replace('"', "#quot;"))?;
+            }
+            let mut cyclic_links = Vec::new();
+            let mut link = 0;
+            for (from, targets) in &edges {
+                for to in targets {
+                    writeln!(out, "    n{} --> n{}", ids[from.as_str()], ids[to.as_str()])?;
+                    if cyclic(from, to) {
+                        cyclic_links.push(link.to_string());
+                    }
+                    link += 1;
+                }
+            }
+            for name in &missing {
+                writeln!(out, "    style n{} stroke:red,stroke-dasharray:4", ids[name])?;
+            }
+            for name in &orphans {
+                writeln!(out, "    style n{} stroke:gray,color:gray", ids[name])?;
+            }
+            if !cyclic_links.is_empty() {
+                writeln!(out, "    linkStyle {} stroke:red", cyclic_links.join(","))?;
+            }
+        }
+    }
+    print!("{}", out);
+
+    for cycle in &cycles {
+        eprintln!("cycle: {}", cycle.join(", "));
+    }
+    for name in &missing {
+        eprintln!("missing: {}", name);
+    }
+    if !orphans.is_empty() {
+        eprintln!("orphans: {}", orphans.join(", "));
+    }
+    Ok(())
+}
+
+/// The groups of templates that refer to each other in a cycle, found as the
+/// strongly connected components of the graph with more than one template
+/// or a reference to itself
+fn cycles(edges: &BTreeMap<String, Vec<String>>) -> Vec<Vec<&str>> {
+    /// Tarjan's algorithm state
+    struct Search<'a> {
+        edges: &'a BTreeMap<String, Vec<String>>,
+        index: BTreeMap<&'a str, usize>,
+        lowlink: BTreeMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        components: Vec<Vec<&'a str>>,
+    }
+
+    impl<'a> Search<'a> {
+        fn visit(&mut self, name: &'a str) {
+            let index = self.index.len();
+            self.index.insert(name, index);
+            self.lowlink.insert(name, index);
+            self.stack.push(name);
+
+            let edges = self.edges;
+            for target in edges.get(name).into_iter().flatten() {
+                let target = target.as_str();
+                if !edges.contains_key(target) {
+                    continue;
+                }
+                if !self.index.contains_key(target) {
+                    self.visit(target);
+                    let low = self.lowlink[name].min(self.lowlink[target]);
+                    self.lowlink.insert(name, low);
+                } else if self.stack.contains(&target) {
+                    let low = self.lowlink[name].min(self.index[target]);
+                    self.lowlink.insert(name, low);
+                }
+            }
+
+            if self.lowlink[name] == self.index[name] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    component.push(member);
+                    if member == name {
+                        break;
+                    }
+                }
+                component.reverse();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut search = Search {
+        edges,
+        index: BTreeMap::new(),
+        lowlink: BTreeMap::new(),
+        stack: Vec::new(),
+        components: Vec::new(),
+    };
+    for name in edges.keys() {
+        if !search.index.contains_key(name.as_str()) {
+            search.visit(name);
+        }
+    }
+
+    search
+        .components
+        .into_iter()
+        .filter(|component| component.len() > 1 || edges[component[0]].iter().any(|target| target == component[0]))
+        .collect()
+}
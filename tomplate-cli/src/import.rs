@@ -0,0 +1,327 @@
+//! `tomplate import`: convert a directory of template files into a registry
+//! file.
+//!
+//! Each file becomes a template named after its path, e.g.
+//! `users/select-by-id.sql` becomes `users_select_by_id`. The engine is taken
+//! from `--engine` or inferred from the extension, and the variables the
+//! template reads are declared as its `params`. Variables are detected with
+//! a scan of the source, so the result is worth a review.
+
+use crate::fmt::multiline;
+use crate::project::Project;
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tomplate_engines::engines::simple;
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+use walkdir::WalkDir;
+
+/// Words that are part of Jinja expressions rather than variables
+const JINJA_KEYWORDS: &[&str] = &[
+    "and", "or", "not", "in", "is", "if", "else", "true", "false", "none", "True", "False", "None",
+    "loop", "self", "super", "caller", "varargs", "kwargs",
+];
+
+/// Jinja tags that don't read variables
+const JINJA_SKIPPED_TAGS: &[&str] = &[
+    "block", "extends", "include", "import", "from", "macro", "call", "filter", "raw", "else",
+    "autoescape",
+];
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Directory of template files to import
+    dir: PathBuf,
+
+    /// Engine of every imported template, instead of inferring it from each
+    /// file's extension
+    #[arg(long)]
+    engine: Option<String>,
+
+    /// Registry file to write, `<dir>.tomplate.toml` by default
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    /// Overwrite the output file if it exists
+    #[arg(long)]
+    force: bool,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let output = args.output.clone().unwrap_or_else(|| {
+        let name = args.dir.file_name().unwrap_or(args.dir.as_os_str()).to_string_lossy();
+        args.dir.with_file_name(format!("{}.tomplate.toml", name))
+    });
+    if output.exists() && !args.force {
+        bail!("{} already exists, pass --force to overwrite it", output.display());
+    }
+
+    let mut imported: BTreeMap<String, (PathBuf, Table)> = BTreeMap::new();
+    for entry in WalkDir::new(&args.dir).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path.strip_prefix(&args.dir).unwrap_or(path);
+        let hidden = relative
+            .components()
+            .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+        if !entry.file_type().is_file() || hidden {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(path) else {
+            eprintln!("warning: skipping {}, it isn't UTF-8 text", path.display());
+            continue;
+        };
+
+        let name = template_name(relative);
+        if let Some((first, _)) = imported.get(&name) {
+            bail!(
+                "{} and {} would both be imported as '{}'",
+                first.display(),
+                path.display(),
+                name
+            );
+        }
+        if project.sources.contains_key(&name) {
+            bail!("Template '{}' from {} already exists in the registry", name, path.display());
+        }
+
+        let engine = args.engine.clone().unwrap_or_else(|| engine_for(path).to_string());
+        imported.insert(name, (path.to_path_buf(), template_table(&text, &engine)?));
+    }
+    if imported.is_empty() {
+        bail!("No template files found in {}", args.dir.display());
+    }
+
+    let mut document = DocumentMut::new();
+    for (name, (_, table)) in &imported {
+        document.insert(name, Item::Table(table.clone()));
+    }
+    fs::write(&output, document.to_string())
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    eprintln!("Imported {} template(s) into {}", imported.len(), output.display());
+    Ok(())
+}
+
+/// Name a template after its path relative to the imported directory
+fn template_name(relative: &Path) -> String {
+    let path = relative.with_extension("");
+    let name: String = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    name.trim_matches('_').to_string()
+}
+
+/// The engine a file's extension suggests
+fn engine_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("hbs" | "handlebars" | "mustache") => "handlebars",
+        Some("tera") => "tera",
+        Some("jinja" | "jinja2" | "j2") => "minijinja",
+        Some("ftl") => "fluent",
+        _ => "simple",
+    }
+}
+
+/// Build the registry table of an imported template
+fn template_table(text: &str, engine: &str) -> Result<Table> {
+    let mut table = Table::new();
+    let body: Value = if text.contains('\n') {
+        multiline(text).parse()?
+    } else {
+        Value::from(text)
+    };
+    table.insert("template", Item::Value(body));
+    if engine != "simple" {
+        table.insert("engine", Item::Value(Value::from(engine)));
+    }
+
+    let params = detect_params(engine, text);
+    if !params.is_empty() {
+        let params: Array = params.iter().map(String::as_str).collect();
+        table.insert("params", Item::Value(Value::Array(params)));
+    }
+    Ok(table)
+}
+
+/// The variables a template reads, in order of first appearance
+pub fn detect_params(engine: &str, text: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut push = |name: &str| {
+        if !name.is_empty() && !params.iter().any(|existing| existing == name) {
+            params.push(name.to_string());
+        }
+    };
+
+    match engine {
+        "handlebars" => {
+            let mut locals: Vec<&str> = Vec::new();
+            for tag in tags(text, "{{", "}}") {
+                let tag = tag.trim_matches(['{', '}', '~']).trim();
+                let args = match tag.chars().next() {
+                    Some('!' | '/' | '>') | None => continue,
+                    Some('#') => tag.split_whitespace().skip(1).collect::<Vec<_>>(),
+                    _ if tag.starts_with("else") => continue,
+                    _ => {
+                        let words: Vec<&str> = tag.split_whitespace().collect();
+                        // A single word is a path; otherwise the first is a helper
+                        if words.len() == 1 { words } else { words[1..].to_vec() }
+                    }
+                };
+                for arg in args {
+                    // Block params, as in `{{#each items as |item|}}`
+                    if arg.starts_with('|') || arg.ends_with('|') {
+                        locals.push(arg.trim_matches('|'));
+                        continue;
+                    }
+                    if arg.starts_with(['"', '\'', '@', '.']) || matches!(arg, "as" | "this" | "true" | "false" | "null") {
+                        continue;
+                    }
+                    let value = arg.split_once('=').map_or(arg, |(_, value)| value);
+                    let root = value.split(['.', '/']).next().unwrap_or_default();
+                    if root.starts_with(|c: char| c.is_alphabetic() || c == '_') && !locals.contains(&root) {
+                        push(root);
+                    }
+                }
+            }
+        }
+        "tera" | "minijinja" => {
+            let mut locals: Vec<String> = Vec::new();
+            for (kind, tag) in jinja_tags(text) {
+                let tag = tag.trim_matches(['-', '+']).trim();
+                let expression = if kind == "{%" {
+                    let (keyword, rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                    if keyword.starts_with("end") || JINJA_SKIPPED_TAGS.contains(&keyword) {
+                        continue;
+                    }
+                    match keyword {
+                        "for" => {
+                            let Some((targets, iterable)) = rest.split_once(" in ") else {
+                                continue;
+                            };
+                            locals.extend(targets.split(',').map(|target| target.trim().to_string()));
+                            iterable
+                        }
+                        "set" | "with" => {
+                            let (target, value) = rest.split_once('=').unwrap_or((rest, ""));
+                            locals.push(target.trim().to_string());
+                            value
+                        }
+                        _ => rest,
+                    }
+                } else {
+                    tag
+                };
+                for name in jinja_variables(expression) {
+                    if !locals.iter().any(|local| local == name) {
+                        push(name);
+                    }
+                }
+            }
+        }
+        "fluent" => {
+            let mut rest = text;
+            while let Some(start) = rest.find('$') {
+                rest = &rest[start + 1..];
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                    .unwrap_or(rest.len());
+                push(&rest[..end]);
+            }
+        }
+        _ => {
+            for name in simple::placeholders(text) {
+                if !name.starts_with('@') {
+                    push(name.split('.').next().unwrap_or(name));
+                }
+            }
+        }
+    }
+    params
+}
+
+/// The trimmed contents of the tags between `start` and `end` delimiters
+fn tags<'a>(text: &'a str, start: &'a str, end: &'a str) -> impl Iterator<Item = &'a str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let open = rest.find(start)? + start.len();
+        let close = rest[open..].find(end)? + open;
+        let tag = &rest[open..close];
+        rest = &rest[close + end.len()..];
+        Some(tag.trim())
+    })
+}
+
+/// Jinja expression and statement tags in order, with the delimiter that
+/// opened each
+fn jinja_tags(text: &str) -> Vec<(&'static str, &str)> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open..];
+        let (kind, close) = if after.starts_with("{{") {
+            ("{{", "}}")
+        } else if after.starts_with("{%") {
+            ("{%", "%}")
+        } else {
+            rest = &rest[open + 1..];
+            continue;
+        };
+        let Some(end) = after[2..].find(close) else {
+            break;
+        };
+        found.push((kind, after[2..2 + end].trim()));
+        rest = &after[2 + end + 2..];
+    }
+    found
+}
+
+/// The root names of the variables in a Jinja expression, skipping
+/// attributes, filters, function calls, keywords and string literals
+fn jinja_variables(expression: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut previous = ' ';
+    let mut test = false;
+    let mut chars = expression.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '"' || c == '\'' {
+            for (_, next) in chars.by_ref() {
+                if next == c {
+                    break;
+                }
+            }
+            previous = c;
+            continue;
+        }
+        if !(c.is_alphabetic() || c == '_') {
+            if !c.is_whitespace() {
+                previous = c;
+            }
+            continue;
+        }
+
+        let mut end = start + c.len_utf8();
+        while let Some(&(index, next)) = chars.peek() {
+            if !(next.is_alphanumeric() || next == '_') {
+                break;
+            }
+            end = index + next.len_utf8();
+            chars.next();
+        }
+        let name = &expression[start..end];
+        let called = expression[end..].trim_start().starts_with('(');
+        // The name after `is` or `is not` is a test, as in `x is defined`
+        let tested = test && name != "not";
+        if previous != '.' && previous != '|' && !called && !tested && !JINJA_KEYWORDS.contains(&name) {
+            names.push(name);
+        }
+        test = name == "is" || (test && name == "not");
+        previous = 'a';
+    }
+    names
+}
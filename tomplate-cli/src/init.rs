@@ -0,0 +1,112 @@
+//! `tomplate init`: set up a crate to use tomplate.
+//!
+//! Creates a build script, a `templates/` directory with an example
+//! template, and adds the dependencies and template patterns to
+//! `Cargo.toml`. Files that already exist are left alone, so it is safe to
+//! run in a crate that has some of the setup.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+
+/// Where templates live in a new setup
+const PATTERN: &str = "templates/**/*.tomplate.toml";
+
+const BUILD_SCRIPT: &str = r#"fn main() {
+    // Templates are found with the patterns under
+    // [package.metadata.tomplate] in Cargo.toml
+    tomplate_build::Builder::new()
+        .add_manifest_patterns()
+        .build()
+        .expect("Failed to build templates");
+}
+"#;
+
+const EXAMPLE_TEMPLATE: &str = r#"# Render with tomplate!("greeting", name = "World")
+[greeting]
+template = "Hello {name}!"
+params = ["name"]
+description = "An example template"
+"#;
+
+const EXAMPLE_PATH: &str = "templates/example.tomplate.toml";
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Overwrite an existing build script
+    #[arg(long)]
+    force: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let manifest = Path::new("Cargo.toml");
+    if !manifest.exists() {
+        bail!("No Cargo.toml found, run `tomplate init` in a crate directory");
+    }
+
+    let build_script = Path::new("build.rs");
+    if build_script.exists() && !args.force {
+        eprintln!("build.rs already exists, call `tomplate_build::Builder` from it or pass --force to overwrite it");
+    } else {
+        fs::write(build_script, BUILD_SCRIPT)?;
+        eprintln!("Created build.rs");
+    }
+
+    if Path::new(EXAMPLE_PATH).exists() {
+        eprintln!("{} already exists", EXAMPLE_PATH);
+    } else {
+        fs::create_dir_all("templates")?;
+        fs::write(EXAMPLE_PATH, EXAMPLE_TEMPLATE)?;
+        eprintln!("Created {}", EXAMPLE_PATH);
+    }
+
+    let source = fs::read_to_string(manifest)?;
+    let mut document: DocumentMut = source.parse().context("Failed to parse Cargo.toml")?;
+    add_setup(&mut document)?;
+    if document.to_string() != source {
+        fs::write(manifest, document.to_string())?;
+        eprintln!("Updated Cargo.toml");
+    }
+
+    eprintln!();
+    eprintln!("Render the example with:");
+    eprintln!();
+    eprintln!("    const GREETING: &str = tomplate::tomplate!(\"greeting\", name = \"World\");");
+    Ok(())
+}
+
+/// Add the dependencies and template patterns to a manifest, keeping any
+/// that are already there
+fn add_setup(document: &mut DocumentMut) -> Result<()> {
+    // Depend on the release series of this tool, e.g. "0.1"
+    let version: String = env!("CARGO_PKG_VERSION").split('.').take(2).collect::<Vec<_>>().join(".");
+    for (section, dependency) in [("dependencies", "tomplate"), ("build-dependencies", "tomplate-build")] {
+        let table = table(document.as_table_mut(), section)?;
+        if !table.contains_key(dependency) {
+            table.insert(dependency, Item::Value(Value::from(version.as_str())));
+        }
+    }
+
+    let package = document
+        .get_mut("package")
+        .and_then(Item::as_table_mut)
+        .context("Cargo.toml has no [package] table")?;
+    let metadata = table(package, "metadata")?;
+    metadata.set_implicit(true);
+    let tomplate = table(metadata, "tomplate")?;
+    if !tomplate.contains_key("patterns") {
+        let patterns: Array = [PATTERN].into_iter().collect();
+        tomplate.insert("patterns", Item::Value(Value::Array(patterns)));
+    }
+    Ok(())
+}
+
+/// The sub-table of a table, created if missing
+fn table<'a>(parent: &'a mut Table, key: &str) -> Result<&'a mut Table> {
+    parent
+        .entry(key)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .with_context(|| format!("`{}` in Cargo.toml isn't a table", key))
+}
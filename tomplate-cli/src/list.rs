@@ -0,0 +1,81 @@
+//! `tomplate list`: find templates in the registry.
+
+use crate::project::Project;
+use anyhow::Result;
+use tomplate_build::Template;
+use tomplate_engines::templates;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Only list templates with this tag; repeat to require several
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+
+    /// Only list templates whose name, description or body contains this
+    /// text, ignoring case
+    #[arg(long)]
+    grep: Option<String>,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let grep = args.grep.map(|text| text.to_lowercase());
+    let mut rows = Vec::new();
+    for name in templates::names() {
+        let Some(template) = templates::get(&name) else {
+            continue;
+        };
+        if !args.tags.iter().all(|tag| template.tags.contains(tag)) {
+            continue;
+        }
+        if let Some(grep) = &grep
+            && !matches(&name, &template, grep)
+        {
+            continue;
+        }
+
+        rows.push([
+            name.clone(),
+            template.engine.clone().unwrap_or_else(|| "simple".to_string()),
+            project
+                .sources
+                .get(&name)
+                .map(|file| file.display().to_string())
+                .unwrap_or_default(),
+            template.params.join(", "),
+        ]);
+    }
+
+    // Pad every column but the last to its widest value
+    let mut widths = [0; 3];
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    for [name, engine, file, params] in &rows {
+        let line = format!(
+            "{:name_width$}  {:engine_width$}  {:file_width$}  {}",
+            name,
+            engine,
+            file,
+            params,
+            name_width = widths[0],
+            engine_width = widths[1],
+            file_width = widths[2],
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+/// Whether a template's name, description or body contains lowercase text
+fn matches(name: &str, template: &Template, text: &str) -> bool {
+    let description = template
+        .metadata
+        .get("description")
+        .and_then(|description| description.as_str())
+        .unwrap_or_default();
+    [name, description, template.template.as_str()]
+        .iter()
+        .any(|field| field.to_lowercase().contains(text))
+}
@@ -0,0 +1,101 @@
+//! The `tomplate` command-line tool.
+//!
+//! Renders and inspects a crate's templates without compiling it. Templates
+//! are discovered with the patterns from `--pattern`, or else those listed
+//! under `[package.metadata.tomplate]` in `Cargo.toml`, the same table
+//! `Builder::add_manifest_patterns` reads in build scripts.
+//!
+//! ```text
+//! tomplate init
+//! tomplate render select_user --param fields='id, name' --param condition='id = $1'
+//! tomplate check
+//! tomplate list --tag sql --grep users
+//! tomplate graph --format dot | dot -Tsvg > templates.svg
+//! tomplate fmt --check
+//! tomplate import templates/ --engine handlebars
+//! tomplate diff --base main
+//! tomplate watch
+//! tomplate explain 'tomplate!("select_user", fields = "id")'
+//! ```
+
+mod check;
+mod diff;
+mod explain;
+mod fmt;
+mod graph;
+mod import;
+mod init;
+mod list;
+mod project;
+mod render;
+mod watch;
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use tomplate_engines::settings;
+
+/// Render and inspect tomplate templates
+#[derive(Parser)]
+#[command(name = "tomplate", version, about)]
+struct Cli {
+    /// Directory of the crate whose templates are used
+    #[arg(long, global = true, default_value = ".")]
+    manifest_dir: PathBuf,
+
+    /// Glob pattern for template files, instead of those in `Cargo.toml`
+    #[arg(long = "pattern", global = true)]
+    patterns: Vec<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Set up the build script, templates and dependencies of a crate
+    Init(init::Args),
+    /// Render a template and print the output
+    Render(render::Args),
+    /// Lint the templates without compiling the crate
+    Check(check::Args),
+    /// List templates with their engine, file and params
+    List(list::Args),
+    /// Print the references between templates as a graph
+    Graph(graph::Args),
+    /// Normalize the layout of template files
+    Fmt(fmt::Args),
+    /// Convert a directory of template files into a registry file
+    Import(import::Args),
+    /// Show how rendered output changed since a git revision
+    Diff(diff::Args),
+    /// Re-check the templates whenever their files change
+    Watch(watch::Args),
+    /// Show how a `tomplate!` call resolves and what it renders to
+    Explain(explain::Args),
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    project::enter(&cli.manifest_dir)?;
+
+    // These don't need the templates to load
+    match cli.command {
+        Command::Init(args) => return init::run(args),
+        // Watching keeps going when the templates fail to load
+        Command::Watch(args) => return watch::run(cli.patterns, args),
+        _ => {}
+    }
+    let project = project::Project::load(cli.patterns)?;
+
+    settings::scoped(&project.settings(), || match cli.command {
+        Command::Render(args) => render::run(&project, args),
+        Command::Check(args) => check::run(&project, args),
+        Command::List(args) => list::run(&project, args),
+        Command::Graph(args) => graph::run(&project, args),
+        Command::Fmt(args) => fmt::run(&project, args),
+        Command::Import(args) => import::run(&project, args),
+        Command::Diff(args) => diff::run(&project, args),
+        Command::Explain(args) => explain::run(&project, args),
+        Command::Init(_) | Command::Watch(_) => unreachable!("handled before loading the project"),
+    })
+}
@@ -0,0 +1,86 @@
+//! Discovery of a crate's templates and the registry built from them.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Patterns used when neither `--pattern` nor `Cargo.toml` lists any
+const DEFAULT_PATTERNS: &[&str] = &["**/*.tomplate.toml"];
+
+/// A crate's templates, amalgamated into a registry the engines can read
+pub struct Project {
+    /// Patterns the template files were discovered with
+    pub patterns: Vec<String>,
+    /// Discovered template files, relative to the crate directory
+    pub files: Vec<PathBuf>,
+    /// The file each template is defined in, by name
+    pub sources: BTreeMap<String, PathBuf>,
+    /// The amalgamated registry
+    pub registry: PathBuf,
+}
+
+/// Enter the directory of the crate whose templates are used
+///
+/// Patterns are resolved against the crate directory, as they are for a
+/// build script.
+pub fn enter(manifest_dir: &Path) -> Result<()> {
+    env::set_current_dir(manifest_dir)
+        .with_context(|| format!("Failed to enter {}", manifest_dir.display()))
+}
+
+/// The given patterns, or else those listed in `Cargo.toml`, or else the
+/// defaults
+pub fn resolve_patterns(mut patterns: Vec<String>) -> Result<Vec<String>> {
+    if patterns.is_empty() && Path::new("Cargo.toml").exists() {
+        patterns = tomplate_build::manifest_patterns(".")?;
+    }
+    if patterns.is_empty() {
+        patterns = DEFAULT_PATTERNS.iter().map(|pattern| pattern.to_string()).collect();
+    }
+    Ok(patterns)
+}
+
+impl Project {
+    /// Discover the crate's templates and write their registry under the
+    /// target directory
+    ///
+    /// Without patterns, those listed in `Cargo.toml` are used.
+    pub fn load(patterns: Vec<String>) -> Result<Self> {
+        let patterns = resolve_patterns(patterns)?;
+
+        let dir = env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target"))
+            .join("tomplate");
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let registry = dir.join("tomplate_amalgamated.toml");
+        let files = tomplate_build::write_registry(&patterns, &registry)?;
+        let sources = sources(&files)?;
+
+        Ok(Project { patterns, files, sources, registry })
+    }
+
+    /// Settings for rendering from the registry: the `TOMPLATE_` variables
+    /// of the environment, with the registry's path
+    pub fn settings(&self) -> HashMap<String, String> {
+        let mut settings: HashMap<String, String> =
+            env::vars().filter(|(name, _)| name.starts_with("TOMPLATE_")).collect();
+        settings.insert("TOMPLATE_TEMPLATES_PATH".to_string(), self.registry.to_string_lossy().into_owned());
+        settings
+    }
+}
+
+/// Map each template name to the file defining it
+fn sources(files: &[PathBuf]) -> Result<BTreeMap<String, PathBuf>> {
+    let mut sources = BTreeMap::new();
+    for file in files {
+        let table: toml::Table = toml::from_str(&fs::read_to_string(file)?)
+            .with_context(|| format!("Failed to parse {}", file.display()))?;
+        for name in table.keys() {
+            sources.insert(name.clone(), file.clone());
+        }
+    }
+    Ok(sources)
+}
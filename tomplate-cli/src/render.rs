@@ -0,0 +1,63 @@
+//! `tomplate render`: render a registry template with parameters given on
+//! the command line.
+
+use crate::project::Project;
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use tomplate_engines::{engines, templates, validate};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Name of the template to render
+    name: String,
+
+    /// A parameter as `key=value`; values that parse as JSON keep their type
+    #[arg(long = "param", short, value_parser = parse_param)]
+    params: Vec<(String, Value)>,
+
+    /// Engine to render with, instead of the template's own
+    #[arg(long)]
+    engine: Option<String>,
+}
+
+pub fn run(project: &Project, args: Args) -> Result<()> {
+    let Some(template) = templates::get(&args.name) else {
+        bail!(
+            "Template not found: {} (searched {} template file(s))",
+            args.name,
+            project.files.len()
+        );
+    };
+
+    let params: HashMap<String, Value> = args.params.into_iter().collect();
+    let engine = args
+        .engine
+        .as_deref()
+        .or(template.engine.as_deref())
+        .unwrap_or("simple");
+    let output = engines::process(
+        engine,
+        &template.template,
+        &params,
+        &engines::Options::from_template(&template),
+    )
+    .map_err(|e| anyhow!(e))?;
+    validate::check(&args.name, &template, &output, &params).map_err(|e| anyhow!(e))?;
+
+    println!("{}", output);
+    Ok(())
+}
+
+/// Parse a `key=value` parameter, keeping JSON numbers, booleans, arrays and
+/// objects typed and reading anything else as a string
+fn parse_param(param: &str) -> Result<(String, Value), String> {
+    let (key, value) = param
+        .split_once('=')
+        .ok_or_else(|| format!("expected `key=value`, got `{}`", param))?;
+    let value = match serde_json::from_str::<Value>(value) {
+        Ok(Value::String(_)) | Err(_) => Value::String(value.to_string()),
+        Ok(value) => value,
+    };
+    Ok((key.to_string(), value))
+}
@@ -0,0 +1,86 @@
+//! `tomplate watch`: re-check templates whenever their files change.
+//!
+//! Template files are polled, so changes are picked up without platform
+//! file-watching support. On every change the templates are discovered and
+//! amalgamated again and `tomplate check` runs. With `--out-dir`, the
+//! registry is also written where the macros read it, e.g. the crate's
+//! `OUT_DIR`, so rust-analyzer picks up template edits without a build. That
+//! registry lacks the build script's defaults until the next build.
+
+use crate::check;
+use crate::project::{self, Project};
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tomplate_engines::settings;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Milliseconds between checks for changes
+    #[arg(long, default_value_t = 200)]
+    interval: u64,
+
+    /// Also write the registry into this directory on every change
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+pub fn run(patterns: Vec<String>, args: Args) -> Result<()> {
+    let patterns = project::resolve_patterns(patterns)?;
+    let mut snapshot = None;
+    loop {
+        // Discovery runs on every poll so new files are picked up too
+        let current = files(&patterns);
+        if snapshot.as_ref() != Some(&current) {
+            let start = Instant::now();
+            match &snapshot {
+                Some(snapshot) => eprintln!("--- {} template file(s) changed", changed(snapshot, &current)),
+                None => eprintln!("--- watching {} template file(s)", current.len()),
+            }
+            match rebuild(&patterns, &args) {
+                Ok(()) => eprintln!("--- done in {:.0?}", start.elapsed()),
+                Err(e) => eprintln!("error: {:#}", e),
+            }
+            snapshot = Some(current);
+        }
+        thread::sleep(Duration::from_millis(args.interval));
+    }
+}
+
+/// Amalgamate and check the templates again
+fn rebuild(patterns: &[String], args: &Args) -> Result<()> {
+    let project = Project::load(patterns.to_vec())?;
+    if let Some(out_dir) = &args.out_dir {
+        tomplate_build::write_registry(&project.patterns, out_dir.join("tomplate_amalgamated.toml"))?;
+    }
+    settings::scoped(&project.settings(), || check::report(&project));
+    Ok(())
+}
+
+/// The template files matching the patterns, with their modification times
+fn files(patterns: &[String]) -> BTreeMap<PathBuf, Option<SystemTime>> {
+    patterns
+        .iter()
+        .filter_map(|pattern| glob::glob(pattern).ok())
+        .flatten()
+        .flatten()
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// How many files were added, removed or modified between two snapshots
+fn changed(
+    before: &BTreeMap<PathBuf, Option<SystemTime>>,
+    after: &BTreeMap<PathBuf, Option<SystemTime>>,
+) -> usize {
+    let modified = after.iter().filter(|(path, time)| before.get(*path) != Some(*time)).count();
+    let removed = before.keys().filter(|path| !after.contains_key(*path)).count();
+    modified + removed
+}
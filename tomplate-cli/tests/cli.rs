@@ -0,0 +1,175 @@
+//! Runs the `tomplate` binary against the crates in `tests/fixtures`.
+//!
+//! Each test works on a copy of its fixture, since `fmt` and `import` write
+//! files and every command writes the registry under `target/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Copy a fixture into a fresh scratch directory and return its path
+fn fixture(name: &str) -> PathBuf {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cli").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    copy_dir(&source, &dir);
+    dir
+}
+
+fn copy_dir(source: &Path, dest: &Path) {
+    fs::create_dir_all(dest).unwrap();
+    for entry in fs::read_dir(source).unwrap() {
+        let entry = entry.unwrap();
+        let path = dest.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &path);
+        } else {
+            fs::copy(entry.path(), path).unwrap();
+        }
+    }
+}
+
+/// Run the binary in a crate directory
+fn tomplate(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tomplate"))
+        .arg("--manifest-dir")
+        .arg(dir)
+        .args(args)
+        .env_remove("CARGO_TARGET_DIR")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_render() {
+    let dir = fixture("project");
+
+    // Patterns come from `[package.metadata.tomplate]`, references resolve
+    let output = tomplate(&dir, &["render", "select_user", "--param", "condition=id = $1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "SELECT id, name FROM users WHERE id = $1\n");
+
+    // Parameters that parse as JSON keep their type
+    let output = tomplate(&dir, &["render", "greeting", "-p", "name=Alice", "-p", "admin=false"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Hello Alice!\n\n");
+
+    let output = tomplate(&dir, &["render", "limited", "-p", "limit=10"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "SELECT * FROM posts LIMIT 10\n");
+
+    let output = tomplate(&dir, &["render", "missing"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Template not found: missing"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_check() {
+    let output = tomplate(&fixture("project"), &["check", "--deny-warnings"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(
+        stderr(&output).contains("Checked 4 template(s) in 1 file(s): 0 error(s), 0 warning(s)"),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = tomplate(&fixture("broken"), &["check"]);
+    assert!(!output.status.success());
+    let report = stderr(&output);
+    assert!(report.contains("error: select_user"), "{}", report);
+    assert!(report.contains("referenced template not found: @missing_fields"), "{}", report);
+    assert!(report.contains("warning: unused_param"), "{}", report);
+    assert!(report.contains("1 error(s), 1 warning(s)"), "{}", report);
+}
+
+#[test]
+fn test_fmt_check() {
+    let output = tomplate(&fixture("project"), &["fmt", "--check"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+
+    let dir = fixture("unformatted");
+    let source = fs::read_to_string(dir.join("layout.tomplate.toml")).unwrap();
+    let output = tomplate(&dir, &["fmt", "--check"]);
+    assert!(!output.status.success());
+    assert_eq!(stdout(&output).trim_end(), "layout.tomplate.toml");
+    assert!(stderr(&output).contains("1 template file(s) need formatting"), "{}", stderr(&output));
+    // Checking leaves the file alone
+    assert_eq!(fs::read_to_string(dir.join("layout.tomplate.toml")).unwrap(), source);
+
+    let output = tomplate(&dir, &["fmt"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(dir.join("layout.tomplate.toml")).unwrap(),
+        "[greeting]\ntemplate = \"\"\"\nHello {{name}}!\nBye {{name}}.\"\"\"\nengine = \"handlebars\"\nparams = [\"name\"]\n"
+    );
+    let output = tomplate(&dir, &["fmt", "--check"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn test_import() {
+    let dir = fixture("import");
+    let output = tomplate(&dir, &["import", "sql"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Imported 2 template(s) into sql.tomplate.toml"), "{}", stderr(&output));
+    assert_eq!(
+        fs::read_to_string(dir.join("sql.tomplate.toml")).unwrap(),
+        "[greeting]\ntemplate = \"\"\"\nHello {{name}}!\n\"\"\"\nengine = \"handlebars\"\nparams = [\"name\"]\n\n\
+         [users_select_by_id]\ntemplate = \"SELECT * FROM users WHERE id = {id}\"\nparams = [\"id\"]\n"
+    );
+
+    // The imported templates render, and importing again needs --force
+    let output = tomplate(&dir, &["render", "users_select_by_id", "-p", "id=7"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "SELECT * FROM users WHERE id = 7\n");
+
+    let output = tomplate(&dir, &["import", "sql"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("already exists, pass --force"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_diff() {
+    // Templates discovered with the default pattern, in a repository of
+    // their own
+    let dir = fixture("unformatted");
+    assert!(tomplate(&dir, &["fmt"]).status.success());
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=tomplate", "-c", "user.email=tomplate@example.com"])
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "templates"]);
+    let source = fs::read_to_string(dir.join("layout.tomplate.toml")).unwrap();
+    fs::write(dir.join("layout.tomplate.toml"), source.replace("Bye", "Goodbye")).unwrap();
+
+    let output = tomplate(&dir, &["diff", "--base", "HEAD"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("-Bye {name}."), "{}", stdout(&output));
+    assert!(stdout(&output).contains("+Goodbye {name}."), "{}", stdout(&output));
+    assert!(stdout(&output).contains("+Goodbye {name}."), "{}", stdout(&output));
+    assert!(stderr(&output).contains("1 template(s) render differently than at HEAD"), "{}", stderr(&output));
+
+    // The copies of the base revision's files aren't left for later
+    // commands to discover, and nothing under `target/` is discovered
+    assert!(!dir.join("target/tomplate/base/files").exists());
+    fs::write(dir.join("target/tomplate/stale.tomplate.toml"), &source).unwrap();
+    let output = tomplate(&dir, &["list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).lines().count(), 1, "{}", stdout(&output));
+}
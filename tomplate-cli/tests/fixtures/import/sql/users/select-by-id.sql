@@ -0,0 +1 @@
+SELECT * FROM users WHERE id = {id}
\ No newline at end of file
@@ -0,0 +1,92 @@
+//! Opt-in on-disk cache of rendered templates, shared across builds.
+//!
+//! Enabled by the builder through `TOMPLATE_CACHE_DIR`. Entries are keyed by
+//! the render inputs together with a fingerprint of the registry and
+//! Handlebars helper scripts, so editing any template invalidates them, and
+//! with the version and features of this crate, so upgrading or enabling
+//! another engine does too.
+
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+// The cache directory, if enabled and creatable
+static DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    let dir = PathBuf::from(std::env::var_os("TOMPLATE_CACHE_DIR")?);
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+});
+
+// Fingerprint of the Handlebars helper scripts, which are loaded once per
+// compiler process
+static HELPERS: LazyLock<u64> = LazyLock::new(|| {
+    let mut hasher = DefaultHasher::new();
+    if let Some(helpers) = crate::settings::var("TOMPLATE_HANDLEBARS_HELPERS") {
+        let paths: std::collections::BTreeMap<String, PathBuf> =
+            serde_json::from_str(&helpers).unwrap_or_default();
+        for (name, path) in paths {
+            name.hash(&mut hasher);
+            fs::read(path).unwrap_or_default().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+});
+
+// The version and enabled features, which decide how templates render
+static ENGINES: LazyLock<String> = LazyLock::new(|| {
+    let features = [
+        ("simple", cfg!(feature = "simple")),
+        ("handlebars", cfg!(feature = "handlebars")),
+        ("handlebars-scripts", cfg!(feature = "handlebars-scripts")),
+        ("tera", cfg!(feature = "tera")),
+        ("minijinja", cfg!(feature = "minijinja")),
+        ("fluent", cfg!(feature = "fluent")),
+        ("sqlparser", cfg!(feature = "sqlparser")),
+        ("yaml", cfg!(feature = "yaml")),
+        ("graphql", cfg!(feature = "graphql")),
+        ("naga", cfg!(feature = "naga")),
+        ("markdown", cfg!(feature = "markdown")),
+    ];
+    let enabled: Vec<&str> = features.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+    format!("{}+{}", env!("CARGO_PKG_VERSION"), enabled.join(","))
+});
+
+/// The file holding an entry, and the full key stored in it to rule out
+/// hash collisions
+fn entry(key: &str) -> Option<(PathBuf, String)> {
+    let dir = DIR.as_ref()?;
+    // Everything outside the render inputs that affects output
+    let key = format!("{}\0{:016x}{:016x}\0{}", *ENGINES, crate::templates::fingerprint(), *HELPERS, key);
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    Some((dir.join(format!("{:016x}.json", hasher.finish())), key))
+}
+
+/// Look up a rendered template
+pub fn load(key: &str) -> Option<String> {
+    let (path, key) = entry(key)?;
+    let stored: serde_json::Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    if stored["key"] != key.as_str() {
+        return None;
+    }
+    stored["output"].as_str().map(str::to_string)
+}
+
+/// Store a rendered template, ignoring failures since the cache is only an
+/// optimization
+pub fn store(key: &str, output: &str) {
+    let Some((path, key)) = entry(key) else {
+        return;
+    };
+    
+    // Write to a temporary file first so concurrent expansions never read a
+    // partial entry
+    let temp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let contents = json!({ "key": key, "output": output }).to_string();
+    if fs::write(&temp, contents).is_ok() && fs::rename(&temp, &path).is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+}
@@ -0,0 +1,112 @@
+//! Engines backed by external commands, registered in the build script with
+//! `Builder::custom_engine`.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Look up the command registered for a custom engine name
+pub fn command(name: &str) -> Option<String> {
+    let engines = crate::settings::var("TOMPLATE_CUSTOM_ENGINES")?;
+    let mut engines: HashMap<String, String> = serde_json::from_str(&engines).ok()?;
+    engines.remove(name)
+}
+
+/// Render a template by piping it to the engine's command
+///
+/// The command receives `{"template": ..., "params": ...}` as JSON on stdin
+/// and writes the rendered output to stdout.
+pub fn process(
+    command: &str,
+    template: &str,
+    params: &HashMap<String, Value>,
+) -> Result<String, String> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| "Custom engine command is empty".to_string())?;
+    
+    // Paths like `scripts/render.py` are relative to the crate, bare names use PATH
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let program = if program.contains(['/', '\\']) {
+        manifest_dir.join(program)
+    } else {
+        PathBuf::from(program)
+    };
+    
+    let input = serde_json::json!({
+        "template": template,
+        "params": super::nested_params(params)?,
+    })
+    .to_string();
+    
+    let mut child = Command::new(&program)
+        .args(parts)
+        .current_dir(&manifest_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run custom engine '{}': {}", command, e))?;
+    
+    // Write from another thread so a command that streams output can't deadlock
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run custom engine '{}': {}", command, e))?;
+    let written = writer
+        .join()
+        .map_err(|_| format!("Failed to write to custom engine '{}'", command))?;
+    
+    // A command that fails early stops reading, so its status explains more
+    // than the broken pipe
+    if !output.status.success() {
+        return Err(format!(
+            "Custom engine '{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    written.map_err(|e| format!("Failed to write to custom engine '{}': {}", command, e))?;
+    
+    String::from_utf8(output.stdout)
+        .map_err(|_| format!("Custom engine '{}' produced invalid UTF-8", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::{self, Options};
+    
+    #[test]
+    fn test_custom_engine() {
+        let engines = r#"{"echo": "cat", "replace": "sed s/Hi/Hello/", "failing": "false"}"#;
+        let settings = HashMap::from([("TOMPLATE_CUSTOM_ENGINES".to_string(), engines.to_string())]);
+        crate::settings::scoped(&settings, || {
+            assert_eq!(command("echo").as_deref(), Some("cat"));
+            assert_eq!(command("unknown"), None);
+            
+            // The command reads the template and nested params as JSON
+            let params = HashMap::from([("user.name".to_string(), Value::from("Alice"))]);
+            let output = engines::process("echo", "Hi {{user.name}}", &params, &Options::default()).unwrap();
+            assert_eq!(output, r#"{"params":{"user":{"name":"Alice"}},"template":"Hi {{user.name}}"}"#);
+            
+            // Arguments are split on whitespace
+            let output = engines::process("replace", "Hi", &HashMap::new(), &Options::default()).unwrap();
+            assert_eq!(output, r#"{"params":{},"template":"Hello"}"#);
+            
+            let error = engines::process("failing", "", &HashMap::new(), &Options::default()).unwrap_err();
+            assert!(error.starts_with("Custom engine 'false' failed"), "{}", error);
+            
+            let error = engines::process("unknown", "", &HashMap::new(), &Options::default()).unwrap_err();
+            assert_eq!(error, "Unknown or disabled template engine: unknown");
+        });
+    }
+}
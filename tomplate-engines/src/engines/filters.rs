@@ -0,0 +1,267 @@
+//! Built-in filters for the simple engine, e.g. `{name|upper}`.
+//!
+//! The quoting filters in [`SHARED`] are also registered as helpers and
+//! filters for the Handlebars, Tera and MiniJinja engines.
+
+use super::Escape;
+use serde_json::Value;
+
+/// A quoting filter, rendering a value as quoted text
+pub type Quote = fn(&Value) -> String;
+
+/// Quoting filters shared with the Handlebars, Tera and MiniJinja engines
+pub const SHARED: &[(&str, Quote)] = &[
+    ("sql_str", sql_str),
+    ("sql_ident", sql_ident),
+    ("sql_in", sql_in),
+    ("json", json),
+    ("shell", shell),
+];
+
+/// Render a parameter value as text, joining lists with `separator`
+///
+/// Strings are used as-is, numbers and booleans are formatted, and objects
+/// are written as JSON.
+pub fn to_text(value: &Value, separator: &str) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| to_text(item, separator))
+            .collect::<Vec<_>>()
+            .join(separator),
+        value => value.to_string(),
+    }
+}
+
+/// Apply a filter with an optional argument to a value
+///
+/// Filters other than `join`, `repeat`, `default`, `sql_in` and `shell` are
+/// applied to each item of a list value.
+pub fn apply(name: &str, arg: Option<&str>, value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Array(items)
+            if !matches!(name, "join" | "repeat" | "default" | "sql_in" | "shell") =>
+        {
+            items
+                .iter()
+                .map(|item| apply(name, arg, item))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        _ => apply_one(name, arg, value),
+    }
+}
+
+/// Apply a filter to a single value or, for list-aware filters, a whole list
+fn apply_one(name: &str, arg: Option<&str>, value: &Value) -> Result<Value, String> {
+    let text = to_text(value, ", ");
+    let result = match (name, arg) {
+        ("upper", None) => text.to_uppercase(),
+        ("lower", None) => text.to_lowercase(),
+        ("trim", None) => text.trim().to_string(),
+        ("snake", None) => words(&text).join("_").to_lowercase(),
+        ("kebab", None) => words(&text).join("-").to_lowercase(),
+        ("camel", None) => camel_case(&text, false),
+        ("pascal", None) => camel_case(&text, true),
+        ("screaming", None) => words(&text).join("_").to_uppercase(),
+        ("sql_str", None) => sql_str(value),
+        ("sql_ident", None) => sql_ident(value),
+        ("sql_in", None) => sql_in(value),
+        ("json", None) => json(value),
+        ("shell", None) => shell(value),
+        ("join", Some(separator)) => match value {
+            Value::Array(_) => to_text(value, separator),
+            // Plain values are treated as comma-separated lists
+            _ => text
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<_>>()
+                .join(separator),
+        },
+        // Produces a list, e.g. `{ids|repeat:?}` renders as `?, ?, ?`
+        ("repeat", Some(item)) => {
+            let count = match value {
+                Value::Array(items) => items.len(),
+                _ => text.trim().parse::<usize>().map_err(|_| {
+                    format!("Filter 'repeat' expects a count or a list, got '{}'", text)
+                })?,
+            };
+            return Ok(Value::Array(vec![Value::from(item); count]));
+        }
+        ("default", Some(fallback)) => {
+            let empty = match value {
+                Value::Null => true,
+                Value::String(value) => value.is_empty(),
+                Value::Array(items) => items.is_empty(),
+                _ => false,
+            };
+            if !empty {
+                return Ok(value.clone());
+            }
+            fallback.to_string()
+        }
+        ("join" | "repeat" | "default", None) => {
+            return Err(format!("Filter '{}' requires an argument, e.g. '{}:, '", name, name));
+        }
+        (
+            "upper" | "lower" | "trim" | "snake" | "kebab" | "camel" | "pascal" | "screaming"
+            | "sql_str" | "sql_ident" | "sql_in" | "json" | "shell",
+            Some(_),
+        ) => {
+            return Err(format!("Filter '{}' does not take an argument", name));
+        }
+        _ => return Err(format!("Unknown filter: {}", name)),
+    };
+    
+    Ok(Value::String(result))
+}
+
+/// Quote a value as an SQL string literal, doubling embedded quotes
+pub fn sql_str(value: &Value) -> String {
+    format!("'{}'", to_text(value, ", ").replace('\'', "''"))
+}
+
+/// Quote a value as an SQL identifier, doubling embedded quotes
+///
+/// The whole value is one identifier, so `schema.table` needs two calls.
+pub fn sql_ident(value: &Value) -> String {
+    format!("\"{}\"", to_text(value, ", ").replace('"', "\"\""))
+}
+
+/// Render a list as a parenthesized SQL list for `IN`, e.g. `('a', 'b', 3)`
+///
+/// Strings are quoted, numbers and booleans are kept as literals and null
+/// becomes `NULL`. Plain values are treated as comma-separated lists. An
+/// empty list renders as `(NULL)`, which matches no rows but stays valid SQL.
+pub fn sql_in(value: &Value) -> String {
+    let items: Vec<String> = match value {
+        Value::Array(items) => items.iter().map(sql_literal).collect(),
+        _ => to_text(value, ", ")
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| sql_str(&Value::from(item)))
+            .collect(),
+    };
+    
+    if items.is_empty() {
+        "(NULL)".to_string()
+    } else {
+        format!("({})", items.join(", "))
+    }
+}
+
+/// Render a value as an SQL literal
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(true) => "TRUE".to_string(),
+        Value::Bool(false) => "FALSE".to_string(),
+        Value::Number(number) => number.to_string(),
+        value => sql_str(value),
+    }
+}
+
+/// Escape a value for use inside a JSON string literal, without the quotes
+pub fn json(value: &Value) -> String {
+    let quoted = Value::String(to_text(value, ", ")).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Quote a value as POSIX shell words, one word per list item
+pub fn shell(value: &Value) -> String {
+    match value {
+        Value::Array(items) => items
+            .iter()
+            .map(shell)
+            .collect::<Vec<_>>()
+            .join(" "),
+        value => shell_quote(&to_text(value, " ")),
+    }
+}
+
+/// Quote text as a single POSIX shell word
+///
+/// Text made only of characters the shell never interprets is kept as-is,
+/// anything else is single-quoted with embedded quotes written as `'\''`.
+pub fn shell_quote(value: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-.,:/@%+=".contains(c);
+    if !value.is_empty() && value.chars().all(plain) {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Escape substituted text according to an escape mode
+pub fn escape(mode: Escape, value: &str) -> String {
+    match mode {
+        Escape::None => value.to_string(),
+        Escape::Html => escape_html(value),
+        Escape::Shell => shell_quote(value),
+    }
+}
+
+/// Escape text for HTML output
+pub fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Split a value into words on separators and lower-to-upper case boundaries
+pub fn words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    
+    for ch in value.chars() {
+        if !ch.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if ch.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = ch.is_lowercase() || ch.is_numeric();
+        current.push(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    
+    words
+}
+
+/// Join words in camelCase, or PascalCase when `capitalize_first` is set
+pub fn camel_case(value: &str, capitalize_first: bool) -> String {
+    words(value)
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let word = word.to_lowercase();
+            if index == 0 && !capitalize_first {
+                return word;
+            }
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,109 @@
+use fluent::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use fluent_syntax::ast;
+use serde_json::Value;
+use std::collections::HashMap;
+use unic_langid::LanguageIdentifier;
+
+/// Render the first message of a Fluent resource for a locale
+///
+/// Other messages and terms in the resource can be referenced from the first
+/// one. Numeric parameters are passed as numbers so plural rules apply.
+pub fn process(
+    template: &str,
+    params: &HashMap<String, Value>,
+    locale: Option<&str>,
+) -> Result<String, String> {
+    let locale = locale.unwrap_or(super::plural::DEFAULT_LOCALE);
+    let langid: LanguageIdentifier = locale
+        .parse()
+        .map_err(|e| format!("Invalid Fluent locale '{}': {}", locale, e))?;
+    
+    let resource = FluentResource::try_new(template.to_string())
+        .map_err(|(_, errors)| format!("Fluent syntax error: {:?}", errors))?;
+    
+    // The first message is the one being rendered
+    let id = resource
+        .entries()
+        .find_map(|entry| match entry {
+            ast::Entry::Message(message) => Some(message.id.name.to_string()),
+            _ => None,
+        })
+        .ok_or_else(|| "Fluent template contains no messages".to_string())?;
+    
+    let mut bundle = FluentBundle::new(vec![langid]);
+    
+    // Unicode isolation marks around placeables aren't wanted in constants
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|errors| format!("Fluent resource error: {:?}", errors))?;
+    
+    let pattern = bundle
+        .get_message(&id)
+        .and_then(|message| message.value())
+        .ok_or_else(|| format!("Fluent message '{}' has no value", id))?;
+    
+    let mut args = FluentArgs::new();
+    for (key, value) in params {
+        let text = super::filters::to_text(value, ", ");
+        match value.as_f64().or_else(|| plain_integer(&text)) {
+            Some(number) => args.set(key.as_str(), FluentValue::from(number)),
+            None => args.set(key.as_str(), FluentValue::from(text)),
+        }
+    }
+    
+    let mut errors = Vec::new();
+    let value = bundle.format_pattern(pattern, Some(&args), &mut errors);
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        return Err(format!("Fluent error: {}", errors.join(", ")));
+    }
+    
+    Ok(value.into_owned())
+}
+
+/// A string holding a plain decimal integer, e.g. from a nested template, so
+/// it still selects plurals
+///
+/// Other numeric-looking strings, such as `02134` or `1e3`, are text that
+/// formatting as a number would change.
+fn plain_integer(text: &str) -> Option<f64> {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let plain = !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'));
+    plain.then(|| text.parse().ok()).flatten()
+}
+
+/// Parse a Fluent resource, reporting syntax errors and a missing message
+pub fn check(template: &str) -> Result<(), String> {
+    let resource = FluentResource::try_new(template.to_string())
+        .map_err(|(_, errors)| format!("Fluent syntax error: {:?}", errors))?;
+    if !resource.entries().any(|entry| matches!(entry, ast::Entry::Message(_))) {
+        return Err("Fluent template contains no messages".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, value: Value) -> String {
+        process(template, &HashMap::from([("value".to_string(), value)]), None).unwrap()
+    }
+
+    #[test]
+    fn test_numeric_strings() {
+        let plural = "items = { $value ->\n    [one] one item\n   *[other] { $value } items\n}\n";
+        assert_eq!(render(plural, Value::from(1)), "one item");
+        assert_eq!(render(plural, Value::from("1")), "one item");
+        assert_eq!(render(plural, Value::from("12")), "12 items");
+
+        // Strings that aren't plain integers are kept as written
+        let zip = "zip = Zip: { $value }\n";
+        assert_eq!(render(zip, Value::from("02134")), "Zip: 02134");
+        assert_eq!(render(zip, Value::from("1e3")), "Zip: 1e3");
+        assert_eq!(render(zip, Value::from("1.50")), "Zip: 1.50");
+    }
+}
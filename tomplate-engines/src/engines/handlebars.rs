@@ -0,0 +1,128 @@
+use super::{Escape, Options};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Handlebars with the built-in and project helpers registered, built once
+/// and cloned for every expansion
+static HANDLEBARS: LazyLock<Result<handlebars::Handlebars<'static>, String>> =
+    LazyLock::new(|| {
+        let mut handlebars = handlebars::Handlebars::new();
+        
+        // Built-in quoting helpers, e.g. `{{sql_ident table}}` or `{{json name}}`
+        handlebars.register_helper("sql_str", Box::new(sql_str));
+        handlebars.register_helper("sql_ident", Box::new(sql_ident));
+        handlebars.register_helper("sql_in", Box::new(sql_in));
+        handlebars.register_helper("json", Box::new(json));
+        
+        // Project helpers registered with `Builder::handlebars_helper`, which
+        // may replace the built-in ones
+        register_helpers(&mut handlebars)?;
+        Ok(handlebars)
+    });
+
+pub fn process(
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+) -> Result<String, String> {
+    let mut handlebars = HANDLEBARS.as_ref().map_err(Clone::clone)?.clone();
+    
+    // Disable HTML escaping for SQL and other non-HTML templates
+    match options.escaping() {
+        Escape::None => handlebars.register_escape_fn(handlebars::no_escape),
+        Escape::Html => {}
+        Escape::Shell => handlebars.register_escape_fn(super::filters::shell_quote),
+    }
+    handlebars.set_strict_mode(options.strict);
+    
+    // Locale-aware helpers for the template's locale, e.g.
+    // `{{plural count one="item" other="items"}}` or `{{currency price "EUR"}}`
+    let locale = options.locale.as_deref().unwrap_or(super::plural::DEFAULT_LOCALE);
+    for helper in super::localized::HELPERS {
+        handlebars.register_helper(helper.name, Box::new(Localized { locale: locale.to_string(), helper }));
+    }
+    
+    // Convert params to serde_json::Value for Handlebars
+    let json_data = super::nested_params(params)?;
+    
+    handlebars
+        .render_template(template, &json_data)
+        .map_err(|e| format!("Handlebars error: {}", e))
+}
+
+/// Parse a template, reporting Handlebars syntax errors
+pub fn check(template: &str) -> Result<(), String> {
+    handlebars::Template::compile(template)
+        .map(drop)
+        .map_err(|e| format!("Handlebars error: {}", e))
+}
+
+handlebars::handlebars_helper!(sql_str: |value: Json| super::filters::sql_str(value));
+handlebars::handlebars_helper!(sql_ident: |value: Json| super::filters::sql_ident(value));
+handlebars::handlebars_helper!(sql_in: |value: Json| super::filters::sql_in(value));
+handlebars::handlebars_helper!(json: |value: Json| super::filters::json(value));
+
+/// A locale-aware helper bound to the template's locale
+struct Localized {
+    locale: String,
+    helper: &'static super::localized::Helper,
+}
+
+impl handlebars::HelperDef for Localized {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &handlebars::Helper<'rc>,
+        _: &'reg handlebars::Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut handlebars::RenderContext<'reg, 'rc>,
+    ) -> Result<handlebars::ScopedJson<'rc>, handlebars::RenderError> {
+        let value = helper
+            .param(0)
+            .ok_or(handlebars::RenderErrorReason::ParamNotFoundForIndex(self.helper.name, 0))?
+            .value();
+        let mut args: serde_json::Map<String, Value> = helper
+            .hash()
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.value().clone()))
+            .collect();
+        
+        // A second parameter is the helper's positional argument, e.g. the
+        // currency code of `{{currency price "EUR"}}`
+        if let Some(param) = helper.param(1) {
+            let name = self.helper.positional.ok_or_else(|| {
+                handlebars::RenderErrorReason::Other(format!("'{}' takes a single parameter", self.helper.name))
+            })?;
+            args.insert(name.to_string(), param.value().clone());
+        }
+        
+        self.helper
+            .call(&self.locale, value, args)
+            .map(|text| handlebars::ScopedJson::Derived(Value::String(text)))
+            .map_err(|e| handlebars::RenderErrorReason::Other(e).into())
+    }
+}
+
+/// Register the Rhai script helpers listed by the build script
+#[cfg(feature = "handlebars-scripts")]
+fn register_helpers(handlebars: &mut handlebars::Handlebars) -> Result<(), String> {
+    let Some(helpers) = crate::settings::var("TOMPLATE_HANDLEBARS_HELPERS") else {
+        return Ok(());
+    };
+    let helpers: HashMap<String, String> = serde_json::from_str(&helpers)
+        .map_err(|e| format!("Invalid TOMPLATE_HANDLEBARS_HELPERS: {}", e))?;
+    
+    for (name, path) in helpers {
+        handlebars
+            .register_script_helper_file(&name, &path)
+            .map_err(|e| format!("Handlebars helper '{}' error: {}", name, e))?;
+    }
+    Ok(())
+}
+
+/// Script helpers need the `handlebars-scripts` feature, without it only the
+/// built-in helpers are available
+#[cfg(not(feature = "handlebars-scripts"))]
+fn register_helpers(_handlebars: &mut handlebars::Handlebars) -> Result<(), String> {
+    Ok(())
+}
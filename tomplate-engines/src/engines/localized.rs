@@ -0,0 +1,362 @@
+//! Helpers that depend on the template's locale.
+//!
+//! The Handlebars, Tera and MiniJinja engines register each [`Helper`] in
+//! [`HELPERS`], rendered for the template's `locale` unless a call passes
+//! `locale` itself:
+//!
+//! - `plural` and `plural_category` select CLDR plural forms, see
+//!   [`plural`](super::plural)
+//! - `format_number` groups digits and writes the decimal separator, with
+//!   an optional number of `decimals`
+//! - `currency` formats an amount in a currency given by its ISO 4217 code,
+//!   e.g. `{{ price | currency("EUR") }}`
+//! - `format_date` writes an ISO 8601 date, e.g. `2024-03-15`, in the
+//!   locale's long form, e.g. `{{ released | format_date("de") }}`
+//!
+//! Conventions are built in for `en`, `en-GB`, `de`, `fr`, `es`, `it`,
+//! `pt`, `nl`, `pl`, `sv` and `ja`; other regions use their language's.
+
+use serde_json::{Map, Value};
+
+/// A helper's implementation, given the locale, the value and the named
+/// arguments
+type Apply = fn(&str, &Value, &Map<String, Value>) -> Result<String, String>;
+
+/// A locale-aware helper
+pub struct Helper {
+    pub name: &'static str,
+    /// Name of the argument a positional argument is passed as, e.g. the
+    /// currency code of `currency("EUR")`
+    pub positional: Option<&'static str>,
+    apply: Apply,
+}
+
+/// The helpers shared by the Handlebars, Tera and MiniJinja engines
+pub const HELPERS: &[Helper] = &[
+    Helper {
+        name: "plural",
+        positional: None,
+        apply: |locale, count, forms| super::plural::select(locale, count, forms),
+    },
+    Helper {
+        name: "plural_category",
+        positional: None,
+        apply: |locale, count, _| super::plural::category(locale, count).map(str::to_string),
+    },
+    Helper {
+        name: "format_number",
+        positional: Some("decimals"),
+        apply: |locale, value, args| {
+            let decimals = match args.get("decimals") {
+                Some(decimals) => Some(decimals.as_u64().ok_or_else(|| {
+                    format!("'decimals' must be a whole number, got {}", decimals)
+                })? as usize),
+                None => None,
+            };
+            format_number(locale, value, decimals)
+        },
+    },
+    Helper {
+        name: "currency",
+        positional: Some("code"),
+        apply: |locale, value, args| {
+            let code = args
+                .get("code")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "'currency' needs a currency code, e.g. currency(\"EUR\")".to_string())?;
+            currency(locale, value, code)
+        },
+    },
+    Helper {
+        name: "format_date",
+        positional: Some("locale"),
+        apply: |locale, value, _| format_date(locale, value),
+    },
+];
+
+impl Helper {
+    /// Call the helper with its arguments by name, for the template's locale
+    /// unless a `locale` argument is given
+    pub fn call(&self, locale: &str, value: &Value, mut args: Map<String, Value>) -> Result<String, String> {
+        let locale = match args.remove("locale") {
+            Some(Value::String(locale)) => locale,
+            Some(other) => return Err(format!("'locale' must be a string, got {}", other)),
+            None => locale.to_string(),
+        };
+        (self.apply)(&locale, value, &args).map_err(|e| format!("{}: {}", self.name, e))
+    }
+}
+
+/// Number, currency and date conventions of a locale
+struct Conventions {
+    /// A locale tag, or a language for all of its regions
+    tag: &'static str,
+    decimal: &'static str,
+    group: &'static str,
+    /// Integer digits needed before digits are grouped, e.g. 5 when `1234`
+    /// stays ungrouped
+    min_grouping: usize,
+    /// Where the amount `#` and the currency symbol `¤` go
+    currency: &'static str,
+    /// Long date pattern with `{day}`, `{month}` and `{year}`
+    date: &'static str,
+    months: [&'static str; 12],
+}
+
+const CONVENTIONS: &[Conventions] = &[
+    Conventions {
+        tag: "en",
+        decimal: ".",
+        group: ",",
+        min_grouping: 4,
+        currency: "¤#",
+        date: "{month} {day}, {year}",
+        months: [
+            "January", "February", "March", "April", "May", "June", "July", "August", "September",
+            "October", "November", "December",
+        ],
+    },
+    Conventions {
+        tag: "en-GB",
+        decimal: ".",
+        group: ",",
+        min_grouping: 4,
+        currency: "¤#",
+        date: "{day} {month} {year}",
+        months: [
+            "January", "February", "March", "April", "May", "June", "July", "August", "September",
+            "October", "November", "December",
+        ],
+    },
+    Conventions {
+        tag: "de",
+        decimal: ",",
+        group: ".",
+        min_grouping: 4,
+        currency: "#\u{a0}¤",
+        date: "{day}. {month} {year}",
+        months: [
+            "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September",
+            "Oktober", "November", "Dezember",
+        ],
+    },
+    Conventions {
+        tag: "fr",
+        decimal: ",",
+        group: "\u{202f}",
+        min_grouping: 4,
+        currency: "#\u{a0}¤",
+        date: "{day} {month} {year}",
+        months: [
+            "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre",
+            "octobre", "novembre", "décembre",
+        ],
+    },
+    Conventions {
+        tag: "es",
+        decimal: ",",
+        group: ".",
+        min_grouping: 5,
+        currency: "#\u{a0}¤",
+        date: "{day} de {month} de {year}",
+        months: [
+            "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre",
+            "octubre", "noviembre", "diciembre",
+        ],
+    },
+    Conventions {
+        tag: "it",
+        decimal: ",",
+        group: ".",
+        min_grouping: 4,
+        currency: "#\u{a0}¤",
+        date: "{day} {month} {year}",
+        months: [
+            "gennaio", "febbraio", "marzo", "aprile", "maggio", "giugno", "luglio", "agosto",
+            "settembre", "ottobre", "novembre", "dicembre",
+        ],
+    },
+    Conventions {
+        tag: "pt",
+        decimal: ",",
+        group: ".",
+        min_grouping: 4,
+        currency: "¤\u{a0}#",
+        date: "{day} de {month} de {year}",
+        months: [
+            "janeiro", "fevereiro", "março", "abril", "maio", "junho", "julho", "agosto", "setembro",
+            "outubro", "novembro", "dezembro",
+        ],
+    },
+    Conventions {
+        tag: "nl",
+        decimal: ",",
+        group: ".",
+        min_grouping: 4,
+        currency: "¤\u{a0}#",
+        date: "{day} {month} {year}",
+        months: [
+            "januari", "februari", "maart", "april", "mei", "juni", "juli", "augustus", "september",
+            "oktober", "november", "december",
+        ],
+    },
+    Conventions {
+        tag: "pl",
+        decimal: ",",
+        group: "\u{a0}",
+        min_grouping: 5,
+        currency: "#\u{a0}¤",
+        date: "{day} {month} {year}",
+        months: [
+            "stycznia", "lutego", "marca", "kwietnia", "maja", "czerwca", "lipca", "sierpnia",
+            "września", "października", "listopada", "grudnia",
+        ],
+    },
+    Conventions {
+        tag: "sv",
+        decimal: ",",
+        group: "\u{a0}",
+        min_grouping: 4,
+        currency: "#\u{a0}¤",
+        date: "{day} {month} {year}",
+        months: [
+            "januari", "februari", "mars", "april", "maj", "juni", "juli", "augusti", "september",
+            "oktober", "november", "december",
+        ],
+    },
+    Conventions {
+        tag: "ja",
+        decimal: ".",
+        group: ",",
+        min_grouping: 4,
+        currency: "¤#",
+        date: "{year}年{month}月{day}日",
+        months: ["1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12"],
+    },
+];
+
+/// Symbols of common currencies, others are written as their code
+const SYMBOLS: &[(&str, &str)] = &[
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("JPY", "¥"),
+    ("CNY", "¥"),
+    ("INR", "₹"),
+    ("KRW", "₩"),
+    ("PLN", "zł"),
+    ("SEK", "kr"),
+    ("BRL", "R$"),
+];
+
+/// Currencies without minor units
+const WHOLE_CURRENCIES: &[&str] = &["JPY", "KRW"];
+
+/// The conventions for a locale, or for its language
+fn conventions(locale: &str) -> Result<&'static Conventions, String> {
+    let locale = locale.replace('_', "-");
+    let language = locale.split('-').next().unwrap_or_default();
+    CONVENTIONS
+        .iter()
+        .find(|conventions| conventions.tag.eq_ignore_ascii_case(&locale))
+        .or_else(|| CONVENTIONS.iter().find(|conventions| conventions.tag.eq_ignore_ascii_case(language)))
+        .ok_or_else(|| format!("No number and date formats for locale '{}'", locale))
+}
+
+/// Format a number with the locale's separators, rounded to `decimals`
+/// digits if given and otherwise with the digits it was written with
+pub fn format_number(locale: &str, value: &Value, decimals: Option<usize>) -> Result<String, String> {
+    let conventions = conventions(locale)?;
+    let (negative, digits) = number_digits(value, decimals)?;
+    let formatted = separate(conventions, &digits);
+    Ok(if negative { format!("-{}", formatted) } else { formatted })
+}
+
+/// Format an amount of a currency, e.g. `1234.5` in `EUR` as `1.234,50 €`
+/// for `de`
+pub fn currency(locale: &str, value: &Value, code: &str) -> Result<String, String> {
+    let conventions = conventions(locale)?;
+    let code = code.to_uppercase();
+    let decimals = if WHOLE_CURRENCIES.contains(&code.as_str()) { 0 } else { 2 };
+    let (negative, digits) = number_digits(value, Some(decimals))?;
+    let amount = separate(conventions, &digits);
+
+    let symbol = SYMBOLS
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map_or(code.as_str(), |(_, symbol)| symbol);
+    // Letters are kept apart from the digits, e.g. `CHF 12.00`
+    let pattern = if conventions.currency == "¤#" && symbol.ends_with(|c: char| c.is_alphabetic()) {
+        "¤\u{a0}#"
+    } else {
+        conventions.currency
+    };
+    let formatted = pattern.replace('¤', symbol).replace('#', &amount);
+    Ok(if negative { format!("-{}", formatted) } else { formatted })
+}
+
+/// Format an ISO 8601 date, or the date of a date-time, in the locale's long
+/// form, e.g. `2024-03-15` as `15. März 2024` for `de`
+pub fn format_date(locale: &str, value: &Value) -> Result<String, String> {
+    let conventions = conventions(locale)?;
+    let text = super::filters::to_text(value, ", ");
+    let invalid = || format!("Expected an ISO 8601 date like 2024-03-15, got '{}'", text);
+
+    let date = text.trim().get(..10).ok_or_else(invalid)?;
+    let mut parts = date.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year = year.parse::<u32>().map_err(|_| invalid())?;
+    let month = month.parse::<usize>().ok().filter(|month| (1..=12).contains(month)).ok_or_else(invalid)?;
+    let day = day.parse::<u32>().ok().filter(|day| (1..=31).contains(day)).ok_or_else(invalid)?;
+
+    Ok(conventions
+        .date
+        .replace("{day}", &day.to_string())
+        .replace("{month}", conventions.months[month - 1])
+        .replace("{year}", &year.to_string()))
+}
+
+/// The sign and the unsigned digits of a number, with `.` as the decimal
+/// point
+fn number_digits(value: &Value, decimals: Option<usize>) -> Result<(bool, String), String> {
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.trim().to_string(),
+        _ => return Err(format!("Expected a number, got {}", value)),
+    };
+    let number = text
+        .parse::<f64>()
+        .map_err(|_| format!("Expected a number, got '{}'", text))?;
+
+    let digits = match decimals {
+        Some(decimals) => format!("{:.*}", decimals, number.abs()),
+        None if text.contains(['e', 'E']) => number.abs().to_string(),
+        None => text.trim_start_matches(['-', '+']).to_string(),
+    };
+    // Rounding can leave nothing but zeros, which isn't negative
+    let negative = number < 0.0 && digits.contains(|c: char| c.is_ascii_digit() && c != '0');
+    Ok((negative, digits))
+}
+
+/// Group the integer digits and write the locale's decimal separator
+fn separate(conventions: &Conventions, digits: &str) -> String {
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let mut result = String::new();
+    if integer.len() >= conventions.min_grouping {
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push_str(conventions.group);
+            }
+            result.push(digit);
+        }
+    } else {
+        result.push_str(integer);
+    }
+    if !fraction.is_empty() {
+        result.push_str(conventions.decimal);
+        result.push_str(fraction);
+    }
+    result
+}
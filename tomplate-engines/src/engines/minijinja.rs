@@ -0,0 +1,155 @@
+use super::{Escape, Options};
+use crate::types::Delimiters;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// Name the rendered template is registered under, kept apart from registry names
+const TEMPLATE_NAME: &str = "__tomplate_template";
+
+/// MiniJinja with the registry loader and built-in filters set up, built once
+/// and cloned for every expansion
+static ENVIRONMENT: LazyLock<minijinja::Environment<'static>> = LazyLock::new(|| {
+    let mut env = minijinja::Environment::new();
+    
+    // Built-in quoting filters, e.g. `{{ table | sql_ident }}`
+    for &(name, quote) in super::filters::SHARED {
+        env.add_filter(name, filter(quote));
+    }
+    
+    // Serve the other MiniJinja templates from the registry by name so
+    // `{% include %}`, `{% import %}` and `{% extends %}` can refer to them.
+    // They are compiled on first use by each clone, with its own settings.
+    env.set_loader(|name| {
+        Ok(crate::templates::get(name)
+            .filter(|template| template.engine.as_deref() == Some("minijinja"))
+            .map(|template| template.template.clone()))
+    });
+    env
+});
+
+pub fn process(
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+) -> Result<String, String> {
+    let mut env = ENVIRONMENT.clone();
+    
+    // Templates aren't named after file extensions, so pick escaping explicitly
+    match options.escaping() {
+        Escape::None => env.set_auto_escape_callback(|_| minijinja::AutoEscape::None),
+        Escape::Html => env.set_auto_escape_callback(|_| minijinja::AutoEscape::Html),
+        Escape::Shell => env.set_formatter(shell_formatter),
+    }
+    
+    // Locale-aware filters for the template's locale, e.g.
+    // `{{ count | plural(one="item", other="items") }}` or
+    // `{{ price | currency("EUR") }}`
+    let locale = options.locale.as_deref().unwrap_or(super::plural::DEFAULT_LOCALE);
+    for helper in super::localized::HELPERS {
+        let locale = locale.to_string();
+        env.add_filter(
+            helper.name,
+            move |value: minijinja::Value, arg: Option<minijinja::Value>, kwargs: minijinja::value::Kwargs| {
+                let mut args = serde_json::Map::new();
+                for name in kwargs.args() {
+                    args.insert(name.to_string(), to_json(&kwargs.get::<minijinja::Value>(name)?)?);
+                }
+                if let Some(arg) = arg {
+                    let name = helper.positional.ok_or_else(|| {
+                        render_error(format!("'{}' takes no positional argument", helper.name))
+                    })?;
+                    args.insert(name.to_string(), to_json(&arg)?);
+                }
+                helper.call(&locale, &to_json(&value)?, args).map_err(render_error)
+            },
+        );
+    }
+    
+    if options.strict {
+        env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+    }
+    env.set_trim_blocks(options.trim_blocks);
+    env.set_lstrip_blocks(options.lstrip_blocks);
+    if let Some(keep) = options.keep_trailing_newline {
+        env.set_keep_trailing_newline(keep);
+    }
+    if let Some(delimiters) = &options.delimiters {
+        env.set_syntax(syntax(delimiters))
+            .map_err(|e| format!("MiniJinja syntax error: {}", e))?;
+    }
+    
+    // Add the template
+    env.add_template_owned(TEMPLATE_NAME, template.to_string())
+        .map_err(|e| format!("MiniJinja template error: {}", e))?;
+    
+    // Get the template
+    let tmpl = env.get_template(TEMPLATE_NAME)
+        .map_err(|e| format!("MiniJinja get template error: {}", e))?;
+    
+    // Convert params to minijinja::Value
+    let context = minijinja::Value::from_serialize(&super::nested_params(params)?);
+    
+    tmpl.render(context)
+        .map_err(|e| format!("MiniJinja render error: {}", e))
+}
+
+/// Parse a template with its delimiters, reporting MiniJinja syntax errors
+pub fn check(template: &str, options: &Options) -> Result<(), String> {
+    let mut env = ENVIRONMENT.clone();
+    if let Some(delimiters) = &options.delimiters {
+        env.set_syntax(syntax(delimiters))
+            .map_err(|e| format!("MiniJinja syntax error: {}", e))?;
+    }
+    env.add_template_owned(TEMPLATE_NAME, template.to_string())
+        .map_err(|e| format!("MiniJinja template error: {}", e))
+}
+
+/// Wrap a quoting function as a MiniJinja filter
+fn filter(
+    quote: fn(&Value) -> String,
+) -> impl Fn(minijinja::Value) -> Result<String, minijinja::Error> + Send + Sync + 'static {
+    move |value| Ok(quote(&to_json(&value)?))
+}
+
+/// Convert a MiniJinja value for the shared filters
+fn to_json(value: &minijinja::Value) -> Result<Value, minijinja::Error> {
+    serde_json::to_value(value).map_err(|e| render_error(e.to_string()))
+}
+
+/// A render error with a message from the shared filters
+fn render_error(message: String) -> minijinja::Error {
+    minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, message)
+}
+
+/// Write each output value as a quoted shell word, leaving values marked
+/// safe as they are
+fn shell_formatter(
+    out: &mut minijinja::Output<'_>,
+    _state: &minijinja::State<'_, '_>,
+    value: &minijinja::Value,
+) -> Result<(), minijinja::Error> {
+    if value.is_safe() {
+        write!(out, "{}", value)?;
+    } else {
+        write!(out, "{}", super::filters::shell_quote(&value.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Build the MiniJinja syntax for custom delimiters, keeping the defaults
+/// for any that aren't set
+fn syntax(delimiters: &Delimiters) -> minijinja::Syntax {
+    let delimiter = |value: &Option<String>, default: &'static str| {
+        value.clone().map_or(default.into(), Into::into)
+    };
+    
+    minijinja::Syntax {
+        block_start: delimiter(&delimiters.block_start, "{%"),
+        block_end: delimiter(&delimiters.block_end, "%}"),
+        variable_start: delimiter(&delimiters.variable_start, "{{"),
+        variable_end: delimiter(&delimiters.variable_end, "}}"),
+        comment_start: delimiter(&delimiters.comment_start, "{#"),
+        comment_end: delimiter(&delimiters.comment_end, "#}"),
+    }
+}
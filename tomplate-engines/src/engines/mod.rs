@@ -0,0 +1,581 @@
+pub mod custom;
+pub mod filters;
+pub mod localized;
+pub mod plural;
+pub mod simple;
+
+#[cfg(feature = "handlebars")]
+pub mod handlebars;
+
+#[cfg(feature = "tera")]
+pub mod tera;
+
+#[cfg(feature = "minijinja")]
+pub mod minijinja;
+
+#[cfg(feature = "fluent")]
+pub mod fluent;
+
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+pub use crate::types::Escape;
+use crate::types::{Delimiters, Placeholders, Postprocess, Template};
+
+/// Supported template engines
+pub enum Engine {
+    Simple,
+    #[cfg(feature = "handlebars")]
+    Handlebars,
+    #[cfg(feature = "tera")]
+    Tera,
+    #[cfg(feature = "minijinja")]
+    MiniJinja,
+    #[cfg(feature = "fluent")]
+    Fluent,
+    /// External command registered with `Builder::custom_engine`
+    Custom(String),
+}
+
+impl FromStr for Engine {
+    type Err = String;
+    
+    /// Parse engine from string
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "simple" | "" => Ok(Engine::Simple),
+            #[cfg(feature = "handlebars")]
+            "handlebars" => Ok(Engine::Handlebars),
+            #[cfg(feature = "tera")]
+            "tera" => Ok(Engine::Tera),
+            #[cfg(feature = "minijinja")]
+            "minijinja" => Ok(Engine::MiniJinja),
+            #[cfg(feature = "fluent")]
+            "fluent" => Ok(Engine::Fluent),
+            _ => custom::command(s)
+                .map(Engine::Custom)
+                .ok_or_else(|| format!("Unknown or disabled template engine: {}", s)),
+        }
+    }
+}
+
+impl Engine {
+    /// Process a template with this engine
+    pub fn process(
+        &self,
+        template: &str,
+        params: &HashMap<String, Value>,
+        options: &Options,
+    ) -> Result<String, String> {
+        self.check_options(options)?;
+        
+        options.render_with(template, |template| match self {
+            Engine::Simple => simple::process_with(template, params, options),
+            #[cfg(feature = "handlebars")]
+            Engine::Handlebars => handlebars::process(template, params, options),
+            #[cfg(feature = "tera")]
+            Engine::Tera => tera::process(template, params, options),
+            #[cfg(feature = "minijinja")]
+            Engine::MiniJinja => minijinja::process(template, params, options),
+            #[cfg(feature = "fluent")]
+            Engine::Fluent => fluent::process(template, params, options.locale.as_deref()),
+            Engine::Custom(command) => custom::process(command, template, params),
+        })
+    }
+    
+    /// Parse a template without rendering it, reporting syntax errors and
+    /// options this engine doesn't support
+    ///
+    /// Custom engines are external commands, so only their options are checked.
+    pub fn check(&self, template: &str, options: &Options) -> Result<(), String> {
+        self.check_options(options)?;
+        
+        match self {
+            Engine::Simple => simple::check(template),
+            #[cfg(feature = "handlebars")]
+            Engine::Handlebars => handlebars::check(template),
+            #[cfg(feature = "tera")]
+            Engine::Tera => tera::check(template, options),
+            #[cfg(feature = "minijinja")]
+            Engine::MiniJinja => minijinja::check(template, options),
+            #[cfg(feature = "fluent")]
+            Engine::Fluent => fluent::check(template),
+            Engine::Custom(_) => Ok(()),
+        }
+    }
+    
+    /// Reject options this engine doesn't support
+    fn check_options(&self, options: &Options) -> Result<(), String> {
+        if !matches!(self, Engine::Simple) {
+            if options.indent {
+                return Err("The 'indent' option is only supported by the simple engine".to_string());
+            }
+            if options.partial {
+                return Err("The 'partial' option is only supported by the simple engine".to_string());
+            }
+        }
+        #[cfg(feature = "minijinja")]
+        let custom_delimiters = !matches!(self, Engine::MiniJinja);
+        #[cfg(not(feature = "minijinja"))]
+        let custom_delimiters = true;
+        if options.delimiters.is_some() && custom_delimiters {
+            return Err("Custom delimiters are only supported by the MiniJinja engine".to_string());
+        }
+        let escapes = match self {
+            #[cfg(feature = "fluent")]
+            Engine::Fluent => false,
+            Engine::Custom(_) => false,
+            _ => true,
+        };
+        if options.escaping() != Escape::None && !escapes {
+            return Err(format!(
+                "The '{}' escape mode is not supported by this engine",
+                options.escaping()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Options controlling how a template is rendered
+///
+/// Options are set per template in TOML, or per call with attributes such as
+/// `tomplate!(#[indent] "name", ...)`.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Re-indent multi-line values to the column of their placeholder
+    pub indent: bool,
+    /// Trim surrounding whitespace from the rendered output
+    pub trim: bool,
+    /// Strip the indentation common to all lines of the template
+    pub dedent: bool,
+    /// Strip the indentation common to all lines of the rendered output
+    pub dedent_output: bool,
+    /// Trim surrounding whitespace from the rendered output, as the `trim`
+    /// modifier of a call asks
+    pub trim_output: bool,
+    /// Keep placeholders that have no matching parameter
+    pub partial: bool,
+    /// Fail on undefined variables in the Handlebars and MiniJinja engines
+    pub strict: bool,
+    /// Remove the first newline after a Tera or MiniJinja block tag
+    pub trim_blocks: bool,
+    /// Strip whitespace before a Tera or MiniJinja block tag on its own line
+    pub lstrip_blocks: bool,
+    /// Keep the trailing newline in Tera and MiniJinja output, if set
+    pub keep_trailing_newline: Option<bool>,
+    /// Custom delimiters for MiniJinja templates
+    pub delimiters: Option<Delimiters>,
+    /// Escaping of substituted values, none if unset
+    pub escape: Option<Escape>,
+    /// Locale used by locale-aware engines, e.g. `en-US`
+    pub locale: Option<String>,
+    /// Bind placeholder style the rendered SQL is rewritten to
+    pub placeholders: Option<Placeholders>,
+    /// Step applied to the rendered output, e.g. Markdown to HTML
+    pub postprocess: Option<Postprocess>,
+}
+
+impl Options {
+    /// Read the options set on a registry template
+    pub fn from_template(template: &Template) -> Self {
+        Options {
+            indent: template.indent,
+            trim: template.trim,
+            dedent: template.dedent,
+            dedent_output: false,
+            trim_output: false,
+            partial: template.partial,
+            strict: template.strict.unwrap_or(false),
+            trim_blocks: template.trim_blocks.unwrap_or(false),
+            lstrip_blocks: template.lstrip_blocks.unwrap_or(false),
+            keep_trailing_newline: template.keep_trailing_newline,
+            delimiters: template.delimiters.clone(),
+            escape: template.escape,
+            locale: template.locale.clone(),
+            placeholders: template.placeholders,
+            postprocess: template.postprocess,
+        }
+    }
+    
+    /// Enable an option by name, as written in a call attribute
+    pub fn enable(&mut self, name: &str) -> Result<(), String> {
+        match name {
+            "indent" => self.indent = true,
+            "trim" => self.trim = true,
+            "dedent" => self.dedent = true,
+            "partial" => self.partial = true,
+            "strict" => self.strict = true,
+            "trim_blocks" => self.trim_blocks = true,
+            "lstrip_blocks" => self.lstrip_blocks = true,
+            "keep_trailing_newline" => self.keep_trailing_newline = Some(true),
+            _ => return Err(format!("Unknown template option: {}", name)),
+        }
+        Ok(())
+    }
+    
+    /// Enable an output modifier by name, as written before the template
+    /// name in `tomplate!(dedent trim "name", ...)`
+    ///
+    /// Modifiers apply to the rendered output whatever the template's own
+    /// options, so `dedent` strips the indentation the output ends up with
+    /// rather than the template's.
+    pub fn modify(&mut self, name: &str) -> Result<(), String> {
+        match name {
+            "dedent" => self.dedent_output = true,
+            "trim" => self.trim_output = true,
+            _ => return Err(format!("Unknown output modifier: {}, expected dedent or trim", name)),
+        }
+        Ok(())
+    }
+    
+    /// The options without the call's output modifiers, for rendering the
+    /// templates it references, whose output isn't the final result
+    pub fn without_modifiers(&self) -> Options {
+        Options {
+            dedent_output: false,
+            trim_output: false,
+            ..self.clone()
+        }
+    }
+    
+    /// Set a valued option by name, as written in `#[locale = "de"]`
+    pub fn set(&mut self, name: &str, value: String) -> Result<(), String> {
+        match name {
+            "locale" => self.locale = Some(value),
+            "escape" => self.escape = Some(value.parse().map_err(|e| format!("{}", e))?),
+            "placeholders" => self.placeholders = Some(value.parse().map_err(|e| format!("{}", e))?),
+            "postprocess" => self.postprocess = Some(value.parse().map_err(|e| format!("{}", e))?),
+            _ => return Err(format!("Unknown template option: {}", name)),
+        }
+        Ok(())
+    }
+    
+    /// Combine with the options given at the call site, which take
+    /// precedence for valued options like `locale`
+    pub fn merge(self, other: &Options) -> Self {
+        Options {
+            indent: self.indent || other.indent,
+            trim: self.trim || other.trim,
+            dedent: self.dedent || other.dedent,
+            dedent_output: self.dedent_output || other.dedent_output,
+            trim_output: self.trim_output || other.trim_output,
+            partial: self.partial || other.partial,
+            strict: self.strict || other.strict,
+            trim_blocks: self.trim_blocks || other.trim_blocks,
+            lstrip_blocks: self.lstrip_blocks || other.lstrip_blocks,
+            keep_trailing_newline: other.keep_trailing_newline.or(self.keep_trailing_newline),
+            delimiters: other.delimiters.clone().or(self.delimiters),
+            escape: other.escape.or(self.escape),
+            locale: other.locale.clone().or(self.locale),
+            placeholders: other.placeholders.or(self.placeholders),
+            postprocess: other.postprocess.or(self.postprocess),
+        }
+    }
+    
+    /// How substituted values are escaped
+    pub fn escaping(&self) -> Escape {
+        self.escape.unwrap_or_default()
+    }
+    
+    /// Render a template, dedenting it beforehand and post-processing,
+    /// dedenting, trimming and rewriting the placeholders of the output
+    /// afterwards when requested
+    pub fn render_with(
+        &self,
+        template: &str,
+        render: impl FnOnce(&str) -> Result<String, String>,
+    ) -> Result<String, String> {
+        let rendered = if self.dedent {
+            render(&dedent(template))?
+        } else {
+            render(template)?
+        };
+        
+        let rendered = match self.postprocess {
+            Some(step) => crate::postprocess::apply(&rendered, step)?,
+            None => rendered,
+        };
+        
+        let rendered = if self.dedent_output {
+            dedent(&rendered)
+        } else {
+            rendered
+        };
+        
+        let rendered = if self.trim || self.trim_output {
+            rendered.trim().to_string()
+        } else {
+            rendered
+        };
+        
+        match self.placeholders {
+            Some(style) => crate::placeholders::rewrite(&rendered, style),
+            None => Ok(rendered),
+        }
+    }
+}
+
+/// Rendered results by key, along with the registry fingerprint they were
+/// rendered against
+type RenderCache = (u64, HashMap<String, Result<String, String>>);
+
+// Rendered results keyed by scoped settings, engine, template, parameters and
+// options, shared by every expansion in the compiler process until the
+// registry changes
+static RENDERED: LazyLock<Mutex<RenderCache>> = LazyLock::new(|| Mutex::new((0, HashMap::new())));
+
+/// Process a template with the specified engine
+pub fn process(
+    engine: &str,
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+) -> Result<String, String> {
+    // Usage logging records the `@` references each render visits, which a
+    // cached result would skip
+    if crate::settings::var_os("TOMPLATE_USAGE_LOG").is_some() {
+        let engine_name = engine;
+        let engine = Engine::from_str(engine)?;
+        return crate::profile::render(engine_name, || engine.process(template, params, options));
+    }
+    
+    let sorted: BTreeMap<&String, &Value> = params.iter().collect();
+    let key = format!(
+        "{:016x}\0{}\0{}\0{}\0{:?}",
+        crate::settings::fingerprint(),
+        engine,
+        template,
+        serde_json::to_string(&sorted).map_err(|e| e.to_string())?,
+        options
+    );
+    let fingerprint = crate::templates::fingerprint();
+    {
+        let mut rendered = RENDERED.lock().unwrap();
+        if rendered.0 != fingerprint {
+            *rendered = (fingerprint, HashMap::new());
+        }
+        if let Some(result) = rendered.1.get(&key) {
+            crate::profile::cached();
+            return result.clone();
+        }
+    }
+    
+    let result = match crate::cache::load(&key) {
+        Some(output) => {
+            crate::profile::cached();
+            Ok(output)
+        }
+        None => {
+            let engine_name = engine;
+            let engine = Engine::from_str(engine)?;
+            let result = crate::profile::render(engine_name, || engine.process(template, params, options));
+            // External commands may depend on anything, so only their
+            // in-process results are reused
+            if let Ok(output) = &result
+                && !matches!(engine, Engine::Custom(_))
+            {
+                crate::cache::store(&key, output);
+            }
+            result
+        }
+    };
+    let mut rendered = RENDERED.lock().unwrap();
+    if rendered.0 == fingerprint {
+        rendered.1.insert(key, result.clone());
+    }
+    result
+}
+
+/// Process a template with the specified engine, reading the registry,
+/// custom engines and helpers from the given settings rather than the
+/// environment
+///
+/// Settings are keyed by variable name, as in `TOMPLATE_TEMPLATES_PATH`. This
+/// is how a build script renders with the settings it passes to the macros.
+pub fn process_with_settings(
+    engine: &str,
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+    settings: &HashMap<String, String>,
+) -> Result<String, String> {
+    crate::settings::scoped(settings, || process(engine, template, params, options))
+}
+
+/// Check a template's syntax with the specified engine, without rendering it
+pub fn check(engine: &str, template: &str, options: &Options) -> Result<(), String> {
+    Engine::from_str(engine)?.check(template, options)
+}
+
+/// Names of the registry templates a template refers to, in order of first
+/// appearance: `{@name}` references in simple templates, `{{> name}}`
+/// partials in Handlebars, and `extends`, `include`, `import` and `from` tags
+/// in Tera and MiniJinja
+///
+/// References are found by scanning the source, so they can be listed
+/// without the engine's feature enabled.
+pub fn references(engine: &str, template: &str, options: &Options) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut push = |name: &str| {
+        if !name.is_empty() && !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    };
+    
+    match engine {
+        "simple" | "" => {
+            for name in simple::placeholders(template) {
+                if let Some(name) = name.strip_prefix('@') {
+                    push(name);
+                }
+            }
+        }
+        "handlebars" => {
+            for tag in tags(template, "{{", "}}") {
+                let tag = tag.trim_matches('~').trim();
+                if let Some(partial) = tag.strip_prefix("#>").or_else(|| tag.strip_prefix('>')) {
+                    let name = partial.split_whitespace().next().unwrap_or_default();
+                    push(name.trim_matches(['"', '\'']));
+                }
+            }
+        }
+        "tera" | "minijinja" => {
+            let delimiters = options.delimiters.clone().unwrap_or_default();
+            let start = delimiters.block_start.as_deref().unwrap_or("{%");
+            let end = delimiters.block_end.as_deref().unwrap_or("%}");
+            for tag in tags(template, start, end) {
+                let tag = tag.trim_matches(['-', '+']).trim_start();
+                let Some((keyword, rest)) = tag.split_once(char::is_whitespace) else {
+                    continue;
+                };
+                if matches!(keyword, "extends" | "include" | "import" | "from") {
+                    // `from "name" import macro` names the template first
+                    let rest = rest.split(" import ").next().unwrap_or_default();
+                    for name in string_literals(rest) {
+                        push(name);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    names
+}
+
+/// The trimmed contents of the tags between `start` and `end` delimiters
+fn tags<'a>(template: &'a str, start: &'a str, end: &'a str) -> impl Iterator<Item = &'a str> {
+    let mut rest = template;
+    std::iter::from_fn(move || {
+        let open = rest.find(start)? + start.len();
+        let close = rest[open..].find(end)? + open;
+        let tag = &rest[open..close];
+        rest = &rest[close + end.len()..];
+        Some(tag.trim())
+    })
+}
+
+/// The contents of the single- or double-quoted string literals in text
+fn string_literals(text: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find(['"', '\'']) {
+        let quote = &rest[open..open + 1];
+        let Some(close) = rest[open + 1..].find(quote) else {
+            break;
+        };
+        literals.push(&rest[open + 1..open + 1 + close]);
+        rest = &rest[open + 1 + close + 1..];
+    }
+    literals
+}
+
+/// Remove the leading whitespace shared by all non-blank lines
+fn dedent(text: &str) -> String {
+    let common = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    
+    text.split('\n')
+        .map(|line| line.get(common..).unwrap_or_else(|| line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Build the context for the structured and custom engines, expanding dotted parameter
+/// names like `user.name` into nested objects
+pub fn nested_params(params: &HashMap<String, Value>) -> Result<Value, String> {
+    let mut root = serde_json::Map::new();
+    
+    // Sort keys so conflicts are reported deterministically
+    let mut keys: Vec<&String> = params.keys().collect();
+    keys.sort();
+    
+    for key in keys {
+        let mut segments: Vec<&str> = key.split('.').collect();
+        let last = segments.pop().unwrap_or_default();
+        
+        let mut object = &mut root;
+        for segment in segments {
+            let entry = object
+                .entry(segment.to_string())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+            object = entry
+                .as_object_mut()
+                .ok_or_else(|| format!("Parameter '{}' conflicts with '{}'", key, segment))?;
+        }
+        
+        if object.get(last).is_some_and(Value::is_object) {
+            return Err(format!("Parameter '{}' conflicts with nested parameters", key));
+        }
+        object.insert(last.to_string(), params[key].clone());
+    }
+    
+    Ok(Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[cfg(any(feature = "handlebars", feature = "minijinja"))]
+    fn strict() -> Options {
+        Options { strict: true, ..Options::default() }
+    }
+    
+    #[test]
+    fn test_strict_from_template() {
+        let template: Template = toml::from_str("template = \"\"\nstrict = true").unwrap();
+        assert!(Options::from_template(&template).strict);
+        let template: Template = toml::from_str("template = \"\"").unwrap();
+        assert!(!Options::from_template(&template).strict);
+    }
+    
+    #[test]
+    #[cfg(feature = "handlebars")]
+    fn test_strict_handlebars() {
+        let params = HashMap::from([("name".to_string(), Value::from("Alice"))]);
+        let template = "Hi {{name}}{{missing}}";
+        assert_eq!(process("handlebars", template, &params, &Options::default()).unwrap(), "Hi Alice");
+        let error = process("handlebars", template, &params, &strict()).unwrap_err();
+        assert!(error.contains("missing"), "{}", error);
+        assert_eq!(process("handlebars", "Hi {{name}}", &params, &strict()).unwrap(), "Hi Alice");
+    }
+    
+    #[test]
+    #[cfg(feature = "minijinja")]
+    fn test_strict_minijinja() {
+        let params = HashMap::from([("name".to_string(), Value::from("Alice"))]);
+        let template = "Hi {{ name }}{{ missing }}";
+        assert_eq!(process("minijinja", template, &params, &Options::default()).unwrap(), "Hi Alice");
+        let error = process("minijinja", template, &params, &strict()).unwrap_err();
+        assert!(error.contains("undefined"), "{}", error);
+        // Tests for definedness still work
+        let template = "{% if missing is defined %}set{% else %}unset{% endif %}";
+        assert_eq!(process("minijinja", template, &params, &strict()).unwrap(), "unset");
+    }
+}
@@ -0,0 +1,80 @@
+//! CLDR plural rules for the `plural` and `plural_category` helpers.
+//!
+//! The Handlebars, Tera and MiniJinja engines pick a count's plural form for
+//! the template's locale, e.g. `{{plural count one="item" other="items"}}` or
+//! `{{ count | plural(one="item", other="items") }}`. Fluent messages select
+//! plurals with their own `{ $count -> ... }` syntax.
+
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use serde_json::{Map, Value};
+use unic_langid::LanguageIdentifier;
+
+/// Locale used when a template sets none
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// The CLDR plural category of a count in a locale: `zero`, `one`, `two`,
+/// `few`, `many` or `other`
+///
+/// Counts may be numbers or numeric strings. Written fractions keep their
+/// digits, so `"1.0"` is `other` in English while `1` is `one`.
+pub fn category(locale: &str, count: &Value) -> Result<&'static str, String> {
+    let langid: LanguageIdentifier = locale
+        .parse()
+        .map_err(|e| format!("Invalid locale '{}': {}", locale, e))?;
+    // Rules are mostly keyed by language alone, so `en-US` uses those of `en`
+    // while `pt-PT` keeps its own
+    let language = LanguageIdentifier::from_parts(langid.language, None, None, &[]);
+    let rules = PluralRules::create(langid, PluralRuleType::CARDINAL)
+        .or_else(|_| PluralRules::create(language, PluralRuleType::CARDINAL))
+        .map_err(|e| format!("No plural rules for locale '{}': {}", locale, e))?;
+
+    let text = match count {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.trim().to_string(),
+        _ => return Err(format!("Plural count must be a number, got {}", count)),
+    };
+    let category = rules
+        .select(text.as_str())
+        .map_err(|_| format!("Plural count must be a number, got '{}'", text))?;
+
+    Ok(match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    })
+}
+
+/// Pick the form for a count's plural category, falling back to `other`
+///
+/// `forms` maps category names to the text for them, e.g. `one = "item"`.
+pub fn select(locale: &str, count: &Value, forms: &Map<String, Value>) -> Result<String, String> {
+    let category = category(locale, count)?;
+    let form = forms
+        .get(category)
+        .or_else(|| forms.get("other"))
+        .ok_or_else(|| format!("No '{}' or 'other' plural form for count {}", category, count))?;
+    Ok(super::filters::to_text(form, ", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_category() {
+        assert_eq!(category("en-US", &Value::from(1)), Ok("one"));
+        assert_eq!(category("en", &Value::from(2)), Ok("other"));
+        assert_eq!(category("en-US", &Value::from("1.0")), Ok("other"));
+        assert_eq!(category("pl-PL", &Value::from(5)), Ok("many"));
+        // Regional rules win over the language's
+        assert_eq!(category("pt", &Value::from(0)), Ok("one"));
+        assert_eq!(category("pt-PT", &Value::from(0)), Ok("other"));
+        
+        assert!(category("not a locale", &Value::from(1)).unwrap_err().starts_with("Invalid locale"));
+        assert!(category("xx", &Value::from(1)).unwrap_err().starts_with("No plural rules for locale 'xx'"));
+        assert_eq!(category("en", &Value::Null), Err("Plural count must be a number, got null".to_string()));
+    }
+}
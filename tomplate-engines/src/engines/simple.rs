@@ -0,0 +1,281 @@
+use super::{filters, Options};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Process a template using simple {variable} substitution
+///
+/// Dotted placeholders such as `{user.name}` look up dotted parameter names,
+/// and `{@name}` renders another registry template with the same parameters.
+/// Placeholders may pipe the value through built-in filters, e.g.
+/// `{name|upper}`, `{table|snake}` or `{cols|join:, }`. List parameters are
+/// joined with `, ` unless a separator is given, as in `{cols*" AND "}`, and
+/// `{ids|repeat:?}` produces one `?` per item. Braces that don't contain a
+/// placeholder, such as `{ }` in JSON, are kept as-is.
+pub fn process(template: &str, params: &HashMap<String, Value>) -> Result<String, String> {
+    process_with(template, params, &Options::default())
+}
+
+/// Process a template with explicit rendering options
+///
+/// With `indent`, every line after the first of a multi-line value is
+/// indented to the column its placeholder starts at. With `partial`,
+/// placeholders without a parameter are kept so the result can be rendered
+/// again later.
+pub fn process_with(
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+) -> Result<String, String> {
+    render(template, params, options, &mut Vec::new())
+}
+
+/// Check that every placeholder's filters exist and have the arguments they need
+pub fn check(template: &str) -> Result<(), String> {
+    for placeholder in parse_placeholders(template) {
+        // A count that `repeat` accepts and every other filter can take
+        placeholder.render(&Value::String("0".to_string()))?;
+    }
+    Ok(())
+}
+
+/// Names of the placeholders in a template, including `@name` references,
+/// in order of first appearance
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    for placeholder in parse_placeholders(template) {
+        if !names.contains(&placeholder.name) {
+            names.push(placeholder.name);
+        }
+    }
+    names
+}
+
+/// Parse the placeholders of a template, skipping braces that don't hold one
+fn parse_placeholders(template: &str) -> Vec<Placeholder<'_>> {
+    let mut placeholders = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = match after.find(['{', '}']) {
+            Some(end) if after[end..].starts_with('}') => end,
+            _ => {
+                rest = after;
+                continue;
+            }
+        };
+        placeholders.extend(Placeholder::parse(&after[..end]));
+        rest = &after[end + 1..];
+    }
+    placeholders
+}
+
+/// Render a template, tracking the registry references being expanded
+fn render(
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+    stack: &mut Vec<String>,
+) -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut unsubstituted = Vec::new();
+    let mut rest = template;
+    
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        
+        // Find the closing brace, restarting if another placeholder opens first
+        let end = match after.find(['{', '}']) {
+            Some(end) if after[end..].starts_with('}') => end,
+            _ => {
+                result.push('{');
+                rest = after;
+                continue;
+            }
+        };
+        let inner = &after[..end];
+        
+        match Placeholder::parse(inner) {
+            Some(placeholder) if placeholder.name.starts_with('@') => {
+                let value = render_reference(&placeholder.name[1..], params, options, stack)?;
+                push_value(&mut result, &placeholder.render(&Value::String(value))?, options);
+            }
+            Some(placeholder) => match lookup(params, placeholder.name) {
+                Some(value) => {
+                    let value = placeholder.render(value)?;
+                    let value = super::filters::escape(options.escaping(), &value);
+                    push_value(&mut result, &value, options);
+                }
+                None if options.partial => result.push_str(&rest[start..start + end + 2]),
+                // A missing parameter renders as null, which `default` replaces
+                None if placeholder.has_default() => {
+                    let value = placeholder.render(&Value::Null)?;
+                    push_value(&mut result, &value, options);
+                }
+                None => {
+                    if !unsubstituted.contains(&placeholder.name) {
+                        unsubstituted.push(placeholder.name);
+                    }
+                }
+            },
+            // Not a placeholder, keep the braces and their content
+            None => result.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    
+    if !unsubstituted.is_empty() {
+        return Err(format!(
+            "Template contains unsubstituted variables: {}",
+            unsubstituted.join(", ")
+        ));
+    }
+    
+    Ok(result)
+}
+
+/// Render a `{@name}` reference to another registry template
+fn render_reference(
+    name: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+    stack: &mut Vec<String>,
+) -> Result<String, String> {
+    if stack.iter().any(|entry| entry == name) {
+        return Err(format!(
+            "Template reference cycle: @{} -> @{}",
+            stack.join(" -> @"),
+            name
+        ));
+    }
+    
+    let template = crate::templates::get(name)
+        .ok_or_else(|| format!("Referenced template not found: @{}", name))?;
+    crate::templates::record_usage(name);
+    let engine = template.engine.as_deref().unwrap_or("simple");
+    let options = Options::from_template(&template).merge(&options.without_modifiers());
+    let options = &options;
+    
+    stack.push(name.to_string());
+    let result = if engine == "simple" || engine.is_empty() {
+        options.render_with(&template.template, |source| render(source, params, options, stack))
+    } else {
+        super::process(engine, &template.template, params, options)
+    };
+    stack.pop();
+    
+    let result = result.map_err(|e| format!("In template '@{}': {}", name, e))?;
+    crate::validate::check(name, &template, &result, params)?;
+    Ok(result)
+}
+
+/// Append a substituted value, re-indenting it if requested
+fn push_value(result: &mut String, value: &str, options: &Options) {
+    if !options.indent || !value.contains('\n') {
+        result.push_str(value);
+        return;
+    }
+    
+    // Match the placeholder's column, keeping tabs so mixed indentation lines up
+    let line = &result[result.rfind('\n').map_or(0, |index| index + 1)..];
+    let indent: String = line.chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    
+    let mut lines = value.split('\n');
+    result.push_str(lines.next().unwrap_or_default());
+    for line in lines {
+        result.push('\n');
+        // Leave blank lines empty rather than padding them with whitespace
+        if !line.trim().is_empty() {
+            result.push_str(&indent);
+        }
+        result.push_str(line);
+    }
+}
+
+/// A parsed `{name|filter:arg|...*"separator"}` placeholder
+struct Placeholder<'a> {
+    name: &'a str,
+    filters: Vec<(&'a str, Option<&'a str>)>,
+    /// Separator used to join list values
+    separator: &'a str,
+}
+
+impl<'a> Placeholder<'a> {
+    /// Parse the text between braces, or return `None` if it isn't a placeholder
+    fn parse(inner: &'a str) -> Option<Self> {
+        let (inner, separator) = match inner.find("*\"") {
+            Some(index) => (&inner[..index], inner[index + 2..].strip_suffix('"')?),
+            None => (inner, ", "),
+        };
+        
+        let mut parts = inner.split('|');
+        let name = parts.next()?.trim();
+        if !is_name(name) && !is_reference(name) {
+            return None;
+        }
+        
+        // Filter arguments are taken verbatim so separators like ", " survive
+        let filters = parts
+            .map(|filter| match filter.split_once(':') {
+                Some((filter, arg)) => (filter.trim(), Some(arg)),
+                None => (filter.trim(), None),
+            })
+            .collect();
+        
+        Some(Placeholder {
+            name,
+            filters,
+            separator,
+        })
+    }
+    
+    /// Whether a `default` filter stands in for a missing parameter
+    fn has_default(&self) -> bool {
+        self.filters.iter().any(|(filter, _)| *filter == "default")
+    }
+    
+    /// Apply the placeholder's filters to a value, in order, then render it
+    /// as text with lists joined by the separator
+    fn render(&self, value: &Value) -> Result<String, String> {
+        let mut value = value.clone();
+        for (filter, arg) in &self.filters {
+            value = filters::apply(filter, *arg, &value)?;
+        }
+        Ok(filters::to_text(&value, self.separator))
+    }
+}
+
+/// Find a parameter by name; dotted names can also reach into structured
+/// values, e.g. `{user.name}` or `{user.roles.0}`
+fn lookup<'a>(params: &'a HashMap<String, Value>, name: &str) -> Option<&'a Value> {
+    if let Some(value) = params.get(name) {
+        return Some(value);
+    }
+    
+    // Try the longest parameter name that prefixes the placeholder
+    let mut end = name.len();
+    while let Some(dot) = name[..end].rfind('.') {
+        if let Some(value) = params.get(&name[..dot]) {
+            return name[dot + 1..].split('.').try_fold(value, |value, key| match value {
+                Value::Object(object) => object.get(key),
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+                _ => None,
+            });
+        }
+        end = dot;
+    }
+    None
+}
+
+/// Check whether text is a valid placeholder name, optionally dotted
+fn is_name(name: &str) -> bool {
+    name.split('.')
+        .all(|segment| !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// Check whether text is a `@name` registry reference
+fn is_reference(name: &str) -> bool {
+    name.strip_prefix('@')
+        .is_some_and(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+}
@@ -0,0 +1,161 @@
+use super::{Escape, Options};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Name the rendered template is registered under, kept apart from registry names
+const TEMPLATE_NAME: &str = "__tomplate_template";
+
+/// Tera with the registry templates and built-in filters loaded, built once
+/// per registry version and cloned for every expansion
+static TERA: Mutex<Option<(u64, Result<tera::Tera, String>)>> = Mutex::new(None);
+
+/// A copy of the Tera instance for the current registry
+fn registry_tera() -> Result<tera::Tera, String> {
+    let fingerprint = crate::templates::fingerprint();
+    let mut cached = TERA.lock().unwrap();
+    match &*cached {
+        Some((built_for, tera)) if *built_for == fingerprint => tera.clone(),
+        _ => {
+            let tera = build_tera();
+            *cached = Some((fingerprint, tera.clone()));
+            tera
+        }
+    }
+}
+
+fn build_tera() -> Result<tera::Tera, String> {
+    let mut tera = tera::Tera::default();
+    
+    // Register the other Tera templates from the registry under their names so
+    // `{% extends %}`, `{% include %}` and `{% import %}` can refer to them.
+    // They are added together since inheritance chains are resolved on insert.
+    let templates: Vec<(String, String)> = crate::templates::names_for_engine("tera")
+        .into_iter()
+        .filter_map(|name| {
+            let template = crate::templates::get(&name)?;
+            let options = Options::from_template(&template);
+            Some((name, strip_block_whitespace(&template.template, &options)))
+        })
+        .collect();
+    tera.add_raw_templates(templates)
+        .map_err(|e| format!("Tera template error: {}", e))?;
+    
+    // Built-in quoting filters, e.g. `{{ table | sql_ident }}`
+    for &(name, quote) in super::filters::SHARED {
+        tera.register_filter(name, filter(quote));
+    }
+    Ok(tera)
+}
+
+pub fn process(
+    template: &str,
+    params: &HashMap<String, Value>,
+    options: &Options,
+) -> Result<String, String> {
+    let mut tera = registry_tera()?;
+    
+    // Auto-escape every template when an escape mode is set, and none otherwise
+    match options.escaping() {
+        Escape::None => tera.autoescape_on(vec![]),
+        Escape::Html => tera.autoescape_on(vec![""]),
+        Escape::Shell => {
+            tera.autoescape_on(vec![""]);
+            tera.set_escape_fn(super::filters::shell_quote);
+        }
+    }
+    
+    // Locale-aware filters for the template's locale, e.g.
+    // `{{ count | plural(one="item", other="items") }}`. Tera filters only
+    // take named arguments, so it's `currency(code="EUR")`.
+    let locale = options.locale.as_deref().unwrap_or(super::plural::DEFAULT_LOCALE);
+    for helper in super::localized::HELPERS {
+        let locale = locale.to_string();
+        tera.register_filter(helper.name, move |value: &Value, args: &HashMap<String, Value>| {
+            let args = args.iter().map(|(name, arg)| (name.clone(), arg.clone())).collect();
+            helper.call(&locale, value, args).map(Value::String).map_err(tera::Error::msg)
+        });
+    }
+    
+    tera.add_raw_template(TEMPLATE_NAME, &strip_block_whitespace(template, options))
+        .map_err(|e| format!("Tera template error: {}", e))?;
+    
+    // Convert params to tera::Context
+    let context = tera::Context::from_value(super::nested_params(params)?)
+        .map_err(|e| format!("Tera context error: {}", e))?;
+    
+    let mut rendered = tera.render(TEMPLATE_NAME, &context)
+        .map_err(|e| format!("Tera render error: {}", e))?;
+    
+    if options.keep_trailing_newline == Some(false) && rendered.ends_with('\n') {
+        rendered.pop();
+    }
+    Ok(rendered)
+}
+
+/// Parse a template against the registry's Tera templates, reporting syntax
+/// errors and missing parents
+pub fn check(template: &str, options: &Options) -> Result<(), String> {
+    registry_tera()?
+        .add_raw_template(TEMPLATE_NAME, &strip_block_whitespace(template, options))
+        .map_err(|e| format!("Tera template error: {}", e))
+}
+
+/// Wrap a quoting function as a Tera filter
+fn filter(
+    quote: fn(&Value) -> String,
+) -> impl Fn(&Value, &HashMap<String, Value>) -> tera::Result<Value> + Send + Sync + 'static {
+    move |value, _| Ok(Value::String(quote(value)))
+}
+
+/// Emulate Jinja's `trim_blocks` and `lstrip_blocks`, which Tera lacks
+fn strip_block_whitespace(template: &str, options: &Options) -> String {
+    if !options.trim_blocks && !options.lstrip_blocks {
+        return template.to_string();
+    }
+    
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    let mut rest_at_line_start = true;
+    
+    while let Some(start) = rest.find("{%") {
+        let before = &rest[..start];
+        let line_start = before.rfind('\n').map(|index| index + 1);
+        let indent_start = line_start.unwrap_or(0);
+        let at_line_start = line_start.is_some() || rest_at_line_start;
+        
+        // Drop the indentation of a tag that starts its line
+        if options.lstrip_blocks
+            && at_line_start
+            && before[indent_start..].chars().all(|c| c == ' ' || c == '\t')
+        {
+            result.push_str(&before[..indent_start]);
+        } else {
+            result.push_str(before);
+        }
+        
+        let Some(length) = rest[start..].find("%}") else {
+            break;
+        };
+        let end = start + length + 2;
+        result.push_str(&rest[start..end]);
+        rest = &rest[end..];
+        rest_at_line_start = false;
+        
+        // Drop the newline that ends a tag's line
+        if options.trim_blocks
+            && let Some(after) = rest.strip_prefix("\r\n").or_else(|| rest.strip_prefix('\n'))
+        {
+            rest = after;
+            rest_at_line_start = true;
+        }
+    }
+    
+    // An unterminated tag is left for Tera to report
+    if let Some(start) = rest.find("{%") {
+        result.push_str(&rest[start..]);
+    } else {
+        result.push_str(rest);
+    }
+    result
+}
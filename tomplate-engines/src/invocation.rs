@@ -0,0 +1,284 @@
+//! Direct template calls such as `tomplate!("select_user", fields = "id")`.
+//!
+//! The macros and the CLI both parse and render calls here, so a call
+//! explained by `tomplate explain` renders exactly as it does in the macro.
+
+use crate::engines::{self, Options};
+use crate::{parser, templates, validate};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use syn::{parse::Parser, punctuated::Punctuated, spanned::Spanned, Expr, ExprMacro, Token};
+use crate::types::Template;
+
+/// The arguments of a direct template call
+#[derive(Clone)]
+pub struct TomplateInput {
+    pub template_name: String,
+    pub params: Vec<(String, ParamValue)>,
+    /// Parameters marked with `#[cfg(...)]`, passed only when it holds
+    pub conditional: Vec<ConditionalParam>,
+    /// Rendering options given as attributes, e.g. `#[indent]`
+    pub options: Options,
+    /// Span of the template name, used for engine errors
+    pub span: proc_macro2::Span,
+}
+
+/// Value of a parameter in a direct template call
+#[derive(Clone)]
+pub enum ParamValue {
+    Literal(Value),
+    Macro(ExprMacro),
+}
+
+/// A parameter of a direct template call marked with `#[cfg(...)]`
+#[derive(Clone)]
+pub struct ConditionalParam {
+    /// The cfg predicate, e.g. `feature = "audit"`
+    pub predicate: proc_macro2::TokenStream,
+    pub name: String,
+    pub value: ParamValue,
+}
+
+/// A call resolved against the registry, ready to render
+pub struct Resolved {
+    /// The registry entry the name matched, or `None` for an inline template
+    pub template: Option<Arc<Template>>,
+    /// The template body
+    pub source: String,
+    /// The engine that renders the body
+    pub engine: String,
+    /// The registry entry's options merged with the call's
+    pub options: Options,
+    /// The parameters, with nested template calls rendered
+    pub params: HashMap<String, Value>,
+}
+
+impl syn::parse::Parse for TomplateInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut options = parser::parse_options(&input.call(syn::Attribute::parse_outer)?)?;
+        parser::parse_modifiers(input, &mut options)?;
+
+        // Parse template name
+        let name_expr = input.parse::<Expr>()?;
+        let span = name_expr.span();
+        let template_name = parser::parse_name(&name_expr)?;
+
+        let mut params = Vec::new();
+        let mut conditional = Vec::new();
+
+        // Parse optional parameters
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+
+            let args = Punctuated::<Expr, Token![,]>::parse_terminated
+                .parse2(parser::expand_references(input.parse()?)?)?;
+
+            for arg in args {
+                match arg {
+                    Expr::Assign(mut assign) => {
+                        // Attributes before the name end up on the name itself
+                        let mut attrs = std::mem::take(&mut assign.attrs);
+                        attrs.extend(take_attrs(&mut assign.left));
+                        let predicate = cfg_predicate(&attrs)?;
+
+                        // Extract parameter name
+                        let param_name = parser::parse_param_name(&assign.left)?;
+
+                        // Extract parameter value (literal or macro)
+                        let param_value = match &*assign.right {
+                            Expr::Lit(lit) => ParamValue::Literal(parser::parse_literal(&lit.lit)?),
+                            Expr::Unary(unary) => ParamValue::Literal(parser::parse_negative(unary)?),
+                            Expr::Call(call) if parser::is_value_call(call) => {
+                                ParamValue::Literal(parser::parse_value_call(call, &template_name)?)
+                            }
+                            Expr::Array(array) => ParamValue::Literal(parser::parse_list(array)?),
+                            Expr::Macro(macro_expr) if parser::is_structured(macro_expr) => {
+                                ParamValue::Literal(parser::parse_structured(macro_expr)?)
+                            }
+                            Expr::Macro(macro_expr) => {
+                                // Check if it's a (possibly path-qualified) tomplate! macro call
+                                if let Some(segment) = macro_expr.mac.path.segments.last() {
+                                    if segment.ident == "tomplate" {
+                                        ParamValue::Macro(macro_expr.clone())
+                                    } else {
+                                        return Err(syn::Error::new_spanned(
+                                            macro_expr,
+                                            "Only tomplate! macro calls are supported in parameters",
+                                        ))
+                                    }
+                                } else {
+                                    return Err(syn::Error::new_spanned(
+                                        macro_expr,
+                                        "Expected tomplate! macro call",
+                                    ))
+                                }
+                            },
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    assign.right,
+                                    "Expected literal value, list, json!/toml! value, file(), transform or tomplate! macro call",
+                                ))
+                            }
+                        };
+
+                        match predicate {
+                            Some(predicate) => conditional.push(ConditionalParam {
+                                predicate,
+                                name: param_name,
+                                value: param_value,
+                            }),
+                            None => params.push((param_name, param_value)),
+                        }
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            arg,
+                            "Expected key = value syntax",
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(TomplateInput {
+            template_name,
+            params,
+            conditional,
+            options,
+            span,
+        })
+    }
+}
+
+/// Remove the attributes of a parameter name, e.g. `user` in `user.name`
+fn take_attrs(expr: &mut Expr) -> Vec<syn::Attribute> {
+    match expr {
+        Expr::Path(path) => std::mem::take(&mut path.attrs),
+        Expr::Field(field) => take_attrs(&mut field.base),
+        _ => Vec::new(),
+    }
+}
+
+/// Combine the `#[cfg(...)]` attributes of a parameter into one predicate
+fn cfg_predicate(attrs: &[syn::Attribute]) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let mut predicates = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("cfg") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "Only #[cfg(...)] attributes are allowed on parameters",
+            ));
+        }
+        predicates.push(attr.meta.require_list()?.tokens.clone());
+    }
+    Ok(match predicates.len() {
+        0 => None,
+        1 => predicates.pop(),
+        _ => Some(quote::quote! { all(#(#predicates),*) }),
+    })
+}
+
+/// Look up the template of a call and expand its parameters
+pub fn resolve(input: &TomplateInput) -> syn::Result<Resolved> {
+    // Try to find the template in registry, or use as inline template
+    let template = templates::get(&input.template_name);
+    let (source, engine, options) = if let Some(template) = &template {
+        // Found in registry
+        templates::record_usage(&input.template_name);
+        (
+            template.template.clone(),
+            template.engine.clone().unwrap_or_else(|| "simple".to_string()),
+            Options::from_template(template).merge(&input.options),
+        )
+    } else {
+        // Not in registry, treat as inline template
+        (input.template_name.clone(), "simple".to_string(), input.options.clone())
+    };
+
+    // Process parameters, expanding any nested macros
+    let mut params = HashMap::new();
+    for (key, value) in &input.params {
+        let expanded_value = match value {
+            ParamValue::Literal(value) => value.clone(),
+            ParamValue::Macro(macro_expr) => {
+                // Recursively render the nested tomplate! call
+                let nested_input = syn::parse2::<TomplateInput>(macro_expr.mac.tokens.clone())?;
+                Value::String(render(nested_input)?)
+            }
+        };
+        params.insert(key.clone(), expanded_value);
+    }
+
+    Ok(Resolved { template, source, engine, options, params })
+}
+
+/// Render a direct template call
+///
+/// Calls with `#[cfg(...)]` parameters have one output per combination of
+/// their predicates, rendered with [`render_variants`].
+pub fn render(input: TomplateInput) -> syn::Result<String> {
+    if let Some(param) = input.conditional.first() {
+        return Err(syn::Error::new(
+            input.span,
+            format!(
+                "Parameter '{}' has a #[cfg] attribute, which only direct tomplate! calls support",
+                param.name
+            ),
+        ));
+    }
+    let resolved = resolve(&input)?;
+
+    // Process the template with the appropriate engine
+    let processed = match engines::process(&resolved.engine, &resolved.source, &resolved.params, &resolved.options) {
+        Ok(processed) => processed,
+        // Without the registry the call can't be rendered; expand to a stub
+        // so editors don't report an error before the build script runs
+        Err(_) if !templates::available() => return Ok(input.template_name),
+        Err(e) => return Err(syn::Error::new(input.span, e)),
+    };
+    if let Some(template) = &resolved.template {
+        validate::check(&input.template_name, template, &processed, &resolved.params)
+            .map_err(|e| syn::Error::new(input.span, e))?;
+    }
+
+    Ok(processed)
+}
+
+/// `#[cfg(...)]` parameters of a call, each doubling its renders
+const MAX_CONDITIONAL: usize = 8;
+
+/// Render a call once per combination of its `#[cfg(...)]` parameters,
+/// returning each result with the cfg predicate it applies under
+///
+/// A combination may fail to render, e.g. when it lacks a parameter, without
+/// that being an error: the combination may never be enabled.
+pub fn render_variants(
+    input: TomplateInput,
+) -> syn::Result<Vec<(proc_macro2::TokenStream, syn::Result<String>)>> {
+    let mut base = input;
+    let conditional = std::mem::take(&mut base.conditional);
+    if conditional.len() > MAX_CONDITIONAL {
+        return Err(syn::Error::new(
+            base.span,
+            format!("A call can have at most {} #[cfg] parameters", MAX_CONDITIONAL),
+        ));
+    }
+
+    let mut variants = Vec::new();
+    for mask in 0..1usize << conditional.len() {
+        let mut input = base.clone();
+        let mut predicates = Vec::new();
+        for (i, param) in conditional.iter().enumerate() {
+            let predicate = &param.predicate;
+            if mask & (1 << i) != 0 {
+                input.params.push((param.name.clone(), param.value.clone()));
+                predicates.push(quote::quote! { #predicate });
+            } else {
+                predicates.push(quote::quote! { not(#predicate) });
+            }
+        }
+        variants.push((quote::quote! { all(#(#predicates),*) }, render(input)));
+    }
+    Ok(variants)
+}
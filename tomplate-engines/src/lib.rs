@@ -0,0 +1,52 @@
+//! # Tomplate Engines
+//!
+//! Template rendering shared by the `tomplate` macros and command-line tool.
+//!
+//! This crate holds the template engines, access to the amalgamated registry
+//! written by `tomplate-build`, and the checks of rendered output. The macros
+//! render templates with it during expansion; tools such as the `tomplate`
+//! CLI use it to render outside the compiler.
+//!
+//! Most users depend on `tomplate` instead, which selects the engines through
+//! its features.
+//!
+//! ## Rendering
+//!
+//! The registry is located through `TOMPLATE_TEMPLATES_PATH`, which the build
+//! script sets for the compiler. Tools pass it with [`settings::scoped`]
+//! instead of setting it in their environment:
+//!
+//! ```rust,ignore
+//! use std::collections::HashMap;
+//! use tomplate_engines::{engines, settings, templates};
+//!
+//! let settings = HashMap::from([(
+//!     "TOMPLATE_TEMPLATES_PATH".to_string(),
+//!     "target/tomplate_amalgamated.toml".to_string(),
+//! )]);
+//! let output = settings::scoped(&settings, || {
+//!     let template = templates::get("select_user").unwrap();
+//!     let params = [("fields".to_string(), "*".into()), ("condition".to_string(), "id = $1".into())];
+//!     engines::process(
+//!         template.engine.as_deref().unwrap_or("simple"),
+//!         &template.template,
+//!         &params.into_iter().collect(),
+//!         &engines::Options::from_template(&template),
+//!     )
+//! })?;
+//! ```
+
+extern crate proc_macro;
+
+pub mod cache;
+pub mod engines;
+pub mod invocation;
+pub mod minify;
+pub mod parser;
+pub mod placeholders;
+pub mod postprocess;
+pub mod profile;
+pub mod settings;
+pub mod templates;
+pub mod types;
+pub mod validate;
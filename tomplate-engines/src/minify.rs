@@ -0,0 +1,401 @@
+//! Conservative minifiers for rendered HTML, CSS and JavaScript.
+//!
+//! They remove comments and whitespace that can't change how the output is
+//! read, and never rewrite names or values. Strings, `<pre>` and
+//! `<textarea>` contents and JavaScript regular expression literals are kept
+//! as they are.
+
+/// Block-level elements, whose surrounding whitespace doesn't render
+///
+/// Whitespace around any other element, including invisible ones such as
+/// `<script>` placed between words, is collapsed to a space but kept.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "br", "caption", "col", "colgroup", "dd",
+    "details", "dialog", "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form",
+    "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "hr", "html", "li", "main", "nav", "ol",
+    "p", "section", "summary", "table", "tbody", "td", "tfoot", "th", "thead", "tr", "ul",
+    "!doctype",
+];
+
+/// Elements whose content is kept verbatim
+const VERBATIM_ELEMENTS: &[&str] = &["pre", "textarea"];
+
+/// Minify HTML: drop comments, collapse whitespace runs to a space and drop
+/// those next to block-level tags, and minify inline `<style>` and
+/// `<script>` contents
+///
+/// Conditional comments such as `<!--[if IE]>` are kept.
+pub fn html(input: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    // Whitespace waiting to be written as a single space
+    let mut pending_space = false;
+    // Whether the last thing written was a block-level tag
+    let mut after_block = true;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            let end = after.find("-->").ok_or("Unterminated HTML comment")?;
+            if after.starts_with("[if") || after.starts_with("<![endif]") {
+                flush_space(&mut out, &mut pending_space, after_block);
+                out.push_str(&rest[..4 + end + 3]);
+                after_block = false;
+            }
+            rest = &after[end + 3..];
+            continue;
+        }
+
+        if rest.starts_with('<') && let Some(tag) = tag_name(rest) {
+            let end = tag_end(rest).ok_or_else(|| format!("Unterminated <{}> tag", tag))?;
+            let closing = rest.starts_with("</");
+            let block = BLOCK_ELEMENTS.contains(&tag.as_str());
+            if block {
+                pending_space = false;
+            } else {
+                flush_space(&mut out, &mut pending_space, after_block);
+            }
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            after_block = block;
+
+            // Raw text elements run to their closing tag
+            let raw = ["script", "style"].contains(&tag.as_str()) || VERBATIM_ELEMENTS.contains(&tag.as_str());
+            if !closing && raw {
+                let close = format!("</{}", tag);
+                let len = find_ignore_case(rest, &close).unwrap_or(rest.len());
+                let content = &rest[..len];
+                match tag.as_str() {
+                    "style" => out.push_str(css(content)?.trim()),
+                    "script" if is_javascript(&out) => out.push_str(js(content)?.trim()),
+                    _ => out.push_str(content),
+                }
+                rest = &rest[len..];
+            }
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            pending_space = true;
+        } else {
+            flush_space(&mut out, &mut pending_space, after_block);
+            out.push(c);
+            after_block = false;
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(out)
+}
+
+/// Write a pending whitespace run as one space, unless it follows a
+/// block-level tag or starts the output
+fn flush_space(out: &mut String, pending: &mut bool, after_block: bool) {
+    if *pending && !after_block && !out.is_empty() {
+        out.push(' ');
+    }
+    *pending = false;
+}
+
+/// The lowercase name of the tag starting `text`, e.g. `div` for `</div>`
+fn tag_name(text: &str) -> Option<String> {
+    let name = text.strip_prefix("</").or_else(|| text.strip_prefix('<'))?;
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '!') {
+        return None;
+    }
+    let end = name
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '!'))
+        .unwrap_or(name.len());
+    (end > 0).then(|| name[..end].to_ascii_lowercase())
+}
+
+/// The length of the tag starting `text`, skipping `>` in quoted attributes
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .to_ascii_lowercase()
+        .find(&needle.to_ascii_lowercase())
+}
+
+/// Whether the `<script>` tag just written holds JavaScript rather than
+/// data such as JSON or a template
+fn is_javascript(out: &str) -> bool {
+    let tag = &out[out.rfind('<').unwrap_or(0)..].to_ascii_lowercase();
+    match tag.find("type=") {
+        None => true,
+        Some(start) => {
+            let value = tag[start + 5..].trim_start_matches(['"', '\'']);
+            ["text/javascript", "module", "application/javascript"]
+                .iter()
+                .any(|kind| value.starts_with(kind))
+        }
+    }
+}
+
+/// Minify CSS: drop comments, collapse whitespace, drop it around `{`, `}`,
+/// `;`, `,` and `>` and after `:`, and drop the `;` before a `}`
+pub fn css(input: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => previous = c,
+                        None => return Err("Unterminated CSS comment".to_string()),
+                    }
+                }
+                pending_space = true;
+            }
+            '"' | '\'' => {
+                push_css_space(&mut out, &mut pending_space, c);
+                copy_string(c, &mut chars, &mut out).ok_or("Unterminated CSS string")?;
+            }
+            c if c.is_whitespace() => pending_space = true,
+            c => {
+                if c == '}' && out.ends_with(';') {
+                    out.pop();
+                }
+                push_css_space(&mut out, &mut pending_space, c);
+                out.push(c);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Write a pending space before `next` where CSS needs one
+fn push_css_space(out: &mut String, pending: &mut bool, next: char) {
+    let tight = |c: char| matches!(c, '{' | '}' | ';' | ',' | '>');
+    if *pending && !out.is_empty() && !tight(next) && !out.ends_with(|c: char| tight(c) || c == ':') {
+        out.push(' ');
+    }
+    *pending = false;
+}
+
+/// Copy a quoted string, the opening quote already consumed, with its escapes
+fn copy_string(quote: char, chars: &mut impl Iterator<Item = char>, out: &mut String) -> Option<()> {
+    out.push(quote);
+    loop {
+        let c = chars.next()?;
+        out.push(c);
+        match c {
+            '\\' => out.push(chars.next()?),
+            c if c == quote => return Some(()),
+            _ => {}
+        }
+    }
+}
+
+/// Keywords after which a `/` starts a regular expression
+const REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "case", "do", "else", "in", "of", "void", "throw", "new", "delete",
+    "instanceof", "yield", "await",
+];
+
+/// Minify JavaScript: drop comments and collapse whitespace
+///
+/// Line breaks are kept where automatic semicolon insertion may depend on
+/// them, that is unless the line ends with `{`, `;`, `,`, `(` or `[`, or the
+/// next one starts with `}`, `)`, `]` or `,`. Spaces are kept only between
+/// identifier characters and between `+ +` and `- -`. Template literals are
+/// copied as they are, including their `${}` substitutions, and so is the
+/// rest of a line where a `/` looks like a regular expression that never
+/// closes.
+pub fn js(input: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    // Pending whitespace, `Some(true)` if it has a line break
+    let mut pending: Option<bool> = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                pending = Some(true);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                let mut newline = false;
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            newline |= c == '\n';
+                            previous = c;
+                        }
+                        None => return Err("Unterminated JavaScript comment".to_string()),
+                    }
+                }
+                pending = pending.max(Some(newline));
+            }
+            c if c.is_whitespace() => {
+                pending = pending.max(Some(c == '\n'));
+            }
+            c => {
+                push_js_space(&mut out, pending.take(), c);
+                match c {
+                    '"' | '\'' | '`' => {
+                        copy_string(c, &mut chars, &mut out).ok_or("Unterminated JavaScript string")?;
+                    }
+                    '/' if regex_allowed(&out) => {
+                        out.push('/');
+                        let mut regex = chars.clone();
+                        let start = out.len();
+                        if copy_regex(&mut regex, &mut out).is_some() {
+                            chars = regex;
+                        } else {
+                            // Not a regular expression after all: keep the
+                            // rest of the line as it is rather than guess
+                            out.truncate(start);
+                            while let Some(c) = chars.next_if(|&c| c != '\n') {
+                                out.push(c);
+                            }
+                        }
+                    }
+                    c => out.push(c),
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Write pending whitespace before `next` where JavaScript needs it
+fn push_js_space(out: &mut String, pending: Option<bool>, next: char) {
+    let Some(line_break) = pending else {
+        return;
+    };
+    let Some(last) = out.chars().last() else {
+        return;
+    };
+    if line_break && !matches!(last, '{' | ';' | ',' | '(' | '[') && !matches!(next, '}' | ')' | ']' | ',') {
+        out.push('\n');
+        return;
+    }
+    let word = |c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | '\\') || !c.is_ascii();
+    if (word(last) && word(next)) || (last == next && matches!(last, '+' | '-')) {
+        out.push(' ');
+    }
+}
+
+/// Whether a `/` after `out` starts a regular expression rather than a
+/// division
+fn regex_allowed(out: &str) -> bool {
+    let out = out.trim_end();
+    let Some(last) = out.chars().last() else {
+        return true;
+    };
+    // After a postfix `++` or `--`, as in `x++ / 2`, a `/` divides
+    if let Some(operand) = out.strip_suffix("++").or_else(|| out.strip_suffix("--"))
+        && operand.trim_end().ends_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | ')' | ']'))
+    {
+        return false;
+    }
+    if "(,=:[!&|?{};+-*%<>~^".contains(last) {
+        return true;
+    }
+    let word_start = out
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .map_or(0, |i| i + 1);
+    REGEX_KEYWORDS.contains(&&out[word_start..])
+}
+
+/// Copy a regular expression literal after its opening `/`, with its flags
+fn copy_regex(chars: &mut std::iter::Peekable<std::str::Chars>, out: &mut String) -> Option<()> {
+    let mut class = false;
+    loop {
+        let c = chars.next()?;
+        out.push(c);
+        match c {
+            '\\' => out.push(chars.next()?),
+            '[' => class = true,
+            ']' => class = false,
+            '/' if !class => break,
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    while let Some(&flag) = chars.peek()
+        && flag.is_ascii_alphabetic()
+    {
+        out.push(flag);
+        chars.next();
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_block_whitespace() {
+        assert_eq!(
+            html("<div>\n  <p>Hello   <b>world</b> </p>\n</div>\n").unwrap(),
+            "<div><p>Hello <b>world</b></p></div>"
+        );
+    }
+
+    #[test]
+    fn test_html_inline_element_whitespace() {
+        // Whitespace around elements that aren't block-level still separates
+        // the words next to them
+        assert_eq!(
+            html("<p>Hello <script>var a = 1;</script> world</p>").unwrap(),
+            "<p>Hello <script>var a=1;</script> world</p>"
+        );
+        assert_eq!(
+            html("<label>Choose: <select>\n  <option>One</option>\n</select></label>").unwrap(),
+            "<label>Choose: <select> <option>One</option> </select></label>"
+        );
+    }
+
+    #[test]
+    fn test_js_regex() {
+        assert_eq!(
+            js("const re = /a b\\/[/]/g;\nlet ok = re.test(s)\n").unwrap(),
+            "const re=/a b\\/[/]/g;let ok=re.test(s)"
+        );
+        assert_eq!(js("let half = total / 2;").unwrap(), "let half=total/2;");
+    }
+
+    #[test]
+    fn test_js_division_after_increment() {
+        assert_eq!(js("let x = 1;\nlet y = x++ / 2;\n").unwrap(), "let x=1;let y=x++/2;");
+        assert_eq!(js("let z = (n)-- / 2 + a[0]++ / 3;").unwrap(), "let z=(n)--/2+a[0]++/3;");
+        // A prefix increment's operand follows it, so this is a regex
+        assert_eq!(js("x = ++ /a/.lastIndex;").unwrap(), "x=++/a/.lastIndex;");
+    }
+
+    #[test]
+    fn test_js_unclosed_regex_kept() {
+        // `{}` here is an object, but a `/` after `}` is taken for a regex;
+        // one that doesn't close on its line is copied with the rest of the
+        // line instead of failing
+        assert_eq!(js("let a = {} / 2;\nlet b = 1\n").unwrap(), "let a={}/ 2;let b=1");
+    }
+}
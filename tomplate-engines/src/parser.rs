@@ -0,0 +1,905 @@
+use crate::engines::Options;
+use proc_macro2::Span;
+use syn::{
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Attribute, Expr, ExprArray, ExprCall, ExprLit, ExprMacro, ExprUnary, Ident, Lit, LitStr, Result, Token, UnOp,
+    Visibility,
+};
+
+/// A composition block containing let bindings and const exports
+pub struct CompositionBlock {
+    pub statements: Vec<Statement>,
+}
+
+/// A braced block of let bindings whose final template call becomes the
+/// macro's value, e.g. `tomplate!({ let a = "x"; tomplate!("{a}", a = a) })`
+pub struct ExpressionBlock {
+    pub statements: Vec<Statement>,
+    pub value: TemplateCall,
+}
+
+/// A statement within a composition block
+pub enum Statement {
+    Let {
+        attrs: Vec<Attribute>,
+        name: Ident,
+        value: TemplateCall,
+    },
+    Const {
+        attrs: Vec<Attribute>,
+        name: Ident,
+        value: TemplateCall,
+    },
+    Enum {
+        attrs: Vec<Attribute>,
+        vis: Visibility,
+        name: Ident,
+        variants: Vec<EnumVariant>,
+    },
+    Matrix {
+        attrs: Vec<Attribute>,
+        spec: MatrixSpec,
+    },
+    /// `fn name(a, b) = tomplate!(...);`, a function taking the parameters
+    /// as runtime arguments
+    Fn {
+        attrs: Vec<Attribute>,
+        vis: Visibility,
+        name: Ident,
+        params: Vec<Ident>,
+        value: TemplateCall,
+    },
+}
+
+/// A variant of a generated enum, mapped to a template
+pub struct EnumVariant {
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub value: TemplateCall,
+}
+
+/// A `tomplate_matrix!` expansion: one const per combination of axis values
+pub struct MatrixSpec {
+    /// Const name pattern such as `"QUERY_{dialect}_{table}"`
+    pub name_pattern: LitStr,
+    /// Template call, which may reference the axes as variables
+    pub value: TemplateCall,
+    /// Axis names and their values, in declaration order
+    pub axes: Vec<(Ident, Vec<String>)>,
+}
+
+/// A `tomplate_for_each!` expansion over the registry templates matching a
+/// glob or prefix
+pub struct ForEachSpec {
+    /// Pattern selecting registry templates, e.g. `"migrations/*"`
+    pub pattern: LitStr,
+    /// What to generate for each matching template
+    pub output: ForEachOutput,
+}
+
+/// What `tomplate_for_each!` generates per template
+pub enum ForEachOutput {
+    /// One const per template, named by a pattern such as `"MIGRATION_{name}"`
+    Consts(LitStr),
+    /// The body of `|name, template| { ... }` repeated per template, with the
+    /// two parameters replaced by string literals
+    Tokens {
+        name: Ident,
+        template: Ident,
+        body: proc_macro2::Group,
+    },
+}
+
+/// A call to template!() within a block
+pub struct TemplateCall {
+    pub source: TemplateSource,
+    pub params: Vec<(String, ParamValue)>,
+    /// Rendering options given as attributes, e.g. `#[indent]`
+    pub options: Options,
+    /// Span of the template source, used for engine errors
+    pub span: Span,
+}
+
+/// Source of a template - either a name reference or inline template
+pub enum TemplateSource {
+    /// Reference to a named template from the registry (or inline if not found)
+    Name(String),
+    /// Plain string used verbatim, without registry lookup or substitution
+    Literal(String),
+    // TODO: Add explicit inline template support later
+    // /// Inline template string
+    // Inline(String),
+}
+
+/// Value of a parameter - literal, variable reference, or nested call
+pub enum ParamValue {
+    /// String, number, boolean, or list literal, keeping its type
+    Literal(serde_json::Value),
+    /// Reference to a let binding
+    Variable(Ident),
+    /// Nested template!() call
+    Nested(Box<TemplateCall>),
+    /// Transform of a value that names a let binding, e.g. `snake_case(table)`
+    Transform(Transform),
+}
+
+/// A case transform whose value is resolved when the block is expanded
+pub struct Transform {
+    /// The simple engine filter the transform applies, e.g. `snake`
+    pub filter: &'static str,
+    /// A let binding, which a bare identifier stands for if there is one
+    /// and otherwise names itself, or another such transform
+    pub value: Box<ParamValue>,
+    pub span: Span,
+}
+
+impl Parse for CompositionBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content = input;
+        
+        let mut statements = Vec::new();
+        
+        while !content.is_empty() {
+            // Parse attributes if any
+            let attrs = content.call(Attribute::parse_outer)?;
+            
+            if content.peek(Token![let]) {
+                if let Some(attr) = attrs.iter().find(|attr| !attr.path().is_ident("cfg")) {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "Only #[cfg(...)] attributes are allowed on let bindings",
+                    ));
+                }
+                statements.push(parse_let_statement(content, attrs)?);
+            } else if content.peek(Token![const]) {
+                statements.push(parse_const_statement(content, attrs)?);
+            } else if is_enum_statement(content) {
+                statements.push(parse_enum_statement(content, attrs)?);
+            } else if is_matrix_statement(content) {
+                statements.push(parse_matrix_statement(content, attrs)?);
+            } else if is_fn_statement(content) {
+                statements.push(parse_fn_statement(content, attrs)?);
+            } else {
+                return Err(content.error(
+                    "Expected 'let', 'const', 'enum', 'fn', or 'tomplate_matrix!' statement",
+                ));
+            }
+            
+            // Consume optional trailing comma
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        
+        Ok(CompositionBlock { statements })
+    }
+}
+
+impl Parse for ExpressionBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        syn::braced!(content in input);
+        
+        let mut statements = Vec::new();
+        let value = loop {
+            let attrs = content.call(Attribute::parse_outer)?;
+            if let Some(attr) = attrs.first() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "Attributes are not allowed in expression blocks",
+                ));
+            }
+            
+            if content.peek(Token![let]) {
+                statements.push(parse_let_statement(&content, attrs)?);
+            } else {
+                break parse_template_call(&content)?;
+            }
+        };
+        
+        if !content.is_empty() {
+            return Err(content.error("Expected end of block after the final tomplate!() call"));
+        }
+        if !input.is_empty() {
+            return Err(input.error("Unexpected tokens after expression block"));
+        }
+        
+        Ok(ExpressionBlock { statements, value })
+    }
+}
+
+fn parse_let_statement(input: ParseStream, attrs: Vec<Attribute>) -> Result<Statement> {
+    input.parse::<Token![let]>()?;
+    let name = input.parse::<Ident>()?;
+    input.parse::<Token![=]>()?;
+    let value = if input.peek(LitStr) {
+        // Plain string fragment: `let fields = "id, name";`
+        let lit = input.parse::<LitStr>()?;
+        TemplateCall {
+            source: TemplateSource::Literal(lit.value()),
+            params: Vec::new(),
+            options: Options::default(),
+            span: lit.span(),
+        }
+    } else {
+        parse_template_call(input)?
+    };
+    input.parse::<Token![;]>()?;
+    
+    Ok(Statement::Let { attrs, name, value })
+}
+
+fn parse_const_statement(input: ParseStream, attrs: Vec<Attribute>) -> Result<Statement> {
+    input.parse::<Token![const]>()?;
+    let name = input.parse::<Ident>()?;
+    input.parse::<Token![=]>()?;
+    let value = parse_template_call(input)?;
+    input.parse::<Token![;]>()?;
+    
+    Ok(Statement::Const { attrs, name, value })
+}
+
+fn is_fn_statement(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Visibility>().is_ok() && fork.peek(Token![fn])
+}
+
+fn parse_fn_statement(input: ParseStream, attrs: Vec<Attribute>) -> Result<Statement> {
+    let vis = input.parse::<Visibility>()?;
+    input.parse::<Token![fn]>()?;
+    let name = input.parse::<Ident>()?;
+    
+    let content;
+    syn::parenthesized!(content in input);
+    let params = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect();
+    
+    input.parse::<Token![=]>()?;
+    let value = parse_template_call(input)?;
+    input.parse::<Token![;]>()?;
+    
+    Ok(Statement::Fn { attrs, vis, name, params, value })
+}
+
+fn is_enum_statement(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Visibility>().is_ok() && fork.peek(Token![enum])
+}
+
+fn parse_enum_statement(input: ParseStream, attrs: Vec<Attribute>) -> Result<Statement> {
+    let vis = input.parse::<Visibility>()?;
+    input.parse::<Token![enum]>()?;
+    let name = input.parse::<Ident>()?;
+    
+    let content;
+    syn::braced!(content in input);
+    
+    let mut variants = Vec::new();
+    while !content.is_empty() {
+        let attrs = content.call(Attribute::parse_outer)?;
+        let name = content.parse::<Ident>()?;
+        content.parse::<Token![=]>()?;
+        let value = parse_template_call(&content)?;
+        variants.push(EnumVariant { attrs, name, value });
+        
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    
+    if variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "Enum must have at least one variant",
+        ));
+    }
+    
+    Ok(Statement::Enum { attrs, vis, name, variants })
+}
+
+impl Parse for MatrixSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name_pattern = input.parse::<LitStr>()?;
+        input.parse::<Token![,]>()?;
+        let value = parse_template_call(input)?;
+        let template = match &value.source {
+            TemplateSource::Name(name) | TemplateSource::Literal(name) => name.as_str(),
+        };
+        
+        // Parse axes: name = [value, ...]
+        let mut axes = Vec::new();
+        while input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            let content;
+            syn::bracketed!(content in input);
+            
+            let mut values = Vec::new();
+            for expr in Punctuated::<Expr, Token![,]>::parse_terminated(&content)? {
+                match parse_param_value(&expr, template)? {
+                    ParamValue::Literal(value) => {
+                        values.push(crate::engines::filters::to_text(&value, ", "))
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            expr,
+                            "Matrix axis values must be literals",
+                        ));
+                    }
+                }
+            }
+            if values.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "Matrix axis must have at least one value",
+                ));
+            }
+            axes.push((name, values));
+        }
+        
+        if !input.is_empty() {
+            return Err(input.error("Expected ',' followed by matrix axes"));
+        }
+        
+        Ok(MatrixSpec { name_pattern, value, axes })
+    }
+}
+
+impl Parse for ForEachSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let pattern = input.parse::<LitStr>()?;
+        input.parse::<Token![,]>()?;
+        
+        let output = if input.peek(LitStr) {
+            ForEachOutput::Consts(input.parse()?)
+        } else {
+            input.parse::<Token![|]>()?;
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![,]>()?;
+            let template = input.parse::<Ident>()?;
+            input.parse::<Token![|]>()?;
+            
+            let body = match input.parse::<proc_macro2::TokenTree>()? {
+                proc_macro2::TokenTree::Group(group) => group,
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "Expected a braced, bracketed or parenthesized body",
+                    ));
+                }
+            };
+            ForEachOutput::Tokens { name, template, body }
+        };
+        
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("Unexpected tokens after tomplate_for_each! body"));
+        }
+        
+        Ok(ForEachSpec { pattern, output })
+    }
+}
+
+fn is_matrix_statement(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<syn::Path>()
+        .map(|path| path.is_ident("tomplate_matrix"))
+        .unwrap_or(false)
+        && fork.peek(Token![!])
+}
+
+fn parse_matrix_statement(input: ParseStream, attrs: Vec<Attribute>) -> Result<Statement> {
+    let mac = input.parse::<syn::Macro>()?;
+    let spec = syn::parse2::<MatrixSpec>(mac.tokens)?;
+    
+    // The semicolon is optional after a braced invocation
+    if input.peek(Token![;]) {
+        input.parse::<Token![;]>()?;
+    }
+    
+    Ok(Statement::Matrix { attrs, spec })
+}
+
+fn parse_template_call(input: ParseStream) -> Result<TemplateCall> {
+    // Expect tomplate!(...) 
+    let mac: ExprMacro = input.parse()?;
+    
+    // Verify it's a tomplate! macro
+    if !mac.mac.path.is_ident("tomplate") {
+        return Err(syn::Error::new_spanned(
+            mac,
+            "Expected 'tomplate!' macro call",
+        ));
+    }
+    
+    // Parse the macro arguments
+    parse_template_args(mac.mac.tokens)
+}
+
+pub fn parse_template_args(tokens: proc_macro2::TokenStream) -> Result<TemplateCall> {
+    let parser = |input: ParseStream| -> Result<TemplateCall> {
+        let mut options = parse_options(&input.call(Attribute::parse_outer)?)?;
+        parse_modifiers(input, &mut options)?;
+        
+        // First argument is either a template name or inline template
+        let first_arg = input.parse::<Expr>()?;
+        let span = first_arg.span();
+        // This could be either a template name or inline template
+        // We'll determine this later based on registry lookup
+        let template_name = parse_name(&first_arg)?;
+        
+        let mut params = Vec::new();
+        
+        // Parse optional parameters
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            
+            let args = Punctuated::<Expr, Token![,]>::parse_terminated
+                .parse2(expand_references(input.parse()?)?)?;
+            
+            for arg in args {
+                match arg {
+                    // key = value syntax
+                    Expr::Assign(assign) => {
+                        let param_name = parse_param_name(&assign.left)?;
+                        
+                        let param_value = parse_param_value(&assign.right, &template_name)?;
+                        params.push((param_name, param_value));
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            arg,
+                            "Expected 'key = value' syntax",
+                        ));
+                    }
+                }
+            }
+        }
+        
+        let source = TemplateSource::Name(template_name);
+        Ok(TemplateCall { source, params, options, span })
+    };
+    
+    parser.parse2(tokens)
+}
+
+/// Parse rendering options given as attributes before the template, e.g.
+/// `tomplate!(#[indent] "name", ...)` or `tomplate!(#[locale = "de"] "name")`
+pub fn parse_options(attrs: &[Attribute]) -> Result<Options> {
+    let mut options = Options::default();
+    for attr in attrs {
+        let name = attr.path().require_ident()?;
+        let result = match &attr.meta {
+            syn::Meta::Path(_) => options.enable(&name.to_string()),
+            syn::Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) => {
+                    options.set(&name.to_string(), value.value())
+                }
+                value => {
+                    return Err(syn::Error::new_spanned(value, "Expected a string literal"));
+                }
+            },
+            syn::Meta::List(_) => {
+                return Err(syn::Error::new_spanned(attr, "Expected `#[option]` or `#[option = \"value\"]`"));
+            }
+        };
+        result.map_err(|e| syn::Error::new_spanned(name, e))?;
+    }
+    Ok(options)
+}
+
+/// Parse the output modifiers written before a call's template name, e.g.
+/// the `dedent trim` of `tomplate!(dedent trim "long_template", ...)`
+pub fn parse_modifiers(input: ParseStream, options: &mut Options) -> Result<()> {
+    // Names written as `concat!(...)` or `std::concat!(...)` start with an
+    // identifier too
+    while input.peek(Ident) && !input.peek2(Token![!]) && !input.peek2(Token![::]) {
+        let modifier = input.parse::<Ident>()?;
+        options
+            .modify(&modifier.to_string())
+            .map_err(|e| syn::Error::new_spanned(&modifier, e))?;
+    }
+    Ok(())
+}
+
+/// Parse a literal parameter value, keeping numbers and booleans typed so
+/// engines can compare and branch on them
+pub fn parse_literal(lit: &Lit) -> Result<serde_json::Value> {
+    match lit {
+        Lit::Str(s) => Ok(s.value().into()),
+        Lit::Int(i) => match i.base10_parse::<i64>() {
+            Ok(value) => Ok(value.into()),
+            Err(_) => Ok(i.base10_parse::<u64>()?.into()),
+        },
+        Lit::Float(f) => Ok(f.base10_parse::<f64>()?.into()),
+        Lit::Bool(b) => Ok(b.value.into()),
+        _ => Err(syn::Error::new_spanned(lit, "Unsupported literal type")),
+    }
+}
+
+/// Parse a negative number such as `-1234.5`, which Rust writes as a negated
+/// literal
+pub fn parse_negative(unary: &ExprUnary) -> Result<serde_json::Value> {
+    let (UnOp::Neg(_), Expr::Lit(ExprLit { lit, .. })) = (&unary.op, &*unary.expr) else {
+        return Err(syn::Error::new_spanned(unary, "Only number literals can be negated"));
+    };
+    match lit {
+        // Parsed with the sign so `i64::MIN` fits
+        Lit::Int(i) => format!("-{}", i.base10_digits())
+            .parse::<i64>()
+            .map(Into::into)
+            .map_err(|e| syn::Error::new_spanned(lit, e)),
+        Lit::Float(f) => Ok((-f.base10_parse::<f64>()?).into()),
+        _ => Err(syn::Error::new_spanned(unary, "Only number literals can be negated")),
+    }
+}
+
+/// Parse an array of literals into a list parameter value
+pub fn parse_list(array: &ExprArray) -> Result<serde_json::Value> {
+    array
+        .elems
+        .iter()
+        .map(|elem| match elem {
+            Expr::Lit(lit) => parse_literal(&lit.lit),
+            Expr::Unary(unary) => parse_negative(unary),
+            _ => Err(syn::Error::new_spanned(elem, "List items must be literals")),
+        })
+        .collect()
+}
+
+/// Check whether a macro call is a structured `json!` or `toml!` value
+pub fn is_structured(mac: &ExprMacro) -> bool {
+    mac.mac
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "json" || segment.ident == "toml")
+}
+
+/// Expand `name = @template` parameters into `name = tomplate!("template")`
+///
+/// The shorthand is for templates without parameters, such as a shared field
+/// list. Only the call's own arguments are expanded; nested calls expand
+/// theirs when they're parsed.
+pub fn expand_references(tokens: proc_macro2::TokenStream) -> Result<proc_macro2::TokenStream> {
+    use proc_macro2::TokenTree;
+    
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut expanded = proc_macro2::TokenStream::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if let [TokenTree::Punct(eq), TokenTree::Punct(at), TokenTree::Ident(name), ..] = &tokens[i..]
+            && eq.as_char() == '='
+            && at.as_char() == '@'
+        {
+            let template = name.to_string();
+            if let Some(entry) = crate::templates::get(&template)
+                && !entry.params.is_empty()
+            {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "@{} takes parameters ({}), call it with tomplate!(\"{}\", ...) instead",
+                        template,
+                        entry.params.join(", "),
+                        template
+                    ),
+                ));
+            }
+            let literal = LitStr::new(&template, name.span());
+            expanded.extend([tokens[i].clone()]);
+            expanded.extend(quote::quote_spanned!(name.span()=> tomplate!(#literal)));
+            i += 3;
+            continue;
+        }
+        expanded.extend([tokens[i].clone()]);
+        i += 1;
+    }
+    Ok(expanded)
+}
+
+/// Case transforms a parameter value can be wrapped in, e.g.
+/// `table = upper("users")`, with the simple engine filter each applies
+pub const TRANSFORMS: &[(&str, &str)] = &[
+    ("upper", "upper"),
+    ("lower", "lower"),
+    ("snake_case", "snake"),
+    ("kebab_case", "kebab"),
+    ("camel_case", "camel"),
+    ("pascal_case", "pascal"),
+    ("screaming_snake_case", "screaming"),
+];
+
+/// Check whether a call is a parameter value evaluated at expansion time:
+/// `file("path")` or a transform such as `upper("users")`
+pub fn is_value_call(call: &ExprCall) -> bool {
+    match &*call.func {
+        Expr::Path(path) => path.path.get_ident().is_some_and(|ident| {
+            ident == "file" || TRANSFORMS.iter().any(|(name, _)| ident == name)
+        }),
+        _ => false,
+    }
+}
+
+/// Evaluate a `file("path")` or transform parameter value of a call to
+/// `template`
+///
+/// Transforms take a literal, a list, another such call, or a bare
+/// identifier standing for its own name, so `snake_case(MY_IDENT_STR)` is
+/// `my_ident_str`. Lists are transformed item by item. In composition
+/// blocks an identifier names a let binding instead where one exists.
+pub fn parse_value_call(call: &ExprCall, template: &str) -> Result<serde_json::Value> {
+    let Some((name, filter)) = transform(call) else {
+        return read_file_param(call, template);
+    };
+    let arg = transform_arg(call, name)?;
+    let value = match arg {
+        Expr::Lit(lit) => parse_literal(&lit.lit)?,
+        Expr::Array(array) => parse_list(array)?,
+        Expr::Path(path) if path.path.get_ident().is_some() => {
+            let ident = path.path.get_ident().unwrap();
+            serde_json::Value::String(syn::ext::IdentExt::unraw(ident).to_string())
+        }
+        Expr::Call(inner) if is_value_call(inner) => parse_value_call(inner, template)?,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                arg,
+                format!("{}() expects a literal, list, identifier, file() or transform", name),
+            ))
+        }
+    };
+    crate::engines::filters::apply(filter, None, &value)
+        .map_err(|e| syn::Error::new_spanned(call, e))
+}
+
+/// Parse a `file("path")` or transform parameter value in a block, where a
+/// bare identifier in a transform refers to a let binding of that name if
+/// there is one
+fn parse_value_call_param(call: &ExprCall, template: &str) -> Result<ParamValue> {
+    let Some((name, filter)) = transform(call) else {
+        return Ok(ParamValue::Literal(read_file_param(call, template)?));
+    };
+    let value = match transform_arg(call, name)? {
+        Expr::Path(path) if path.path.get_ident().is_some() => {
+            ParamValue::Variable(path.path.get_ident().unwrap().clone())
+        }
+        Expr::Call(inner) if is_value_call(inner) => parse_value_call_param(inner, template)?,
+        // Literals and lists are transformed right away
+        _ => return Ok(ParamValue::Literal(parse_value_call(call, template)?)),
+    };
+    match value {
+        // The inner call needed no binding, so neither does this one
+        ParamValue::Literal(value) => crate::engines::filters::apply(filter, None, &value)
+            .map(ParamValue::Literal)
+            .map_err(|e| syn::Error::new_spanned(call, e)),
+        value => Ok(ParamValue::Transform(Transform {
+            filter,
+            value: Box::new(value),
+            span: call.span(),
+        })),
+    }
+}
+
+/// The name and filter of a transform call, or `None` for `file()`
+fn transform(call: &ExprCall) -> Option<(&'static str, &'static str)> {
+    let Expr::Path(path) = &*call.func else {
+        return None;
+    };
+    TRANSFORMS.iter().copied().find(|(name, _)| path.path.is_ident(name))
+}
+
+/// The single argument of a transform call
+fn transform_arg<'a>(call: &'a ExprCall, name: &str) -> Result<&'a Expr> {
+    match call.args.iter().collect::<Vec<_>>().as_slice() {
+        [arg] => Ok(*arg),
+        _ => Err(syn::Error::new_spanned(
+            call,
+            format!("{}() expects a single value", name),
+        )),
+    }
+}
+
+/// Read a `file("path")` parameter value
+///
+/// The path is relative to the registry file defining `template`, so a
+/// template's snippets are found wherever it's called from. Templates that
+/// aren't in the registry resolve it relative to the crate root. The file is
+/// tracked so the build script reruns when it changes.
+fn read_file_param(call: &ExprCall, template: &str) -> Result<serde_json::Value> {
+    let path = match call.args.iter().collect::<Vec<_>>().as_slice() {
+        [Expr::Lit(ExprLit { lit: Lit::Str(path), .. })] => path,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                call,
+                "file() expects a single string literal path",
+            ))
+        }
+    };
+    
+    let base = crate::templates::get(template)
+        .and_then(|template| Some(template.source_file.as_ref()?.parent()?.to_path_buf()))
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(std::path::PathBuf::from))
+        .unwrap_or_default();
+    let full_path = base.join(path.value());
+    let contents = std::fs::read_to_string(&full_path).map_err(|e| {
+        syn::Error::new_spanned(path, format!("Failed to read {}: {}", full_path.display(), e))
+    })?;
+    crate::templates::track_file(&full_path);
+    Ok(serde_json::Value::String(contents))
+}
+
+/// Parse a structured `json!(...)` or `toml!(...)` parameter value
+///
+/// `json!` takes inline JSON such as `json!({"ids": [1, 2]})` or a string of
+/// JSON text, while `toml!` takes a string of TOML text.
+pub fn parse_structured(mac: &ExprMacro) -> Result<serde_json::Value> {
+    let is_json = mac.mac.path.segments.last().is_some_and(|segment| segment.ident == "json");
+    
+    match syn::parse2::<LitStr>(mac.mac.tokens.clone()) {
+        Ok(text) if is_json => serde_json::from_str(&text.value())
+            .map_err(|e| syn::Error::new_spanned(&text, format!("Invalid JSON: {}", e))),
+        Ok(text) => toml::from_str(&text.value())
+            .map_err(|e| syn::Error::new_spanned(&text, format!("Invalid TOML: {}", e))),
+        Err(_) if is_json => mac.mac.parse_body_with(parse_json),
+        Err(_) => Err(syn::Error::new_spanned(
+            mac,
+            "toml!() expects a string literal of TOML text",
+        )),
+    }
+}
+
+/// Parse inline JSON written as Rust tokens
+fn parse_json(input: ParseStream) -> Result<serde_json::Value> {
+    if input.peek(syn::token::Brace) {
+        let content;
+        syn::braced!(content in input);
+        
+        let mut object = serde_json::Map::new();
+        while !content.is_empty() {
+            let key = content.parse::<LitStr>()?;
+            content.parse::<Token![:]>()?;
+            object.insert(key.value(), parse_json(&content)?);
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+        Ok(object.into())
+    } else if input.peek(syn::token::Bracket) {
+        let content;
+        syn::bracketed!(content in input);
+        
+        Punctuated::<_, Token![,]>::parse_terminated_with(&content, parse_json)
+            .map(|items| items.into_iter().collect())
+    } else if input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        match input.parse::<Lit>()? {
+            Lit::Int(i) => Ok((-i.base10_parse::<i64>()?).into()),
+            Lit::Float(f) => Ok((-f.base10_parse::<f64>()?).into()),
+            lit => Err(syn::Error::new_spanned(lit, "Expected a number")),
+        }
+    } else if input.peek(Lit) {
+        parse_literal(&input.parse()?)
+    } else {
+        let ident = input.parse::<Ident>()?;
+        if ident != "null" {
+            return Err(syn::Error::new_spanned(ident, "Expected a JSON value"));
+        }
+        Ok(serde_json::Value::Null)
+    }
+}
+
+/// Resolve the template name of a call: a string literal, or `concat!`,
+/// `env!` and `stringify!` calls that evaluate to one
+///
+/// `env!` reads the variable as the compiler sees it, e.g. one set by the
+/// build script with `cargo:rustc-env`. Names passed through `macro_rules!`
+/// macros arrive as invisible groups, which are looked through.
+pub fn parse_name(expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Ok(s.value()),
+        Expr::Group(group) => parse_name(&group.expr),
+        Expr::Macro(mac) => {
+            let name = mac.mac.path.segments.last().map(|segment| segment.ident.to_string());
+            match name.as_deref() {
+                Some("concat") => {
+                    let args = mac.mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+                    let mut name = String::new();
+                    for arg in &args {
+                        match arg {
+                            Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => name.push_str(lit.base10_digits()),
+                            Expr::Lit(ExprLit { lit: Lit::Char(lit), .. }) => name.push(lit.value()),
+                            Expr::Lit(ExprLit { lit: Lit::Bool(lit), .. }) => name.push_str(&lit.value.to_string()),
+                            arg => name.push_str(&parse_name(arg)?),
+                        }
+                    }
+                    Ok(name)
+                }
+                Some("env") => {
+                    let args = mac.mac.parse_body_with(Punctuated::<LitStr, Token![,]>::parse_terminated)?;
+                    let variable = args
+                        .first()
+                        .ok_or_else(|| syn::Error::new_spanned(mac, "env! takes 1 or 2 arguments"))?;
+                    std::env::var(variable.value()).map_err(|_| {
+                        let message = args.get(1).map(LitStr::value).unwrap_or_else(|| {
+                            format!("environment variable `{}` not defined at compile time", variable.value())
+                        });
+                        syn::Error::new_spanned(variable, message)
+                    })
+                }
+                Some("stringify") => Ok(mac.mac.tokens.to_string()),
+                _ => Err(syn::Error::new_spanned(
+                    mac,
+                    "Only concat!, env! and stringify! can be expanded in a template name; \
+                     to pick a name with another macro, have a macro_rules! macro pass the \
+                     name to tomplate! instead",
+                )),
+            }
+        }
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "Expected template name as string literal or a concat!/env! expression",
+        )),
+    }
+}
+
+/// Parse a parameter name: a simple identifier or a dotted path like `user.name`
+pub fn parse_param_name(expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Path(path) if path.path.segments.len() == 1 => {
+            Ok(path.path.segments[0].ident.to_string())
+        }
+        Expr::Field(field) => match &field.member {
+            syn::Member::Named(member) => {
+                Ok(format!("{}.{}", parse_param_name(&field.base)?, member))
+            }
+            syn::Member::Unnamed(_) => Err(syn::Error::new_spanned(
+                &field.member,
+                "Parameter name segments must be identifiers",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "Parameter name must be an identifier or a dotted path like `user.name`",
+        )),
+    }
+}
+
+/// Parse a parameter value of a call to `template`
+fn parse_param_value(expr: &Expr, template: &str) -> Result<ParamValue> {
+    match expr {
+        // Literal values
+        Expr::Lit(lit) => Ok(ParamValue::Literal(parse_literal(&lit.lit)?)),
+        Expr::Unary(unary) => Ok(ParamValue::Literal(parse_negative(unary)?)),
+        // Snippet file or transform: `body = file("snippets/footer.sql")`,
+        // `table = upper("users")`
+        Expr::Call(call) if is_value_call(call) => parse_value_call_param(call, template),
+        // List of literals: `cols = ["id", "name"]`
+        Expr::Array(array) => Ok(ParamValue::Literal(parse_list(array)?)),
+        // Variable reference (simple identifier)
+        Expr::Path(path) if path.path.segments.len() == 1 => {
+            Ok(ParamValue::Variable(path.path.segments[0].ident.clone()))
+        }
+        // Structured json!() or toml!() value
+        Expr::Macro(mac) if is_structured(mac) => Ok(ParamValue::Literal(parse_structured(mac)?)),
+        // Nested tomplate!() call
+        Expr::Macro(mac) if mac.mac.path.is_ident("tomplate") => {
+            let nested = parse_template_args(mac.mac.tokens.clone())?;
+            Ok(ParamValue::Nested(Box::new(nested)))
+        }
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "Parameter value must be a literal, list, json!() or toml!() value, file() or transform call, variable reference, or tomplate!() call",
+        )),
+    }
+}
@@ -0,0 +1,138 @@
+//! Bind placeholders in rendered SQL: `$1`, `?` and `:name`.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use crate::types::Placeholders;
+
+/// A bind placeholder found in SQL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placeholder {
+    /// `$1`, `$2`, ...
+    Numbered(usize),
+    /// `?`
+    Positional,
+    /// `:name`
+    Named(String),
+}
+
+/// Find the bind placeholders in SQL with their byte ranges
+///
+/// Placeholders in string literals, quoted identifiers and comments are
+/// ignored, as are `::` casts.
+pub fn find(sql: &str) -> Result<Vec<(Range<usize>, Placeholder)>, String> {
+    let mut found = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    let mut previous = None;
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                for (_, next) in chars.by_ref() {
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek().is_some_and(|&(_, next)| next == '-') => {
+                for (_, next) in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek().is_some_and(|&(_, next)| next == '*') => {
+                chars.next();
+                let mut last = ' ';
+                for (_, next) in chars.by_ref() {
+                    if last == '*' && next == '/' {
+                        break;
+                    }
+                    last = next;
+                }
+            }
+            '$' => {
+                let mut end = start + 1;
+                while let Some((index, _)) = chars.next_if(|(_, next)| next.is_ascii_digit()) {
+                    end = index + 1;
+                }
+                let digits = &sql[start + 1..end];
+                if !digits.is_empty() {
+                    match digits.parse::<usize>() {
+                        Ok(0) | Err(_) => return Err(format!("${} is not a valid placeholder", digits)),
+                        Ok(n) => found.push((start..end, Placeholder::Numbered(n))),
+                    }
+                }
+            }
+            '?' => found.push((start..start + 1, Placeholder::Positional)),
+            ':' if previous != Some(':')
+                && chars.peek().is_some_and(|&(_, next)| next.is_ascii_alphabetic() || next == '_') =>
+            {
+                let mut end = start + 1;
+                while let Some((index, next)) =
+                    chars.next_if(|(_, next)| next.is_ascii_alphanumeric() || *next == '_')
+                {
+                    end = index + next.len_utf8();
+                }
+                found.push((start..end, Placeholder::Named(sql[start + 1..end].to_string())));
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+
+    Ok(found)
+}
+
+/// Rewrite the bind placeholders in SQL to another style
+///
+/// `?` placeholders are numbered in order, and `:name` placeholders are
+/// numbered by first appearance. Named output uses `:p1`, `:p2`, ... for
+/// numbered and positional placeholders. `$n` placeholders can only become
+/// `?` when each appears once, in order.
+pub fn rewrite(sql: &str, style: Placeholders) -> Result<String, String> {
+    let found = find(sql)?;
+    let mut rewritten = String::with_capacity(sql.len());
+    let mut last = 0;
+    let mut positional = 0;
+    let mut names: HashMap<String, usize> = HashMap::new();
+
+    for (range, placeholder) in found {
+        rewritten.push_str(&sql[last..range.start]);
+        last = range.end;
+
+        let number = match &placeholder {
+            Placeholder::Numbered(n) => *n,
+            Placeholder::Positional => {
+                positional += 1;
+                positional
+            }
+            Placeholder::Named(name) => {
+                let next = names.len() + 1;
+                *names.entry(name.clone()).or_insert(next)
+            }
+        };
+
+        match (style, &placeholder) {
+            (Placeholders::Postgres, _) => rewritten.push_str(&format!("${}", number)),
+            (Placeholders::Mysql, Placeholder::Numbered(n)) => {
+                positional += 1;
+                if *n != positional {
+                    return Err(format!(
+                        "${} can't become ? because placeholders must appear once each, in order",
+                        n
+                    ));
+                }
+                rewritten.push('?');
+            }
+            (Placeholders::Mysql, _) => rewritten.push('?'),
+            (Placeholders::Named, Placeholder::Named(name)) => {
+                rewritten.push(':');
+                rewritten.push_str(name);
+            }
+            (Placeholders::Named, _) => rewritten.push_str(&format!(":p{}", number)),
+        }
+    }
+
+    rewritten.push_str(&sql[last..]);
+    Ok(rewritten)
+}
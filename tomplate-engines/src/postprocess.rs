@@ -0,0 +1,33 @@
+//! Steps applied to rendered output, set with a template's `postprocess`.
+
+use crate::types::Postprocess;
+
+/// Apply a post-processing step to rendered output
+pub fn apply(output: &str, step: Postprocess) -> Result<String, String> {
+    match step {
+        Postprocess::Markdown => markdown(output),
+        Postprocess::HtmlMinify => crate::minify::html(output),
+        Postprocess::CssMinify => crate::minify::css(output),
+        Postprocess::JsMinify => crate::minify::js(output),
+    }
+}
+
+/// Convert Markdown to HTML, with the CommonMark extensions for tables,
+/// strikethrough, footnotes and task lists
+#[cfg(feature = "markdown")]
+fn markdown(output: &str) -> Result<String, String> {
+    use pulldown_cmark::{Options, Parser};
+    
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_TASKLISTS;
+    let mut html = String::with_capacity(output.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, Parser::new_ext(output, options));
+    Ok(html)
+}
+
+#[cfg(not(feature = "markdown"))]
+fn markdown(_output: &str) -> Result<String, String> {
+    Err("The 'markdown' post-processing step requires the 'markdown' feature".to_string())
+}
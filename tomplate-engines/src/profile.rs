@@ -0,0 +1,111 @@
+//! Timing of template processing, enabled by `TOMPLATE_PROFILE`.
+//!
+//! The variable names a file that receives a summary of the expansions,
+//! registry loads and renders in the current compiler process. Proc macros
+//! have no hook at the end of compilation, so the summary is rewritten after
+//! every expansion.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Stats {
+    /// Count and total time per macro
+    expansions: BTreeMap<&'static str, (usize, Duration)>,
+    /// Count and total time of registry loads
+    registry_loads: (usize, Duration),
+    /// Count and total time of renders per engine
+    renders: BTreeMap<String, (usize, Duration)>,
+    /// Renders served from the in-process or on-disk cache
+    cached: usize,
+}
+
+static PATH: LazyLock<Option<String>> = LazyLock::new(|| std::env::var("TOMPLATE_PROFILE").ok());
+
+static STATS: LazyLock<Mutex<Stats>> = LazyLock::new(Default::default);
+
+/// Times a macro expansion until dropped
+pub struct Expansion {
+    name: &'static str,
+    start: Instant,
+}
+
+/// Start timing an expansion of the named macro
+pub fn expansion(name: &'static str) -> Option<Expansion> {
+    PATH.as_ref()?;
+    Some(Expansion { name, start: Instant::now() })
+}
+
+impl Drop for Expansion {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let mut stats = STATS.lock().unwrap();
+        let entry = stats.expansions.entry(self.name).or_default();
+        entry.0 += 1;
+        entry.1 += elapsed;
+        write_summary(&stats);
+    }
+}
+
+/// Time a registry load
+pub fn registry_load<T>(load: impl FnOnce() -> T) -> T {
+    if PATH.is_none() {
+        return load();
+    }
+    let start = Instant::now();
+    let result = load();
+    let mut stats = STATS.lock().unwrap();
+    stats.registry_loads.0 += 1;
+    stats.registry_loads.1 += start.elapsed();
+    result
+}
+
+/// Time a render by an engine
+pub fn render<T>(engine: &str, render: impl FnOnce() -> T) -> T {
+    if PATH.is_none() {
+        return render();
+    }
+    let start = Instant::now();
+    let result = render();
+    let elapsed = start.elapsed();
+    let mut stats = STATS.lock().unwrap();
+    let entry = stats.renders.entry(engine.to_string()).or_default();
+    entry.0 += 1;
+    entry.1 += elapsed;
+    result
+}
+
+/// Count a render served from a cache
+pub fn cached() {
+    if PATH.is_some() {
+        STATS.lock().unwrap().cached += 1;
+    }
+}
+
+fn write_summary(stats: &Stats) {
+    let Some(path) = PATH.as_ref() else {
+        return;
+    };
+    
+    let mut summary = String::new();
+    let _ = writeln!(
+        summary,
+        "tomplate profile for {}",
+        std::env::var("CARGO_PKG_NAME").unwrap_or_default()
+    );
+    let _ = writeln!(summary, "\nExpansions:");
+    for (name, (count, total)) in &stats.expansions {
+        let _ = writeln!(summary, "  {:<20} {:>6}  {:>10.2?}", name, count, total);
+    }
+    let (loads, load_time) = stats.registry_loads;
+    let _ = writeln!(summary, "\nRegistry loads: {} ({:.2?})", loads, load_time);
+    let _ = writeln!(summary, "\nRenders by engine:");
+    for (engine, (count, total)) in &stats.renders {
+        let _ = writeln!(summary, "  {:<20} {:>6}  {:>10.2?}", engine, count, total);
+    }
+    let _ = writeln!(summary, "  {:<20} {:>6}", "(cached)", stats.cached);
+    
+    let _ = std::fs::write(path, summary);
+}
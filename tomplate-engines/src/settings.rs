@@ -0,0 +1,58 @@
+//! Settings passed from the build script to the engines.
+//!
+//! The macros read them from the variables the build script sets with
+//! `cargo:rustc-env`, such as `TOMPLATE_TEMPLATES_PATH`. A build script
+//! rendering templates itself can't set its own environment safely, so it
+//! passes them to [`scoped`] instead, which applies them to everything
+//! rendered on the current thread until it returns.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::ffi::OsString;
+
+thread_local! {
+    static SCOPED: RefCell<Option<HashMap<String, String>>> = const { RefCell::new(None) };
+}
+
+/// A setting by variable name, from the current scope or the environment
+pub fn var(name: &str) -> Option<String> {
+    SCOPED
+        .with_borrow(|scoped| scoped.as_ref().map(|settings| settings.get(name).cloned()))
+        .unwrap_or_else(|| std::env::var(name).ok())
+}
+
+/// Like [`var`], for settings holding paths
+pub fn var_os(name: &str) -> Option<OsString> {
+    SCOPED
+        .with_borrow(|scoped| scoped.as_ref().map(|settings| settings.get(name).map(OsString::from)))
+        .unwrap_or_else(|| std::env::var_os(name))
+}
+
+/// A hash of the settings in the current scope, for caches of anything
+/// rendered under them
+///
+/// Zero outside a scope, where settings come from the environment.
+pub fn fingerprint() -> u64 {
+    SCOPED.with_borrow(|scoped| {
+        let Some(settings) = scoped else {
+            return 0;
+        };
+        let mut hasher = DefaultHasher::new();
+        settings.iter().collect::<BTreeMap<_, _>>().hash(&mut hasher);
+        hasher.finish()
+    })
+}
+
+/// Run `f` with the given settings in place of the environment's
+///
+/// Settings missing from the map are unset within the scope, rather than
+/// read from the environment, so the result doesn't depend on it.
+pub fn scoped<T>(settings: &HashMap<String, String>, f: impl FnOnce() -> T) -> T {
+    let previous = SCOPED.replace(Some(settings.clone()));
+    let result = f();
+    SCOPED.set(previous);
+    result
+}
+
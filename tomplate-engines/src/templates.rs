@@ -0,0 +1,279 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::hash::Hasher;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use crate::types::{IndexEntry, Template};
+
+/// The amalgamated registries, whose templates are parsed on first use
+///
+/// Indexed registries are read one template at a time rather than held in
+/// memory, so expansion cost scales with the templates used.
+struct Registry {
+    paths: Vec<PathBuf>,
+    /// Each template's registry and location in it
+    index: BTreeMap<String, (usize, IndexEntry)>,
+    parsed: Mutex<HashMap<String, Arc<Template>>>,
+    /// Hash of the registries' contents
+    fingerprint: u64,
+}
+
+/// The loaded registry and the state of the files it was loaded from
+struct Loaded {
+    stamp: Vec<(PathBuf, Option<SystemTime>, Option<u64>)>,
+    registry: Arc<Registry>,
+}
+
+// Cache for the registry - loaded from OUT_DIR, and reloaded whenever the
+// files change so long-lived proc-macro servers such as rust-analyzer don't
+// keep serving stale templates
+//
+// `TOMPLATE_TEMPLATES_PATH` holds one or more registries separated like
+// `PATH`; a template in an earlier registry shadows one of the same name in
+// a later registry, so a crate's own templates can be layered over shared ones.
+static REGISTRY: Mutex<Option<Loaded>> = Mutex::new(None);
+
+/// Whether the build script's registries are available
+///
+/// They are missing when the crate has no build script using
+/// `tomplate-build`, or before it has run, as in a fresh rust-analyzer
+/// session. The registry is then empty and renders that fail expand to stubs
+/// instead of errors.
+pub fn available() -> bool {
+    crate::settings::var_os("TOMPLATE_TEMPLATES_PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).all(|path| path.exists()))
+}
+
+/// The current registry, reloading it if its files changed since last use
+fn registry() -> Arc<Registry> {
+    // Get the OUT_DIR from the environment at macro expansion time
+    let tomplate_paths = crate::settings::var_os("TOMPLATE_TEMPLATES_PATH").unwrap_or_default();
+    let stamp = stamp(&tomplate_paths);
+    
+    let mut loaded = REGISTRY.lock().unwrap();
+    if let Some(loaded) = &*loaded
+        && loaded.stamp == stamp
+    {
+        return loaded.registry.clone();
+    }
+    
+    let registry = Arc::new(crate::profile::registry_load(|| load(&tomplate_paths)));
+    *loaded = Some(Loaded { stamp, registry: registry.clone() });
+    registry
+}
+
+/// Load the registries, indexing or parsing each
+fn load(tomplate_paths: &OsString) -> Registry {
+    let mut registry = Registry {
+        paths: Vec::new(),
+        index: BTreeMap::new(),
+        parsed: Mutex::new(HashMap::new()),
+        fingerprint: 0,
+    };
+    let mut hasher = DefaultHasher::new();
+    for tomplate_path in std::env::split_paths(tomplate_paths) {
+        hash_file(&tomplate_path, &mut hasher);
+        hasher.write_u8(0xff);
+        registry.add_layer(tomplate_path);
+    }
+    registry.fingerprint = hasher.finish();
+    registry
+}
+
+/// Modification times and sizes of the registries and their indexes
+fn stamp(tomplate_paths: &OsString) -> Vec<(PathBuf, Option<SystemTime>, Option<u64>)> {
+    std::env::split_paths(tomplate_paths)
+        .flat_map(|path| [path.with_extension("index.json"), path])
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            let modified = metadata.as_ref().and_then(|metadata| metadata.modified().ok());
+            let len = metadata.map(|metadata| metadata.len());
+            (path, modified, len)
+        })
+        .collect()
+}
+
+/// Hash a file's contents without reading it into memory at once
+fn hash_file(path: &Path, hasher: &mut DefaultHasher) {
+    let Ok(file) = File::open(path) else {
+        return;
+    };
+    let mut reader = BufReader::new(file);
+    while let Ok(chunk) = reader.fill_buf() {
+        if chunk.is_empty() {
+            break;
+        }
+        hasher.write(chunk);
+        let len = chunk.len();
+        reader.consume(len);
+    }
+}
+
+impl Registry {
+    /// Add a registry whose templates are shadowed by those already added
+    fn add_layer(&mut self, path: PathBuf) {
+        let layer = self.paths.len();
+        
+        // The builder writes an index of where each template is; without one,
+        // parse the whole registry up front
+        let index: Option<BTreeMap<String, IndexEntry>> =
+            std::fs::read_to_string(path.with_extension("index.json"))
+                .ok()
+                .and_then(|index| serde_json::from_str(&index).ok());
+        match index {
+            Some(index) => {
+                for (name, entry) in index {
+                    self.index.entry(name).or_insert((layer, entry));
+                }
+            }
+            None => {
+                let source = std::fs::read_to_string(&path).unwrap_or_else(|_| String::new());
+                let templates: HashMap<String, Template> = if source.is_empty() {
+                    HashMap::new()
+                } else {
+                    toml::from_str(&source).expect("Failed to parse amalgamated templates TOML")
+                };
+                let parsed = self.parsed.get_mut().unwrap();
+                for (name, mut template) in templates {
+                    if self.index.contains_key(&name) {
+                        continue;
+                    }
+                    resolve_source_file(&mut template, &path);
+                    let entry = IndexEntry { engine: template.engine.clone(), ..Default::default() };
+                    self.index.insert(name.clone(), (layer, entry));
+                    parsed.insert(name, Arc::new(template));
+                }
+            }
+        }
+        self.paths.push(path);
+    }
+    
+    /// Read a template's table from its registry
+    fn read_table(&self, layer: usize, entry: &IndexEntry) -> Option<String> {
+        let mut file = File::open(&self.paths[layer]).ok()?;
+        file.seek(SeekFrom::Start(entry.start as u64)).ok()?;
+        let mut table = String::new();
+        file.take(entry.end.checked_sub(entry.start)? as u64)
+            .read_to_string(&mut table)
+            .ok()?;
+        Some(table)
+    }
+}
+
+/// Resolve a template's source file, which the registry records relative to
+/// itself
+fn resolve_source_file(template: &mut Template, registry: &Path) {
+    if let Some(source_file) = &mut template.source_file {
+        *source_file = registry.parent().unwrap_or(Path::new("")).join(&*source_file);
+    }
+}
+
+/// Get a registry template, parsing it on first use
+///
+/// Parsed templates are shared by every expansion until the registry changes.
+pub fn get(name: &str) -> Option<Arc<Template>> {
+    let registry = registry();
+    if let Some(template) = registry.parsed.lock().unwrap().get(name) {
+        return Some(template.clone());
+    }
+    
+    let (layer, entry) = registry.index.get(name)?;
+    let table = registry.read_table(*layer, entry)?;
+    let mut templates: HashMap<String, Template> =
+        toml::from_str(&table).expect("Failed to parse amalgamated templates TOML");
+    let mut template = templates.remove(name)?;
+    resolve_source_file(&mut template, &registry.paths[*layer]);
+    let template = Arc::new(template);
+    Some(registry.parsed.lock().unwrap().entry(name.to_string()).or_insert(template).clone())
+}
+
+/// Names of all registry templates, in sorted order
+pub fn names() -> Vec<String> {
+    registry().index.keys().cloned().collect()
+}
+
+/// Names of the registry templates using an engine, in sorted order
+pub fn names_for_engine(engine: &str) -> Vec<String> {
+    registry()
+        .index
+        .iter()
+        .filter(|(_, (_, entry))| entry.engine.as_deref() == Some(engine))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Hash of the registries' contents, for caches of anything derived from
+/// them
+pub fn fingerprint() -> u64 {
+    registry().fingerprint
+}
+
+/// Names of the registry templates matching a glob such as `"migrations/*"`,
+/// in sorted order
+///
+/// A pattern without glob characters matches names starting with it.
+pub fn matching_names(pattern: &str) -> Result<Vec<String>, String> {
+    let glob = if pattern.contains(['*', '?', '[']) {
+        Some(glob::Pattern::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?)
+    } else {
+        None
+    };
+    
+    Ok(names()
+        .into_iter()
+        .filter(|name| match &glob {
+            Some(glob) => glob.matches(name),
+            None => name.starts_with(pattern),
+        })
+        .collect())
+}
+
+/// Add a file read during the expansion to the list named by
+/// `TOMPLATE_TRACKED_FILES`, so the build script reruns when it changes
+///
+/// Files already listed aren't added again, leaving the list untouched once
+/// every file is tracked. Failing to write it never fails the expansion.
+pub fn track_file(path: &Path) {
+    let Some(list) = crate::settings::var("TOMPLATE_TRACKED_FILES") else {
+        return;
+    };
+    let entry = path.display().to_string();
+    let tracked = std::fs::read_to_string(&list).unwrap_or_default();
+    if tracked.lines().any(|line| line == entry) {
+        return;
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(list) {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
+
+/// Append a use of a registry template to the usage log, if the builder
+/// enabled one through `TOMPLATE_USAGE_LOG`
+///
+/// Each line holds the template name, crate, file and line of the macro call,
+/// separated by tabs. Failing to write the log never fails the expansion.
+pub fn record_usage(name: &str) {
+    let Some(path) = crate::settings::var_os("TOMPLATE_USAGE_LOG") else {
+        return;
+    };
+    // Call sites only exist inside a macro expansion
+    if !proc_macro::is_available() {
+        return;
+    }
+    
+    let span = proc_macro::Span::call_site();
+    let entry = format!(
+        "{}\t{}\t{}\t{}\n",
+        name,
+        std::env::var("CARGO_PKG_NAME").unwrap_or_default(),
+        span.file(),
+        span.line()
+    );
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = file.write_all(entry.as_bytes());
+    }
+}
@@ -0,0 +1,919 @@
+//! Type definitions for the Tomplate build system.
+//!
+//! This module contains the core types used throughout the build system,
+//! including template definitions, error handling, and engine specifications.
+//! They live here so the engines can read templates and `tomplate-build` can
+//! render with the engines; `tomplate-build` re-exports them.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A template definition from a `.tomplate.toml` file.
+///
+/// Templates are defined in TOML format with a required `template` field
+/// and optional `engine` and metadata fields.
+///
+/// # Examples
+///
+/// A typical template definition in TOML:
+///
+/// ```toml
+/// [my_template]
+/// template = "Hello {name}, welcome to {place}!"
+/// engine = "simple"  # Optional
+///
+/// [service]
+/// template = """
+/// service:
+///   {body}
+/// """
+/// indent = true  # Optional, aligns multi-line values
+///
+/// [report]
+/// template = """
+///     SELECT *
+///     FROM reports
+/// """
+/// dedent = true  # Optional, strips the common indentation
+/// trim = true    # Optional, strips surrounding whitespace
+///
+/// [complex_template]
+/// template = """
+/// {{#if logged_in}}
+///   Welcome back, {{username}}!
+/// {{else}}
+///   Please log in.
+/// {{/if}}
+/// """
+/// engine = "handlebars"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Template {
+    /// The template string containing the template pattern.
+    ///
+    /// This can use various syntaxes depending on the engine:
+    /// - Simple: `{variable}` placeholders
+    /// - Handlebars: `{{variable}}` with full Handlebars features
+    /// - Tera: `{{ variable }}` with Tera/Jinja2 syntax
+    /// - MiniJinja: Similar to Tera with Jinja2 syntax
+    pub template: String,
+    
+    /// Optional template engine to use.
+    ///
+    /// If not specified, defaults to "simple" or the builder's default engine.
+    /// Valid values: "simple", "handlebars", "tera", "minijinja", "fluent",
+    /// or the name of an engine registered with `Builder::custom_engine`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+    
+    /// Parameters the template expects, in order.
+    ///
+    /// Declaring them lets `tomplate_fn!` generate a function that takes
+    /// each one as a runtime `&str` argument, e.g.
+    /// `params = ["fields", "condition"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<String>,
+    
+    /// Tags for finding the template, e.g. `tags = ["sql", "users"]`.
+    ///
+    /// Used by `tomplate list --tag` to filter large registries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    
+    /// Re-indent multi-line values to the column of their placeholder.
+    ///
+    /// Keeps nested YAML or SQL aligned when a substituted value spans
+    /// several lines. Supported by the simple engine.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub indent: bool,
+    
+    /// Trim leading and trailing whitespace from the rendered output.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub trim: bool,
+    
+    /// Remove the indentation common to all non-blank lines of the template
+    /// before rendering.
+    ///
+    /// Lets multi-line TOML strings be indented to match the surrounding file
+    /// without carrying that indentation into the rendered constant.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dedent: bool,
+    
+    /// Keep placeholders without a matching parameter instead of failing.
+    ///
+    /// The result is itself a template that can be rendered again with the
+    /// remaining parameters. Supported by the simple engine.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub partial: bool,
+    
+    /// Fail on undefined variables instead of rendering them as empty.
+    ///
+    /// The simple and Tera engines are always strict; this option enables
+    /// the same behavior for Handlebars and MiniJinja. When unset, the
+    /// builder's `strict` setting applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    
+    /// Remove the first newline after a block tag in Tera and MiniJinja
+    /// templates, like Jinja's `trim_blocks`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_blocks: Option<bool>,
+    
+    /// Strip whitespace before a block tag at the start of a line in Tera
+    /// and MiniJinja templates, like Jinja's `lstrip_blocks`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lstrip_blocks: Option<bool>,
+    
+    /// Keep the template's trailing newline in Tera and MiniJinja output.
+    ///
+    /// When unset, MiniJinja strips a single trailing newline and Tera keeps
+    /// it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_trailing_newline: Option<bool>,
+    
+    /// Custom delimiters for MiniJinja templates, e.g. to avoid clashes
+    /// with `{{` in the generated text.
+    ///
+    /// ```toml
+    /// [templates.my_template.delimiters]
+    /// variable_start = "${"
+    /// variable_end = "}"
+    /// ```
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delimiters: Option<Delimiters>,
+    
+    /// How substituted values are escaped, `"none"` when unset.
+    ///
+    /// Set `escape = "html"` for templates that generate HTML, or
+    /// `escape = "shell"` for command lines and systemd unit fragments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escape: Option<Escape>,
+    
+    /// Locale used by locale-aware engines such as Fluent, e.g. `"de-DE"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    
+    /// Bind placeholder style the rendered SQL is rewritten to, so one
+    /// template serves several databases.
+    ///
+    /// Calls can override it with `#[placeholders = "mysql"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholders: Option<Placeholders>,
+    
+    /// A step applied to the rendered output before it is validated, e.g.
+    /// `"markdown"` to embed Markdown help texts as HTML.
+    ///
+    /// Calls can override it with `#[postprocess = "markdown"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postprocess: Option<Postprocess>,
+    
+    /// Checks the rendered output must pass, failing compilation otherwise.
+    ///
+    /// Either a [`Format`] the output must be valid in, or a table of
+    /// checks:
+    ///
+    /// ```toml
+    /// [list_users]
+    /// template = "SELECT * FROM users"
+    /// validate = "sql"
+    ///
+    /// [select_user.validate]
+    /// regex = "^SELECT "
+    /// format = "sql"
+    /// dialect = "postgres"
+    /// ```
+    #[serde(
+        default,
+        deserialize_with = "deserialize_validate",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub validate: Option<Validate>,
+    
+    /// Marks the template as an HTTP route, included in the OpenAPI paths
+    /// written by `Builder::openapi_paths`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<Route>,
+    
+    /// Variants of the template for Cargo build profiles.
+    ///
+    /// The keys of the variant for the profile being built replace the
+    /// template's own when the registry is amalgamated, so a debug build can
+    /// render more verbose output than a release build:
+    ///
+    /// ```toml
+    /// [log_line]
+    /// template = "{level}: {message}"
+    ///
+    /// [log_line.profiles.debug]
+    /// template = "{level} [{file}:{line}]: {message}"
+    /// ```
+    ///
+    /// The profile is Cargo's `PROFILE`, `debug` or `release`, or when that
+    /// is unset, `debug` or `release` depending on `DEBUG`. Without either,
+    /// as outside build scripts, no variant applies.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, toml::Table>,
+    
+    /// The registry file that defines the template, filled in when the
+    /// registry is amalgamated.
+    ///
+    /// The registry records it relative to its own directory, so it holds no
+    /// path of the machine it was built on, and resolves it when read.
+    ///
+    /// `file("...")` parameters in calls to the template are read relative
+    /// to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_file: Option<PathBuf>,
+    
+    /// Additional metadata for the template.
+    ///
+    /// This can include custom fields for documentation, validation schemas,
+    /// or any other template-specific information. These fields are preserved
+    /// but not used by the core template system.
+    #[serde(flatten)]
+    pub metadata: HashMap<String, toml::Value>,
+}
+
+/// An HTTP route described by a template.
+///
+/// The template is the route's path. Its `{name}` placeholders become path
+/// parameters, and placeholders after a `?` become query parameters.
+///
+/// ```toml
+/// [user_posts]
+/// template = "/users/{id}/posts?page={page}"
+///
+/// [user_posts.route]
+/// method = "get"
+/// summary = "List a user's posts"
+/// tags = ["posts"]
+/// responses = { "200" = { description = "The posts" } }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Route {
+    /// HTTP method in lowercase, `"get"` when unset.
+    #[serde(default = "default_method")]
+    pub method: String,
+    
+    /// Short summary of the operation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    
+    /// Tags grouping the operation in documentation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    
+    /// Further fields of the OpenAPI operation object, such as `responses`
+    /// or `description`, copied as they are.
+    #[serde(flatten)]
+    pub operation: BTreeMap<String, toml::Value>,
+}
+
+fn default_method() -> String {
+    "get".to_string()
+}
+
+/// Checks applied to the rendered output of a template.
+///
+/// Every output of the template, including when it is referenced from
+/// another template, must pass the checks or compilation fails.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Validate {
+    /// A regular expression the output must match, e.g. `"^SELECT "`.
+    ///
+    /// Matches anywhere in the output unless anchored with `^` or `$`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    
+    /// A format the output must be valid in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Format>,
+    
+    /// The SQL dialect for [`Format::Sql`], e.g. `"postgres"`, `"mysql"` or
+    /// `"sqlite"`. Generic SQL when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<String>,
+    
+    /// The maximum length of the output in bytes, e.g. for fixed-size
+    /// protocol fields and database columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_len: Option<usize>,
+    
+    /// Reject output that is empty or only whitespace.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub non_empty: bool,
+    
+    /// Check that SQL bind placeholders are consistent: `$1` to `$n`
+    /// without gaps, and not mixed with `?` or `:name`.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub binds: bool,
+}
+
+/// Accept `validate = "<format>"` as shorthand for a table with only a format
+fn deserialize_validate<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Validate>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Format(Format),
+        Table(Validate),
+    }
+    
+    Ok(Some(match Repr::deserialize(deserializer)? {
+        Repr::Format(format) => Validate { format: Some(format), ..Default::default() },
+        Repr::Table(validate) => validate,
+    }))
+}
+
+/// A format rendered output can be validated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// SQL statements, parsed with `sqlparser`. Requires the `sqlparser`
+    /// feature of `tomplate`.
+    Sql,
+    
+    /// A JSON document.
+    Json,
+    
+    /// One or more YAML documents, such as Kubernetes manifests. Requires
+    /// the `yaml` feature of `tomplate`.
+    Yaml,
+    
+    /// A TOML document.
+    Toml,
+    
+    /// An HTML fragment with balanced tags and quoted attribute values.
+    /// Void elements such as `<br>` need no closing tag.
+    Html,
+    
+    /// An XML fragment with balanced tags and quoted attribute values.
+    Xml,
+    
+    /// A GraphQL executable document, validated against the schema set
+    /// with `Builder::graphql_schema` or
+    /// only parsed without one. Requires the `graphql` feature of `tomplate`.
+    Graphql,
+    
+    /// A WGSL shader module, parsed and validated with naga. Requires the
+    /// `naga` feature of `tomplate`.
+    Wgsl,
+}
+
+impl Format {
+    /// Returns the format's name as used in TOML.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Sql => "sql",
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::Html => "html",
+            Format::Xml => "xml",
+            Format::Graphql => "graphql",
+            Format::Wgsl => "wgsl",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Custom delimiters for Jinja-style templates.
+///
+/// Unset delimiters keep their defaults: `{% %}` for blocks, `{{ }}` for
+/// variables and `{# #}` for comments. Honored by the MiniJinja engine.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Delimiters {
+    /// Start of a block tag, `{%` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_start: Option<String>,
+    
+    /// End of a block tag, `%}` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_end: Option<String>,
+    
+    /// Start of a variable expression, `{{` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_start: Option<String>,
+    
+    /// End of a variable expression, `}}` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_end: Option<String>,
+    
+    /// Start of a comment, `{#` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_start: Option<String>,
+    
+    /// End of a comment, `#}` by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_end: Option<String>,
+}
+
+/// Settings applied to every template of an engine.
+///
+/// Registered with `Builder::engine_config`,
+/// these are written into the amalgamated registry for templates that don't
+/// set them themselves. Unset fields keep the engine's defaults.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate_build::{Builder, Engine, EngineConfig};
+///
+/// Builder::new()
+///     .add_pattern("**/*.tomplate.toml")
+///     .engine_config(Engine::MiniJinja, EngineConfig {
+///         strict: Some(true),
+///         trim_blocks: Some(true),
+///         lstrip_blocks: Some(true),
+///         ..Default::default()
+///     })
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EngineConfig {
+    /// Fail on undefined variables, see [`Template::strict`].
+    pub strict: Option<bool>,
+    
+    /// Jinja-style `trim_blocks`, see [`Template::trim_blocks`].
+    pub trim_blocks: Option<bool>,
+    
+    /// Jinja-style `lstrip_blocks`, see [`Template::lstrip_blocks`].
+    pub lstrip_blocks: Option<bool>,
+    
+    /// Keep trailing newlines, see [`Template::keep_trailing_newline`].
+    pub keep_trailing_newline: Option<bool>,
+    
+    /// Custom delimiters, see [`Template::delimiters`].
+    pub delimiters: Option<Delimiters>,
+    
+    /// Escaping of substituted values, see [`Template::escape`].
+    pub escape: Option<Escape>,
+}
+
+/// Escaping applied to values substituted into a template.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Escape {
+    /// Insert values verbatim, as for SQL and other non-HTML output.
+    #[default]
+    None,
+    
+    /// Escape `&`, `<`, `>`, `"` and `'` for HTML output.
+    Html,
+    
+    /// Quote values as single POSIX shell words.
+    Shell,
+}
+
+impl Escape {
+    /// Returns the string representation used in TOML files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Escape::None => "none",
+            Escape::Html => "html",
+            Escape::Shell => "shell",
+        }
+    }
+}
+
+impl fmt::Display for Escape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Escape {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Escape::None),
+            "html" => Ok(Escape::Html),
+            "shell" => Ok(Escape::Shell),
+            _ => Err(Error::InvalidTemplate(format!("Unknown escape mode: {}", s))),
+        }
+    }
+}
+
+/// Bind placeholder style of rendered SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placeholders {
+    /// Numbered placeholders: `$1`, `$2`, ...
+    Postgres,
+    
+    /// Positional placeholders: `?`.
+    Mysql,
+    
+    /// Named placeholders: `:name`. Numbered and positional placeholders
+    /// become `:p1`, `:p2`, ...
+    Named,
+}
+
+impl Placeholders {
+    /// Returns the string representation used in TOML files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Placeholders::Postgres => "postgres",
+            Placeholders::Mysql => "mysql",
+            Placeholders::Named => "named",
+        }
+    }
+}
+
+impl fmt::Display for Placeholders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Placeholders {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "postgres" => Ok(Placeholders::Postgres),
+            "mysql" => Ok(Placeholders::Mysql),
+            "named" => Ok(Placeholders::Named),
+            _ => Err(Error::InvalidTemplate(format!("Unknown placeholder style: {}", s))),
+        }
+    }
+}
+
+/// A step applied to rendered output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Postprocess {
+    /// Convert Markdown to HTML with pulldown-cmark, with tables,
+    /// strikethrough, footnotes and task lists. Requires the `markdown`
+    /// feature of `tomplate`.
+    Markdown,
+    
+    /// Remove comments and collapse whitespace in HTML, minifying inline
+    /// `<style>` and `<script>` contents too. `<pre>` and `<textarea>`
+    /// contents are kept.
+    HtmlMinify,
+    
+    /// Remove comments and whitespace from CSS.
+    CssMinify,
+    
+    /// Remove comments and whitespace from JavaScript, keeping the line
+    /// breaks that automatic semicolon insertion may depend on.
+    JsMinify,
+}
+
+impl Postprocess {
+    /// Returns the step's name as used in TOML.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Postprocess::Markdown => "markdown",
+            Postprocess::HtmlMinify => "html-minify",
+            Postprocess::CssMinify => "css-minify",
+            Postprocess::JsMinify => "js-minify",
+        }
+    }
+}
+
+impl fmt::Display for Postprocess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Postprocess {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" => Ok(Postprocess::Markdown),
+            "html-minify" => Ok(Postprocess::HtmlMinify),
+            "css-minify" => Ok(Postprocess::CssMinify),
+            "js-minify" => Ok(Postprocess::JsMinify),
+            _ => Err(Error::InvalidTemplate(format!("Unknown post-processing step: {}", s))),
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// The location of a template within the amalgamated registry.
+///
+/// The builder writes an index of these next to the registry, so the macros
+/// can parse only the templates a crate uses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexEntry {
+    /// Byte offset of the template's table in the registry.
+    pub start: usize,
+    
+    /// Byte offset just past the template's table.
+    pub end: usize,
+    
+    /// The template's engine, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine: Option<String>,
+}
+
+/// Error types for Tomplate build operations.
+///
+/// This enum represents all possible errors that can occur during
+/// template discovery, parsing, and amalgamation.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// I/O error occurred during file operations.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    
+    /// Failed to parse TOML template file.
+    #[error("TOML parsing error: {0}")]
+    TomlParse(#[from] toml::de::Error),
+    
+    /// Failed to serialize templates to TOML.
+    #[error("TOML serialization error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+    
+    /// Failed to serialize build settings to JSON.
+    #[error("JSON serialization error: {0}")]
+    JsonSerialize(#[from] serde_json::Error),
+    
+    /// Invalid glob pattern provided.
+    #[error("Glob pattern error: {0}")]
+    Glob(#[from] glob::PatternError),
+    
+    /// Referenced template file does not exist.
+    #[error("Template file not found: {0}")]
+    FileNotFound(PathBuf),
+    
+    /// Found duplicate template names across files.
+    ///
+    /// Each template name must be unique across all discovered files.
+    #[error("Duplicate template name: {0}")]
+    DuplicateTemplate(String),
+    
+    /// Template definition is invalid or malformed.
+    #[error("Invalid template definition: {0}")]
+    InvalidTemplate(String),
+    
+    /// Referenced template not found in registry.
+    #[error("Template not found: {0}")]
+    TemplateNotFound(String),
+    
+    /// Template engine error during processing.
+    #[error("Template engine error: {0}")]
+    EngineError(String),
+    
+    /// Invalid parameter provided to template.
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+}
+
+/// Result type alias for Tomplate build operations.
+///
+/// A convenience type alias for `Result<T, tomplate_build::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Supported template engines for processing templates.
+///
+/// Each engine provides different features and syntax:
+///
+/// - **Simple**: Basic `{variable}` substitution
+/// - **Handlebars**: Full Handlebars templating with helpers and conditionals
+/// - **Tera**: Jinja2-like templating with filters and inheritance
+/// - **MiniJinja**: Lightweight Jinja2 implementation
+/// - **Fluent**: Localized messages with plural and select rules
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate_build::Engine;
+///
+/// // Use in Builder
+/// Builder::new()
+///     .default_engine(Engine::Handlebars)
+///     .build()?;
+/// ```
+///
+/// # Feature Flags
+///
+/// Some engines require feature flags to be enabled:
+/// - `handlebars`: Enables the Handlebars engine
+/// - `tera`: Enables the Tera engine
+/// - `minijinja`: Enables the MiniJinja engine
+/// - `fluent`: Enables the Fluent engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    /// Simple variable substitution engine.
+    ///
+    /// Uses `{variable}` syntax for placeholders.
+    /// This is the default engine and requires no additional features.
+    ///
+    /// `{@other_template}` renders another registry template with the same
+    /// parameters, so composition can be written entirely in TOML.
+    ///
+    /// Values can be piped through built-in filters: `upper`, `lower`,
+    /// `trim`, `snake`, `kebab`, `camel`, `pascal`, `screaming`,
+    /// `join:<separator>`, `repeat:<item>` and `default:<value>`, which
+    /// replaces empty values and parameters that aren't passed.
+    ///
+    /// The quoting filters `sql_str`, `sql_ident` and `sql_in` quote SQL
+    /// string literals, identifiers and `IN` lists, `json` escapes a value
+    /// for use inside a JSON string and `shell` quotes it as POSIX shell
+    /// words. They are also available to the Handlebars, Tera and MiniJinja
+    /// engines.
+    ///
+    /// Those engines also pick plural forms by the CLDR rules of the
+    /// template's `locale` (default `en-US`): `{{plural count one="item"
+    /// other="items"}}` in Handlebars, `{{ count | plural(one="item",
+    /// other="items") }}` in Tera and MiniJinja. Forms are named after the
+    /// categories `zero`, `one`, `two`, `few`, `many` and `other`, which is
+    /// used for any category without a form, and `plural_category` returns
+    /// the category itself.
+    ///
+    /// Numbers and dates are formatted for the locale too: `format_number`
+    /// groups digits, `currency` formats an amount, e.g. `{{ price |
+    /// currency("EUR") }}`, and `format_date` writes an ISO 8601 date in the
+    /// locale's long form, e.g. `{{ released | format_date("de") }}`. Tera
+    /// takes the arguments by name, e.g. `currency(code="EUR")`.
+    ///
+    /// List parameters are joined with `, `, or with the separator given
+    /// after `*`, e.g. `{cols*" AND "}`.
+    ///
+    /// # Example Template
+    /// ```text
+    /// Hello {name}, you have {count} messages.
+    /// SELECT {cols|join:, } FROM {table|snake}
+    /// SELECT * FROM users WHERE id IN ({ids|repeat:?})
+    /// ```
+    Simple,
+    
+    /// Handlebars template engine.
+    ///
+    /// Provides full Handlebars functionality including:
+    /// - Conditionals: `{{#if}}`, `{{#unless}}`
+    /// - Loops: `{{#each}}`
+    /// - Helpers and partials
+    /// - HTML escaping (disabled by default in Tomplate)
+    ///
+    /// Requires the `handlebars` feature.
+    ///
+    /// # Example Template
+    /// ```text
+    /// {{#if logged_in}}
+    ///   Welcome {{username}}!
+    /// {{else}}
+    ///   Please log in.
+    /// {{/if}}
+    /// ```
+    #[cfg(feature = "handlebars")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "handlebars")))]
+    Handlebars,
+    
+    /// Tera template engine.
+    ///
+    /// Provides Jinja2-like syntax with:
+    /// - Variables: `{{ variable }}`
+    /// - Filters: `{{ value | upper }}`
+    /// - Control structures: `{% if %}`, `{% for %}`
+    /// - Template inheritance, with other Tera templates in the registry
+    ///   available by name to `{% extends %}`, `{% include %}` and
+    ///   `{% import %}`
+    ///
+    /// Requires the `tera` feature.
+    ///
+    /// # Example Template
+    /// ```text
+    /// {% for user in users %}
+    ///   {{ user.name | upper }}
+    /// {% endfor %}
+    /// ```
+    #[cfg(feature = "tera")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tera")))]
+    Tera,
+    
+    /// MiniJinja template engine.
+    ///
+    /// A lightweight Jinja2 implementation with:
+    /// - Similar syntax to Tera
+    /// - Good performance
+    /// - Smaller dependency footprint
+    /// - Other MiniJinja templates in the registry available by name to
+    ///   `{% include %}`, `{% import %}` and `{% extends %}`
+    ///
+    /// Requires the `minijinja` feature.
+    ///
+    /// # Example Template
+    /// ```text
+    /// {% if items %}
+    ///   {% for item in items %}
+    ///     - {{ item }}
+    ///   {% endfor %}
+    /// {% endif %}
+    /// ```
+    #[cfg(feature = "minijinja")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "minijinja")))]
+    MiniJinja,
+    
+    /// Fluent localization engine.
+    ///
+    /// Renders the first message of an `.ftl` resource, with plural and
+    /// select rules evaluated for the template's `locale` (default `en-US`).
+    /// Other messages and terms in the resource can be referenced from it.
+    ///
+    /// Requires the `fluent` feature.
+    ///
+    /// # Example Template
+    /// ```text
+    /// unread = { $count ->
+    ///     [one] You have one unread message.
+    ///    *[other] You have { $count } unread messages.
+    /// }
+    /// ```
+    #[cfg(feature = "fluent")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fluent")))]
+    Fluent,
+}
+
+impl Engine {
+    /// Returns the string representation of the engine.
+    ///
+    /// This is the value used in TOML files for the `engine` field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// assert_eq!(Engine::Simple.as_str(), "simple");
+    /// assert_eq!(Engine::Handlebars.as_str(), "handlebars");
+    /// ```
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Engine::Simple => "simple",
+            #[cfg(feature = "handlebars")]
+            Engine::Handlebars => "handlebars",
+            #[cfg(feature = "tera")]
+            Engine::Tera => "tera",
+            #[cfg(feature = "minijinja")]
+            Engine::MiniJinja => "minijinja",
+            #[cfg(feature = "fluent")]
+            Engine::Fluent => "fluent",
+        }
+    }
+}
+
+impl Default for Engine {
+    /// Returns the default engine (Simple).
+    fn default() -> Self {
+        Engine::Simple
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Engine {
+    type Err = Error;
+    
+    /// Parses an engine name from a string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::str::FromStr;
+    /// use tomplate_build::Engine;
+    ///
+    /// let engine = Engine::from_str("handlebars")?;
+    /// assert_eq!(engine, Engine::Handlebars);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the engine name is unknown or the required
+    /// feature is not enabled.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "simple" | "" => Ok(Engine::Simple),
+            #[cfg(feature = "handlebars")]
+            "handlebars" => Ok(Engine::Handlebars),
+            #[cfg(feature = "tera")]
+            "tera" => Ok(Engine::Tera),
+            #[cfg(feature = "minijinja")]
+            "minijinja" => Ok(Engine::MiniJinja),
+            #[cfg(feature = "fluent")]
+            "fluent" => Ok(Engine::Fluent),
+            _ => Err(Error::EngineError(format!("Unknown or disabled template engine: {}", s))),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,659 @@
+//! Checks of rendered output declared with a template's `validate` table.
+
+use crate::placeholders::{self, Placeholder};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use crate::types::{Format, Template, Validate};
+
+/// Check the rendered output of a registry template against its `validate`
+/// settings, given the parameters it was rendered with
+pub fn check(
+    name: &str,
+    template: &Template,
+    output: &str,
+    params: &HashMap<String, Value>,
+) -> Result<(), String> {
+    let Some(validate) = &template.validate else {
+        return Ok(());
+    };
+    
+    if let Some(pattern) = &validate.regex {
+        let regex = Regex::new(pattern)
+            .map_err(|e| format!("Invalid validation regex for template '{}': {}", name, e))?;
+        if !regex.is_match(output) {
+            return Err(format!(
+                "Output of template '{}' does not match /{}/: {:?}",
+                name, pattern, output
+            ));
+        }
+    }
+    
+    if validate.non_empty && output.trim().is_empty() {
+        return Err(format!("Output of template '{}' is empty", name));
+    }
+    
+    if let Some(max_len) = validate.max_len
+        && output.len() > max_len
+    {
+        return Err(format!(
+            "Output of template '{}' is {} bytes long, over the maximum of {}",
+            name,
+            output.len(),
+            max_len
+        ));
+    }
+    
+    if validate.binds {
+        bind_count(output).map_err(|e| {
+            format!("Output of template '{}' has inconsistent bind placeholders: {}", name, e)
+        })?;
+    }
+    
+    if let Some(format) = validate.format {
+        check_format(format, validate, output, params)
+            .map_err(|e| format!("Output of template '{}' is not valid {}: {}", name, format, e))?;
+    }
+    
+    Ok(())
+}
+
+/// Count the bind placeholders in SQL, checking that `$n` placeholders run
+/// from `$1` without gaps and that styles aren't mixed
+///
+/// `:name` placeholders count once per distinct name.
+pub fn bind_count(sql: &str) -> Result<usize, String> {
+    let mut numbered = BTreeSet::new();
+    let mut positional = 0;
+    let mut named = BTreeSet::new();
+    
+    for (_, placeholder) in placeholders::find(sql)? {
+        match placeholder {
+            Placeholder::Numbered(n) => {
+                numbered.insert(n);
+            }
+            Placeholder::Positional => positional += 1,
+            Placeholder::Named(name) => {
+                named.insert(name);
+            }
+        }
+    }
+    
+    let styles = [!numbered.is_empty(), positional > 0, !named.is_empty()];
+    if styles.iter().filter(|used| **used).count() > 1 {
+        return Err("mixes $n, ? and :name placeholders".to_string());
+    }
+    
+    match numbered.last() {
+        Some(&max) => {
+            let missing: Vec<String> = (1..=max)
+                .filter(|n| !numbered.contains(n))
+                .map(|n| format!("${}", n))
+                .collect();
+            if missing.is_empty() {
+                Ok(max)
+            } else {
+                Err(format!("${} is used but {} is not", max, missing.join(", ")))
+            }
+        }
+        None => Ok(positional + named.len()),
+    }
+}
+
+fn check_format(
+    format: Format,
+    validate: &Validate,
+    output: &str,
+    params: &HashMap<String, Value>,
+) -> Result<(), String> {
+    match format {
+        Format::Sql => check_sql(output, validate.dialect.as_deref()),
+        Format::Json => serde_json::from_str::<serde::de::IgnoredAny>(output)
+            .map(|_| ())
+            .map_err(|e| located(e.to_string(), output, e.line(), e.column(), params)),
+        Format::Yaml => check_yaml(output, params),
+        Format::Graphql => check_graphql(output),
+        Format::Wgsl => check_wgsl(output, params),
+        Format::Html | Format::Xml => check_markup(output, format == Format::Html)
+            .map_err(|(message, offset)| at_offset(message, output, offset, params)),
+        Format::Toml => toml::from_str::<toml::Table>(output).map(|_| ()).map_err(|e| {
+            let message = e.message().to_string();
+            match e.span() {
+                Some(span) => at_offset(message, output, span.start, params),
+                None => message,
+            }
+        }),
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn check_yaml(output: &str, params: &HashMap<String, Value>) -> Result<(), String> {
+    use serde::Deserialize;
+    
+    for document in serde_yaml::Deserializer::from_str(output) {
+        serde::de::IgnoredAny::deserialize(document).map_err(|e| match e.location() {
+            Some(location) => at_offset(e.to_string(), output, location.index(), params),
+            None => e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "yaml"))]
+fn check_yaml(_output: &str, _params: &HashMap<String, Value>) -> Result<(), String> {
+    Err("YAML validation requires the 'yaml' feature".to_string())
+}
+
+#[cfg(feature = "graphql")]
+type GraphqlSchema = std::sync::Arc<Result<apollo_compiler::validation::Valid<apollo_compiler::Schema>, String>>;
+
+// The last schema loaded, with the path and text it was parsed from
+#[cfg(feature = "graphql")]
+static GRAPHQL_SCHEMA: std::sync::Mutex<Option<(String, String, GraphqlSchema)>> = std::sync::Mutex::new(None);
+
+/// The schema GraphQL output is validated against, if the builder set one,
+/// parsed again only when the setting or the file changes
+#[cfg(feature = "graphql")]
+fn graphql_schema() -> Option<GraphqlSchema> {
+    let path = crate::settings::var("TOMPLATE_GRAPHQL_SCHEMA")?;
+    let sdl = match std::fs::read_to_string(&path) {
+        Ok(sdl) => sdl,
+        Err(e) => return Some(std::sync::Arc::new(Err(format!("Failed to read GraphQL schema {}: {}", path, e)))),
+    };
+    
+    let mut loaded = GRAPHQL_SCHEMA.lock().unwrap();
+    if let Some((loaded_path, loaded_sdl, schema)) = &*loaded
+        && *loaded_path == path
+        && *loaded_sdl == sdl
+    {
+        return Some(schema.clone());
+    }
+    let schema = std::sync::Arc::new(
+        apollo_compiler::Schema::parse_and_validate(sdl.as_str(), &path)
+            .map_err(|e| format!("Invalid GraphQL schema {}: {}", path, e.errors)),
+    );
+    *loaded = Some((path, sdl, schema.clone()));
+    Some(schema)
+}
+
+#[cfg(feature = "graphql")]
+fn check_graphql(output: &str) -> Result<(), String> {
+    match graphql_schema() {
+        Some(schema) => {
+            let schema = schema.as_ref().as_ref().map_err(Clone::clone)?;
+            apollo_compiler::ExecutableDocument::parse_and_validate(schema, output, "template.graphql")
+                .map(|_| ())
+                .map_err(|e| e.errors.to_string())
+        }
+        None => apollo_compiler::ast::Document::parse(output, "template.graphql")
+            .map(|_| ())
+            .map_err(|e| e.errors.to_string()),
+    }
+}
+
+#[cfg(not(feature = "graphql"))]
+fn check_graphql(_output: &str) -> Result<(), String> {
+    Err("GraphQL validation requires the 'graphql' feature".to_string())
+}
+
+#[cfg(feature = "naga")]
+fn check_wgsl(output: &str, params: &HashMap<String, Value>) -> Result<(), String> {
+    let module = naga::front::wgsl::parse_str(output).map_err(|e| match e.location(output) {
+        Some(location) => at_offset(e.message().to_string(), output, location.offset as usize, params),
+        None => e.emit_to_string(output),
+    })?;
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map(|_| ())
+        .map_err(|e| e.emit_to_string(output))
+}
+
+#[cfg(not(feature = "naga"))]
+fn check_wgsl(_output: &str, _params: &HashMap<String, Value>) -> Result<(), String> {
+    Err("WGSL validation requires the 'naga' feature".to_string())
+}
+
+/// HTML elements that never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Check that tags are balanced and attribute values quoted, returning an
+/// error message and its byte offset otherwise
+fn check_markup(output: &str, html: bool) -> Result<(), (String, usize)> {
+    let mut open: Vec<(String, usize)> = Vec::new();
+    let mut pos = 0;
+    
+    while let Some(found) = output[pos..].find('<') {
+        let start = pos + found;
+        let tail = &output[start..];
+        
+        // Comments, CDATA sections, doctypes and processing instructions
+        let skipped = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<!", ">"), ("<?", ">")]
+            .iter()
+            .find(|(open, _)| tail.starts_with(open));
+        if let Some((open, close)) = skipped {
+            let end = tail[open.len()..]
+                .find(close)
+                .ok_or_else(|| (format!("Unclosed '{}'", open), start))?;
+            pos = start + open.len() + end + close.len();
+            continue;
+        }
+        
+        let closing = tail.starts_with("</");
+        let name_start = start + if closing { 2 } else { 1 };
+        let name_len = output[name_start..]
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')))
+            .unwrap_or(output.len() - name_start);
+        if name_len == 0 {
+            return Err(("Expected a tag name after '<', or an escaped '&lt;'".to_string(), start));
+        }
+        let name = &output[name_start..name_start + name_len];
+        let name = if html { name.to_ascii_lowercase() } else { name.to_string() };
+        
+        // Attributes up to the end of the tag
+        let mut cursor = name_start + name_len;
+        let self_closing = loop {
+            cursor += output[cursor..].len() - output[cursor..].trim_start().len();
+            let rest = &output[cursor..];
+            if rest.is_empty() {
+                return Err((format!("Unclosed tag <{}>", name), start));
+            } else if rest.starts_with('>') {
+                cursor += 1;
+                break false;
+            } else if rest.starts_with("/>") && !closing {
+                cursor += 2;
+                break true;
+            } else if closing {
+                return Err((format!("Unexpected content in </{}>", name), cursor));
+            }
+            
+            let attr_len = rest
+                .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/' | '"' | '\''))
+                .unwrap_or(rest.len());
+            if attr_len == 0 {
+                return Err((format!("Unexpected character in <{}>", name), cursor));
+            }
+            let attr = &rest[..attr_len];
+            cursor += attr_len;
+            
+            let after = output[cursor..].trim_start();
+            if let Some(value) = after.strip_prefix('=') {
+                let value_start = output.len() - value.trim_start().len();
+                let quote = output[value_start..].chars().next();
+                match quote {
+                    Some(quote @ ('"' | '\'')) => {
+                        let len = output[value_start + 1..]
+                            .find(quote)
+                            .ok_or_else(|| (format!("Unclosed value for attribute '{}'", attr), value_start))?;
+                        cursor = value_start + len + 2;
+                    }
+                    _ => return Err((format!("Unquoted value for attribute '{}'", attr), value_start)),
+                }
+            } else if !html {
+                return Err((format!("Attribute '{}' has no value", attr), cursor - attr_len));
+            }
+        };
+        pos = cursor;
+        
+        if closing {
+            match open.pop() {
+                Some((open_name, _)) if open_name == name => {}
+                Some((open_name, _)) => {
+                    return Err((format!("</{}> closes <{}>", name, open_name), start));
+                }
+                None => return Err((format!("</{}> has no opening tag", name), start)),
+            }
+        } else if !self_closing && (!html || !VOID_ELEMENTS.contains(&name.as_str())) {
+            // Script and style contents are raw text, not markup
+            if html && (name == "script" || name == "style") {
+                let end = output[pos..]
+                    .to_ascii_lowercase()
+                    .find(&format!("</{}", name))
+                    .ok_or_else(|| (format!("Unclosed <{}>", name), start))?;
+                pos += end;
+            }
+            open.push((name, start));
+        }
+    }
+    
+    match open.pop() {
+        Some((name, start)) => Err((format!("Unclosed <{}>", name), start)),
+        None => Ok(()),
+    }
+}
+
+/// Add the byte offset of an error at a 1-based line and column, and the
+/// parameter whose value is nearest to it
+fn located(message: String, output: &str, line: usize, column: usize, params: &HashMap<String, Value>) -> String {
+    let offset = output
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>()
+        + column.saturating_sub(1);
+    at_offset(message, output, offset, params)
+}
+
+/// Add a byte offset to an error, and the parameter whose value is nearest
+/// to it
+fn at_offset(message: String, output: &str, offset: usize, params: &HashMap<String, Value>) -> String {
+    let offset = offset.min(output.len());
+    match nearest_param(output, offset, params) {
+        Some(param) => format!("{} (byte {}, near the value of '{}')", message, offset, param),
+        None => format!("{} (byte {})", message, offset),
+    }
+}
+
+/// The parameter whose substituted value is closest to a byte offset
+fn nearest_param<'a>(output: &str, offset: usize, params: &'a HashMap<String, Value>) -> Option<&'a str> {
+    params
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            if value.is_empty() {
+                return None;
+            }
+            output
+                .match_indices(value.as_str())
+                .map(|(start, matched)| {
+                    let end = start + matched.len();
+                    if offset < start {
+                        start - offset
+                    } else {
+                        offset.saturating_sub(end)
+                    }
+                })
+                .min()
+                .map(|distance| (distance, name.as_str()))
+        })
+        .min()
+        .map(|(_, name)| name)
+}
+
+#[cfg(feature = "sqlparser")]
+fn check_sql(output: &str, dialect: Option<&str>) -> Result<(), String> {
+    use sqlparser::dialect::{dialect_from_str, GenericDialect};
+    use sqlparser::parser::Parser;
+    
+    let dialect = match dialect {
+        Some(name) => dialect_from_str(name).ok_or_else(|| format!("Unknown SQL dialect '{}'", name))?,
+        None => Box::new(GenericDialect {}),
+    };
+    Parser::parse_sql(dialect.as_ref(), output)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "sqlparser"))]
+fn check_sql(_output: &str, _dialect: Option<&str>) -> Result<(), String> {
+    Err("SQL validation requires the 'sqlparser' feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// A template with the given `validate` table, as written in TOML
+    fn template(validate: &str) -> Template {
+        toml::from_str(&format!("template = \"\"\n[validate]\n{}", validate)).unwrap()
+    }
+    
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), Value::from(*value)))
+            .collect()
+    }
+    
+    #[test]
+    fn test_regex() {
+        let template = template(r#"regex = "^SELECT ""#);
+        assert!(check("query", &template, "SELECT id FROM users", &HashMap::new()).is_ok());
+        
+        let error = check("query", &template, "DELETE FROM users", &HashMap::new()).unwrap_err();
+        assert_eq!(error, r#"Output of template 'query' does not match /^SELECT /: "DELETE FROM users""#);
+        
+        let error = check("query", &self::template(r#"regex = "(""#), "", &HashMap::new()).unwrap_err();
+        assert!(error.starts_with("Invalid validation regex for template 'query'"), "{}", error);
+    }
+    
+    #[test]
+    fn test_no_validate_table() {
+        let template: Template = toml::from_str(r#"template = "{x}""#).unwrap();
+        assert!(check("plain", &template, "", &HashMap::new()).is_ok());
+    }
+    
+    #[test]
+    #[cfg(feature = "sqlparser")]
+    fn test_sql() {
+        let template = template(r#"format = "sql""#);
+        assert!(check("query", &template, "SELECT id FROM users WHERE id = $1", &HashMap::new()).is_ok());
+        let error = check("query", &template, "SELEC id FROM users", &HashMap::new()).unwrap_err();
+        assert!(error.starts_with("Output of template 'query' is not valid sql"), "{}", error);
+        
+        let template = self::template("format = \"sql\"\ndialect = \"postgres\"");
+        assert!(check("query", &template, "SELECT id::text FROM users", &HashMap::new()).is_ok());
+        let template = self::template("format = \"sql\"\ndialect = \"cobol\"");
+        let error = check("query", &template, "SELECT 1", &HashMap::new()).unwrap_err();
+        assert!(error.ends_with("Unknown SQL dialect 'cobol'"), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(not(feature = "sqlparser"))]
+    fn test_sql_needs_feature() {
+        let error = check("query", &template(r#"format = "sql""#), "SELECT 1", &HashMap::new()).unwrap_err();
+        assert!(error.ends_with("SQL validation requires the 'sqlparser' feature"), "{}", error);
+    }
+    
+    #[test]
+    fn test_json() {
+        let template = template(r#"format = "json""#);
+        assert!(check("body", &template, r#"{"name": "Alice", "roles": []}"#, &HashMap::new()).is_ok());
+        
+        // The error points at the broken value, and the param that produced it
+        let output = "{\"name\": \"Alice\",\n \"age\": forty}";
+        let params = params(&[("name", "Alice"), ("age", "forty")]);
+        let error = check("body", &template, output, &params).unwrap_err();
+        assert!(error.starts_with("Output of template 'body' is not valid json: "), "{}", error);
+        assert!(error.ends_with("(byte 27, near the value of 'age')"), "{}", error);
+    }
+    
+    #[test]
+    fn test_located() {
+        // Lines and columns are 1-based
+        assert_eq!(located("bad".to_string(), "ab\ncd", 2, 2, &HashMap::new()), "bad (byte 4)");
+        assert_eq!(located("bad".to_string(), "ab\ncd", 1, 1, &HashMap::new()), "bad (byte 0)");
+        // Offsets past the end are clamped
+        assert_eq!(located("bad".to_string(), "ab", 3, 9, &HashMap::new()), "bad (byte 2)");
+    }
+    
+    #[test]
+    fn test_nearest_param() {
+        let output = "SELECT id, name FROM users WHERE name = 'Alice'";
+        let params = params(&[("fields", "id, name"), ("user", "Alice"), ("empty", "")]);
+        assert_eq!(nearest_param(output, 8, &params), Some("fields"));
+        assert_eq!(nearest_param(output, 30, &params), Some("user"));
+        assert_eq!(nearest_param(output, output.len(), &params), Some("user"));
+        
+        // Values that don't appear, and empty ones, are never nearest
+        let params = self::params(&[("missing", "Bob"), ("empty", "")]);
+        assert_eq!(nearest_param(output, 0, &params), None);
+        
+        // Non-string values are matched as written
+        let params = HashMap::from([("limit".to_string(), Value::from(10))]);
+        assert_eq!(nearest_param("LIMIT 10", 0, &params), Some("limit"));
+    }
+    
+    #[test]
+    fn test_toml() {
+        let template = template(r#"format = "toml""#);
+        assert!(check("config", &template, "name = \"app\"\n[server]\nport = 80", &HashMap::new()).is_ok());
+        
+        let params = params(&[("port", "eighty")]);
+        let error = check("config", &template, "[server]\nport = eighty", &params).unwrap_err();
+        assert!(error.starts_with("Output of template 'config' is not valid toml: "), "{}", error);
+        assert!(error.ends_with("(byte 16, near the value of 'port')"), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn test_yaml() {
+        let template = template(r#"format = "yaml""#);
+        // Every document of a stream is checked
+        assert!(check("manifest", &template, "kind: Pod\n---\nkind: Service", &HashMap::new()).is_ok());
+        let error = check("manifest", &template, "kind: Pod\n---\nports: [80", &HashMap::new()).unwrap_err();
+        assert!(error.starts_with("Output of template 'manifest' is not valid yaml: "), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(not(feature = "yaml"))]
+    fn test_yaml_needs_feature() {
+        let error = check("manifest", &template(r#"format = "yaml""#), "a: 1", &HashMap::new()).unwrap_err();
+        assert!(error.ends_with("YAML validation requires the 'yaml' feature"), "{}", error);
+    }
+    
+    #[test]
+    fn test_check_markup_html() {
+        let valid = [
+            r#"<p class="intro">Hi<br>there <img src='a.png' alt="">!</p>"#,
+            "<!DOCTYPE html><!-- <unbalanced --><ul><li>One</li></ul>",
+            "<input disabled><br/>",
+            "<script>if (a < b && c > d) {}</script><STYLE>p > a {}</style>",
+        ];
+        for output in valid {
+            assert_eq!(check_markup(output, true), Ok(()), "{}", output);
+        }
+        
+        let invalid = [
+            ("<div><p></div>", "</div> closes <p>", 8),
+            ("<p>text", "Unclosed <p>", 0),
+            ("</p>", "</p> has no opening tag", 0),
+            ("<a href=/home>", "Unquoted value for attribute 'href'", 8),
+            ("<a href=\"/home>", "Unclosed value for attribute 'href'", 8),
+            ("1 < 2", "Expected a tag name after '<', or an escaped '&lt;'", 2),
+            ("<p", "Unclosed tag <p>", 0),
+            ("<!-- open", "Unclosed '<!--'", 0),
+            ("<script>x", "Unclosed <script>", 0),
+        ];
+        for (output, message, offset) in invalid {
+            assert_eq!(check_markup(output, true), Err((message.to_string(), offset)), "{}", output);
+        }
+    }
+    
+    #[test]
+    fn test_check_markup_xml() {
+        assert_eq!(check_markup(r#"<?xml version="1.0"?><a x="1"><b/><![CDATA[<c>]]></a>"#, false), Ok(()));
+        // Void elements and bare attributes are HTML only, and case matters
+        assert_eq!(check_markup("<a><br></a>", false), Err(("</a> closes <br>".to_string(), 7)));
+        assert_eq!(check_markup("<input disabled/>", false), Err(("Attribute 'disabled' has no value".to_string(), 7)));
+        assert_eq!(check_markup("<A></a>", false), Err(("</a> closes <A>".to_string(), 3)));
+        assert_eq!(check_markup("<A></a>", true), Ok(()));
+    }
+    
+    #[test]
+    fn test_markup_format() {
+        let template = template(r#"format = "html""#);
+        let params = params(&[("href", "/home")]);
+        let error = check("link", &template, "<a href=/home>Home</a>", &params).unwrap_err();
+        assert_eq!(
+            error,
+            "Output of template 'link' is not valid html: Unquoted value for attribute 'href' (byte 8, near the value of 'href')"
+        );
+    }
+    
+    #[test]
+    fn test_length_constraints() {
+        let template = template("max_len = 5\nnon_empty = true");
+        assert!(check("code", &template, "ABCDE", &HashMap::new()).is_ok());
+        
+        let error = check("code", &template, "ABCDEF", &HashMap::new()).unwrap_err();
+        assert_eq!(error, "Output of template 'code' is 6 bytes long, over the maximum of 5");
+        // Lengths are in bytes rather than characters
+        assert!(check("code", &template, "ÄÄÄ", &HashMap::new()).is_err());
+        
+        let error = check("code", &template, " \n\t", &HashMap::new()).unwrap_err();
+        assert_eq!(error, "Output of template 'code' is empty");
+        assert!(check("code", &self::template("max_len = 5"), "", &HashMap::new()).is_ok());
+    }
+    
+    #[test]
+    #[cfg(feature = "graphql")]
+    fn test_graphql() {
+        // Without a schema from the builder, queries are only parsed
+        let template = template(r#"format = "graphql""#);
+        assert!(check("query", &template, "query User($id: ID!) { user(id: $id) { name } }", &HashMap::new()).is_ok());
+        let error = check("query", &template, "query { user { name }", &HashMap::new()).unwrap_err();
+        assert!(error.starts_with("Output of template 'query' is not valid graphql: "), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(feature = "graphql")]
+    fn test_graphql_schema() {
+        let path = std::env::temp_dir().join(format!("tomplate-schema-{}.graphql", std::process::id()));
+        let settings = HashMap::from([("TOMPLATE_GRAPHQL_SCHEMA".to_string(), path.display().to_string())]);
+        let template = template(r#"format = "graphql""#);
+        let check = |query: &str| crate::settings::scoped(&settings, || check("query", &template, query, &HashMap::new()));
+        
+        // The schema is read from the scoped settings, and again when it changes
+        std::fs::write(&path, "type Query { user: User }\ntype User { name: String }").unwrap();
+        assert!(check("{ user { name } }").is_ok());
+        assert!(check("{ user { email } }").is_err());
+        std::fs::write(&path, "type Query { user: User }\ntype User { name: String, email: String }").unwrap();
+        assert!(check("{ user { email } }").is_ok());
+        std::fs::remove_file(&path).unwrap();
+        
+        let error = check("{ user { name } }").unwrap_err();
+        assert!(error.contains("Failed to read GraphQL schema"), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(not(feature = "graphql"))]
+    fn test_graphql_needs_feature() {
+        let error = check("query", &template(r#"format = "graphql""#), "{ a }", &HashMap::new()).unwrap_err();
+        assert!(error.ends_with("GraphQL validation requires the 'graphql' feature"), "{}", error);
+    }
+    
+    #[test]
+    fn test_bind_count() {
+        assert_eq!(bind_count("SELECT 1"), Ok(0));
+        assert_eq!(bind_count("WHERE a = $1 AND b = $2 OR c = $1"), Ok(2));
+        assert_eq!(bind_count("WHERE a = ? AND b = ?"), Ok(2));
+        // Named placeholders count once per name
+        assert_eq!(bind_count("WHERE a = :id OR b = :id AND c = :name"), Ok(2));
+        // Placeholders in strings aren't binds
+        assert_eq!(bind_count("WHERE a = '$3' AND b = $1"), Ok(1));
+        
+        assert_eq!(bind_count("WHERE a = $1 AND b = $3"), Err("$3 is used but $2 is not".to_string()));
+        assert_eq!(bind_count("WHERE a = $4"), Err("$4 is used but $1, $2, $3 is not".to_string()));
+        assert_eq!(bind_count("WHERE a = $1 AND b = ?"), Err("mixes $n, ? and :name placeholders".to_string()));
+        
+        let error = check("query", &template("binds = true"), "WHERE a = $2", &HashMap::new()).unwrap_err();
+        assert_eq!(error, "Output of template 'query' has inconsistent bind placeholders: $2 is used but $1 is not");
+    }
+    
+    #[test]
+    #[cfg(feature = "naga")]
+    fn test_wgsl() {
+        let template = template(r#"format = "wgsl""#);
+        let shader = "@compute @workgroup_size(64)\nfn main(@builtin(global_invocation_id) id: vec3<u32>) {}";
+        assert!(check("shader", &template, shader, &HashMap::new()).is_ok());
+        
+        let params = params(&[("size", "sixty")]);
+        let shader = "@compute @workgroup_size(sixty)\nfn main() {}";
+        let error = check("shader", &template, shader, &params).unwrap_err();
+        assert!(error.starts_with("Output of template 'shader' is not valid wgsl: "), "{}", error);
+        assert!(error.contains("near the value of 'size'"), "{}", error);
+    }
+    
+    #[test]
+    #[cfg(not(feature = "naga"))]
+    fn test_wgsl_needs_feature() {
+        let error = check("shader", &template(r#"format = "wgsl""#), "fn main() {}", &HashMap::new()).unwrap_err();
+        assert!(error.ends_with("WGSL validation requires the 'naga' feature"), "{}", error);
+    }
+}
@@ -0,0 +1,47 @@
+//! The on-disk cache of rendered templates enabled by `TOMPLATE_CACHE_DIR`.
+//!
+//! The cache directory is read once per process, so this runs in its own
+//! test binary.
+
+use std::fs;
+use std::path::Path;
+use tomplate_engines::cache;
+
+#[test]
+fn test_expansion_cache() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("expansion_cache");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let registry = dir.join("registry.toml");
+    fs::write(&registry, "[query]\ntemplate = \"SELECT 1\"\n").unwrap();
+    // SAFETY: this is the only test in the binary, and nothing else runs yet
+    unsafe {
+        std::env::set_var("TOMPLATE_CACHE_DIR", dir.join("cache"));
+        std::env::set_var("TOMPLATE_TEMPLATES_PATH", &registry);
+    }
+    
+    assert_eq!(cache::load("simple\0Hi {name}"), None);
+    cache::store("simple\0Hi {name}", "Hi Alice");
+    assert_eq!(cache::load("simple\0Hi {name}").as_deref(), Some("Hi Alice"));
+    assert_eq!(cache::load("simple\0Bye {name}"), None);
+    assert_eq!(fs::read_dir(dir.join("cache")).unwrap().count(), 1);
+    
+    // Entries are keyed by the version and features of the engines, so an
+    // upgrade doesn't serve output of the old ones
+    let entry = fs::read_dir(dir.join("cache")).unwrap().next().unwrap().unwrap();
+    let stored: serde_json::Value = serde_json::from_slice(&fs::read(entry.path()).unwrap()).unwrap();
+    let engines = format!("{}+", env!("CARGO_PKG_VERSION"));
+    assert!(stored["key"].as_str().unwrap().starts_with(&engines), "{}", stored["key"]);
+    
+    // Entries are keyed by the registry's contents
+    fs::write(&registry, "[query]\ntemplate = \"SELECT id FROM users\"\n").unwrap();
+    assert_eq!(cache::load("simple\0Hi {name}"), None);
+    cache::store("simple\0Hi {name}", "Hello Alice");
+    assert_eq!(cache::load("simple\0Hi {name}").as_deref(), Some("Hello Alice"));
+    
+    // Renders go through the cache
+    let params = [("name".to_string(), "Bob".into())].into_iter().collect();
+    let options = tomplate_engines::engines::Options::default();
+    assert_eq!(tomplate_engines::engines::process("simple", "Hi {name}", &params, &options).unwrap(), "Hi Bob");
+    assert_eq!(fs::read_dir(dir.join("cache")).unwrap().count(), 3);
+}
@@ -0,0 +1,48 @@
+//! The expansion profile written to the file named by `TOMPLATE_PROFILE`.
+//!
+//! The profile is read once per process, so this runs in its own test
+//! binary.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tomplate_engines::engines::{self, Options};
+use tomplate_engines::{profile, templates};
+
+#[test]
+fn test_profile() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("profile");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let registry = dir.join("registry.toml");
+    fs::write(&registry, "[user_fields]\ntemplate = \"id, name\"\n").unwrap();
+    let path = dir.join("profile.txt");
+    // SAFETY: this is the only test in the binary, and nothing else runs yet
+    unsafe {
+        std::env::set_var("TOMPLATE_PROFILE", &path);
+        std::env::set_var("TOMPLATE_TEMPLATES_PATH", &registry);
+    }
+    
+    for _ in 0..2 {
+        let _expansion = profile::expansion("tomplate");
+        assert!(templates::get("user_fields").is_some());
+        let output = engines::process("simple", "SELECT {@user_fields}", &HashMap::new(), &Options::default());
+        assert_eq!(output.unwrap(), "SELECT id, name");
+    }
+    drop(profile::expansion("tomplate_join"));
+    
+    // The summary is rewritten after every expansion
+    let summary = fs::read_to_string(&path).unwrap();
+    let counts: Vec<(&str, &str)> = summary
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            Some((words.next()?, words.next()?))
+        })
+        .collect();
+    assert!(counts.contains(&("tomplate", "2")), "{}", summary);
+    assert!(counts.contains(&("tomplate_join", "1")), "{}", summary);
+    assert!(counts.contains(&("simple", "1")), "{}", summary);
+    assert!(counts.contains(&("(cached)", "1")), "{}", summary);
+    assert!(summary.contains("Registry loads: 1 "), "{}", summary);
+}
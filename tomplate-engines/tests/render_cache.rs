@@ -0,0 +1,76 @@
+//! Renders are reused within a process until the registry changes.
+//!
+//! The cache is shared by the whole process, so this runs in its own test
+//! binary.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tomplate_engines::engines::{self, Options};
+use tomplate_engines::settings;
+
+#[test]
+fn test_renders_are_memoized() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("render_cache");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let registry = dir.join("registry.toml");
+    fs::write(&registry, "[query]\ntemplate = \"SELECT 1\"\n").unwrap();
+    
+    // An engine that logs each input it renders
+    let log = dir.join("calls.log");
+    let settings = HashMap::from([
+        ("TOMPLATE_TEMPLATES_PATH".to_string(), registry.display().to_string()),
+        (
+            "TOMPLATE_CUSTOM_ENGINES".to_string(),
+            serde_json::json!({ "logged": format!("tee -a {}", log.display()) }).to_string(),
+        ),
+    ]);
+    let calls = || fs::read_to_string(&log).unwrap_or_default().matches("\"template\"").count();
+    let render = |template: &str, name: &str| {
+        let params = HashMap::from([("name".to_string(), serde_json::Value::from(name))]);
+        settings::scoped(&settings, || engines::process("logged", template, &params, &Options::default())).unwrap()
+    };
+    
+    let first = render("Hi {name}", "Alice");
+    assert_eq!(render("Hi {name}", "Alice"), first);
+    assert_eq!(calls(), 1);
+    
+    // Any other input renders again
+    render("Hi {name}", "Bob");
+    render("Bye {name}", "Alice");
+    assert_eq!(calls(), 3);
+    let params = HashMap::from([("name".to_string(), serde_json::Value::from("Alice"))]);
+    let trimmed = Options { trim: true, ..Options::default() };
+    settings::scoped(&settings, || engines::process("logged", "Hi {name}", &params, &trimmed)).unwrap();
+    assert_eq!(calls(), 4);
+    
+    // So does the same input under other settings, such as another command
+    // for the engine
+    let mut greeting = settings.clone();
+    greeting.insert(
+        "TOMPLATE_CUSTOM_ENGINES".to_string(),
+        serde_json::json!({ "logged": format!("sed -e s/Hi/Yo/ -e w{}", dir.join("sed.log").display()) }).to_string(),
+    );
+    let params = HashMap::from([("name".to_string(), serde_json::Value::from("Alice"))]);
+    let other = settings::scoped(&greeting, || engines::process("logged", "Hi {name}", &params, &Options::default()));
+    assert_eq!(other.unwrap(), first.replace("Hi", "Yo"));
+    assert_eq!(render("Hi {name}", "Alice"), first);
+    assert_eq!(calls(), 4);
+    
+    // Changing the registry drops every cached render
+    fs::write(&registry, "[query]\ntemplate = \"SELECT id FROM users\"\n").unwrap();
+    render("Hi {name}", "Alice");
+    assert_eq!(calls(), 5);
+    render("Hi {name}", "Alice");
+    assert_eq!(calls(), 5);
+    
+    // Logging usage renders every time, so each reference is recorded
+    let mut logging = settings.clone();
+    logging.insert("TOMPLATE_USAGE_LOG".to_string(), dir.join("usage.log").display().to_string());
+    let params = HashMap::from([("name".to_string(), serde_json::Value::from("Alice"))]);
+    for _ in 0..2 {
+        settings::scoped(&logging, || engines::process("logged", "Hi {name}", &params, &Options::default())).unwrap();
+    }
+    assert_eq!(calls(), 7);
+}
@@ -0,0 +1,109 @@
+//! `tomplate_assert!` and `tomplate_assert_eq!`, checks on rendered templates
+//! that run during expansion.
+
+use crate::{block, parser::{self, ParamValue, TemplateCall}};
+use proc_macro2::TokenStream;
+use syn::{
+    parse::{Parse, ParseStream},
+    BinOp, Expr, ExprLit, Lit, Result, Token, UnOp,
+};
+
+/// Arguments of a `tomplate_assert!`
+pub struct AssertSpec {
+    predicate: Expr,
+    call: TemplateCall,
+}
+
+impl Parse for AssertSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let predicate = input.parse::<Expr>()?;
+        input.parse::<Token![,]>()?;
+        let call = parser::parse_template_args(input.parse::<TokenStream>()?)?;
+        Ok(AssertSpec { predicate, call })
+    }
+}
+
+/// Render the template and check the predicate against it
+pub fn process_assert(spec: AssertSpec) -> Result<TokenStream> {
+    let output = block::render_call(&spec.call)?;
+    if holds(&spec.predicate, &output)? {
+        Ok(TokenStream::new())
+    } else {
+        Err(syn::Error::new_spanned(
+            &spec.predicate,
+            format!("Assertion failed for rendered template:\n{}", output),
+        ))
+    }
+}
+
+/// Render the template and compare it with its `expected` parameter
+pub fn process_assert_eq(mut call: TemplateCall) -> Result<TokenStream> {
+    let index = call
+        .params
+        .iter()
+        .position(|(name, _)| name == "expected")
+        .ok_or_else(|| syn::Error::new(call.span, "Expected an `expected = \"...\"` parameter"))?;
+    let expected = match call.params.remove(index).1 {
+        ParamValue::Literal(serde_json::Value::String(expected)) => expected,
+        _ => return Err(syn::Error::new(call.span, "`expected` must be a string literal")),
+    };
+    
+    let output = block::render_call(&call)?;
+    if output == expected {
+        return Ok(TokenStream::new());
+    }
+    
+    let offset = output
+        .char_indices()
+        .zip(expected.chars())
+        .find(|((_, left), right)| left != right)
+        .map(|((offset, _), _)| offset)
+        .unwrap_or_else(|| output.len().min(expected.len()));
+    Err(syn::Error::new(
+        call.span,
+        format!(
+            "Rendered template differs from expected at byte {}\n  rendered: {:?}\n  expected: {:?}",
+            offset, output, expected
+        ),
+    ))
+}
+
+/// Evaluate a predicate such as `contains("WHERE") && !ends_with(";")`
+fn holds(predicate: &Expr, output: &str) -> Result<bool> {
+    match predicate {
+        Expr::Paren(paren) => holds(&paren.expr, output),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => Ok(!holds(&unary.expr, output)?),
+        Expr::Binary(binary) => match binary.op {
+            BinOp::And(_) => Ok(holds(&binary.left, output)? && holds(&binary.right, output)?),
+            BinOp::Or(_) => Ok(holds(&binary.left, output)? || holds(&binary.right, output)?),
+            _ => Err(syn::Error::new_spanned(binary.op, "Expected `&&` or `||`")),
+        },
+        Expr::Call(call) => {
+            let function = match &*call.func {
+                Expr::Path(path) => path.path.get_ident().map(|ident| ident.to_string()),
+                _ => None,
+            };
+            let argument = match (call.args.len(), call.args.first()) {
+                (1, Some(Expr::Lit(ExprLit { lit: Lit::Str(s), .. }))) => s.value(),
+                _ => return Err(syn::Error::new_spanned(&call.args, "Expected a single string literal")),
+            };
+            
+            match function.as_deref() {
+                Some("contains") => Ok(output.contains(&argument)),
+                Some("starts_with") => Ok(output.starts_with(&argument)),
+                Some("ends_with") => Ok(output.ends_with(&argument)),
+                Some("matches") => regex::Regex::new(&argument)
+                    .map(|regex| regex.is_match(output))
+                    .map_err(|e| syn::Error::new_spanned(&call.args, format!("Invalid regex: {}", e))),
+                _ => Err(syn::Error::new_spanned(
+                    &call.func,
+                    "Expected contains, starts_with, ends_with or matches",
+                )),
+            }
+        }
+        _ => Err(syn::Error::new_spanned(
+            predicate,
+            "Expected a predicate such as contains(\"...\"), combined with !, && and ||",
+        )),
+    }
+}
@@ -1,9 +1,16 @@
-use crate::parser::{CompositionBlock, Statement, TemplateCall, TemplateSource, ParamValue};
-use crate::scope::Scope;
+use crate::engines::Options;
+use crate::function;
+use crate::parser::{
+    CompositionBlock, ExpressionBlock, ForEachOutput, ForEachSpec, MatrixSpec, ParamValue, Statement,
+    TemplateCall, TemplateSource,
+};
+use crate::scope::{EnumEntry, Scope, Variant};
 use crate::templates;
-use proc_macro2::TokenStream;
-use std::collections::HashSet;
-use syn::Result;
+use proc_macro2::{Group, Literal, TokenStream, TokenTree};
+use quote::quote;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use syn::{ext::IdentExt, Attribute, Expr, ExprLit, Ident, Lit, LitStr, Result};
 
 /// Process a composition block and generate the resulting const declarations
 pub fn process_block(block: CompositionBlock) -> Result<TokenStream> {
@@ -11,38 +18,355 @@ pub fn process_block(block: CompositionBlock) -> Result<TokenStream> {
     let mut scope = Scope::new();
     
     // Validate the block (no duplicate names, let before const references, etc.)
-    validate_block(&block)?;
+    validate_statements(&block.statements)?;
     
     // Process all statements
     for statement in block.statements {
-        match statement {
-            Statement::Let { name, value } => {
-                // Process the template call and store in local scope
-                let resolved = process_template_call(&value, &scope)?;
-                scope.add_local(name.to_string(), resolved);
+        process_statement(statement, &mut scope)?;
+    }
+    
+    // Generate the output TokenStream with all const declarations
+    Ok(scope.generate_output())
+}
+
+/// Process an expression block and generate the resulting string literal
+pub fn process_expression_block(block: ExpressionBlock) -> Result<TokenStream> {
+    let mut scope = Scope::new();
+    
+    // The final call may reference any of the block's let bindings
+    let let_names = validate_statements(&block.statements)?;
+    validate_references(&block.value, &let_names)?;
+    
+    for statement in block.statements {
+        process_statement(statement, &mut scope)?;
+    }
+    
+    // Without cfg-gated bindings the call resolves to exactly one variant
+    let value = process_template_call(&block.value, &scope)?
+        .into_iter()
+        .map(|variant| variant.value)
+        .next()
+        .unwrap_or_default();
+    
+    Ok(quote! { #value })
+}
+
+/// Process a standalone `tomplate_matrix!` invocation
+pub fn process_matrix(spec: MatrixSpec) -> Result<TokenStream> {
+    process_block(CompositionBlock {
+        statements: vec![Statement::Matrix { attrs: Vec::new(), spec }],
+    })
+}
+
+/// Render a standalone template call, which has no let bindings to refer to
+pub fn render_call(call: &TemplateCall) -> Result<String> {
+    validate_references(call, &HashSet::new())?;
+    Ok(process_template_call(call, &Scope::new())?
+        .into_iter()
+        .map(|variant| variant.value)
+        .next()
+        .unwrap_or_default())
+}
+
+/// Render every registry template matching a glob or prefix without
+/// parameters, returning the names and rendered templates in sorted order
+pub fn render_matching(pattern: &LitStr) -> Result<Vec<(String, String)>> {
+    let names = templates::matching_names(&pattern.value())
+        .map_err(|e| syn::Error::new_spanned(pattern, e))?;
+    
+    let mut rendered = Vec::with_capacity(names.len());
+    for name in names {
+        let call = TemplateCall {
+            source: TemplateSource::Name(name.clone()),
+            params: Vec::new(),
+            options: Options::default(),
+            span: pattern.span(),
+        };
+        let value = render_call(&call)
+            .map_err(|e| syn::Error::new_spanned(pattern, format!("In template '{}': {}", name, e)))?;
+        rendered.push((name, value));
+    }
+    Ok(rendered)
+}
+
+/// Process a `tomplate_for_each!` invocation, rendering every registry
+/// template that matches its pattern without parameters
+pub fn process_for_each(spec: ForEachSpec) -> Result<TokenStream> {
+    let rendered = render_matching(&spec.pattern)?;
+    
+    match spec.output {
+        ForEachOutput::Consts(name_pattern) => {
+            let mut scope = Scope::new();
+            for (name, value) in rendered {
+                let name_params = HashMap::from([("name".to_string(), const_name_part(&name).into())]);
+                let name = crate::engines::simple::process(&name_pattern.value(), &name_params)
+                    .map_err(|e| syn::Error::new_spanned(&name_pattern, e))?;
+                let mut name = syn::parse_str::<Ident>(&name).map_err(|_| {
+                    syn::Error::new_spanned(&name_pattern, format!("'{}' is not a valid const name", name))
+                })?;
+                name.set_span(name_pattern.span());
+                scope.add_export(Vec::new(), name, Variant::new(value));
             }
-            Statement::Const { attrs, name, value } => {
-                // Process the template call and add to exports
-                let resolved = process_template_call(&value, &scope)?;
-                scope.add_export(attrs, name.to_string(), resolved);
+            Ok(scope.generate_output())
+        }
+        ForEachOutput::Tokens { name, template, body } => {
+            let repetitions = rendered.iter().map(|(template_name, value)| {
+                let replacements = [(&name, template_name.as_str()), (&template, value.as_str())];
+                substitute(body.stream(), &replacements)
+            });
+            
+            // A bracketed body collects the repetitions into an array
+            Ok(match body.delimiter() {
+                proc_macro2::Delimiter::Bracket => quote! { [#(#repetitions),*] },
+                _ => repetitions.collect(),
+            })
+        }
+    }
+}
+
+/// Process a `#[tomplate::consts(prefix = "...")]` module, adding a `pub
+/// const` for every matching registry template
+///
+/// Const names are the template names without the prefix, upper-cased with
+/// anything that is not alphanumeric replaced by `_`. With `pattern = "..."`
+/// instead, templates are matched by glob and keep their full names.
+pub fn process_consts(args: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
+    let mut selector = None;
+    for meta in syn::parse::Parser::parse2(parser, args)? {
+        let meta = meta.require_name_value()?;
+        let kind = meta.path.require_ident()?.to_string();
+        if kind != "prefix" && kind != "pattern" {
+            return Err(syn::Error::new_spanned(&meta.path, "Expected `prefix = \"...\"` or `pattern = \"...\"`"));
+        }
+        let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) = &meta.value else {
+            return Err(syn::Error::new_spanned(&meta.value, "Expected a string literal"));
+        };
+        if selector.replace((kind, value.clone())).is_some() {
+            return Err(syn::Error::new_spanned(meta, "Only one of `prefix` or `pattern` may be given"));
+        }
+    }
+    let Some((kind, pattern)) = selector else {
+        return Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "Expected `prefix = \"...\"` or `pattern = \"...\"`",
+        ));
+    };
+    
+    let mut module = syn::parse2::<syn::ItemMod>(item)?;
+    let Some((_, items)) = &mut module.content else {
+        return Err(syn::Error::new_spanned(
+            &module,
+            "#[tomplate::consts] needs an inline module, e.g. `mod emails {}`",
+        ));
+    };
+    
+    let mut names = HashSet::new();
+    for (name, value) in render_matching(&pattern)? {
+        let short = match kind.as_str() {
+            "prefix" => name.strip_prefix(pattern.value().as_str()).unwrap_or(&name),
+            _ => &name,
+        };
+        let const_name = const_name_part(short);
+        if syn::parse_str::<Ident>(&const_name).is_err() {
+            return Err(syn::Error::new_spanned(
+                &pattern,
+                format!("'{}' is not a valid const name", const_name),
+            ));
+        }
+        if !names.insert(const_name.clone()) {
+            return Err(syn::Error::new_spanned(
+                &pattern,
+                format!("Templates map to the same const name '{}'", const_name),
+            ));
+        }
+        
+        let ident = Ident::new(&const_name, pattern.span());
+        items.push(syn::parse_quote! {
+            #[doc = concat!("Rendered `", #name, "` template.")]
+            pub const #ident: &str = #value;
+        });
+    }
+    
+    Ok(quote! { #module })
+}
+
+/// Replace the given identifiers with string literals throughout a token stream
+fn substitute(tokens: TokenStream, replacements: &[(&Ident, &str)]) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Ident(ident) => match replacements.iter().find(|(from, _)| **from == ident) {
+                Some((_, value)) => {
+                    let mut literal = Literal::string(value);
+                    literal.set_span(ident.span());
+                    TokenTree::Literal(literal)
+                }
+                None => TokenTree::Ident(ident),
+            },
+            TokenTree::Group(group) => {
+                let mut substituted = Group::new(group.delimiter(), substitute(group.stream(), replacements));
+                substituted.set_span(group.span());
+                TokenTree::Group(substituted)
+            }
+            token => token,
+        })
+        .collect()
+}
+
+/// Process a single statement, updating the scope with its bindings or exports
+fn process_statement(statement: Statement, scope: &mut Scope) -> Result<()> {
+    match statement {
+        Statement::Let { attrs, name, value } => {
+            // Process the template call and store in local scope,
+            // tagging each variant with the binding's own cfg predicates
+            let cfgs = cfg_predicates(&attrs)?;
+            for mut variant in process_template_call(&value, scope)? {
+                variant.cfgs = cfgs.iter().cloned().chain(variant.cfgs).collect();
+                scope.add_local(name.to_string(), variant);
+            }
+        }
+        Statement::Const { attrs, name, value } => {
+            check_link_section(&attrs)?;
+            
+            // Process the template call and add to exports, one const
+            // per combination of cfg-gated let bindings it references
+            for variant in process_template_call(&value, scope)? {
+                scope.add_export(attrs.clone(), name.clone(), variant);
+            }
+        }
+        Statement::Enum { attrs, vis, name, variants } => {
+            // Process each variant's template call and generate the enum
+            let mut entries = Vec::with_capacity(variants.len());
+            for variant in variants {
+                let values = process_template_call(&variant.value, scope)?;
+                entries.push(EnumEntry {
+                    attrs: variant.attrs,
+                    name: variant.name,
+                    values,
+                });
+            }
+            scope.add_enum(attrs, vis, name, entries);
+        }
+        Statement::Fn { attrs, vis, name, params, mut value } => {
+            check_escape(&value)?;
+            
+            // Parameters shadow let bindings and are passed to the template
+            // implicitly, standing in as markers while rendering
+            let mut child = scope.child();
+            for param in &params {
+                child.set_local(param.to_string(), Variant::new(function::marker(&param.to_string())));
+                if !value.params.iter().any(|(key, _)| *param == key) {
+                    value.params.push((param.to_string(), ParamValue::Variable(param.clone())));
+                }
+            }
+            
+            for variant in process_template_call(&value, &child)? {
+                let body = function::format_body(&variant.value, &params, value.span)?;
+                scope.add_function(attrs.clone(), vis.clone(), name.clone(), params.clone(), variant.cfgs, body);
+            }
+        }
+        Statement::Matrix { attrs, spec } => {
+            // Enumerate every combination of axis values
+            let mut combinations = vec![Vec::new()];
+            for (axis, values) in &spec.axes {
+                let mut next = Vec::with_capacity(combinations.len() * values.len());
+                for combination in &combinations {
+                    for value in values {
+                        let mut combination: Vec<(String, String)> = combination.clone();
+                        combination.push((axis.to_string(), value.clone()));
+                        next.push(combination);
+                    }
+                }
+                combinations = next;
+            }
+            
+            for combination in combinations {
+                // Axis values shadow let bindings of the same name
+                let mut child = scope.child();
+                let mut name_params = HashMap::new();
+                for (axis, value) in combination {
+                    name_params.insert(axis.clone(), const_name_part(&value).into());
+                    child.set_local(axis, Variant::new(value));
+                }
+                
+                let pattern = &spec.name_pattern;
+                let name = crate::engines::simple::process(&pattern.value(), &name_params)
+                    .map_err(|e| syn::Error::new_spanned(pattern, e))?;
+                let mut name = syn::parse_str::<Ident>(&name).map_err(|_| {
+                    syn::Error::new_spanned(pattern, format!("'{}' is not a valid const name", name))
+                })?;
+                name.set_span(pattern.span());
+                
+                for variant in process_template_call(&spec.value, &child)? {
+                    scope.add_export(attrs.clone(), name.clone(), variant);
+                }
             }
         }
     }
     
-    // Generate the output TokenStream with all const declarations
-    Ok(scope.generate_output())
+    Ok(())
+}
+
+/// Reject escape modes on the template a `fn` statement renders
+fn check_escape(call: &TemplateCall) -> Result<()> {
+    let options = match &call.source {
+        TemplateSource::Name(name) => match templates::get(name) {
+            Some(template) => Options::from_template(&template).merge(&call.options),
+            None => call.options.clone(),
+        },
+        TemplateSource::Literal(_) => return Ok(()),
+    };
+    function::check_escape(&options, call.span)
+}
+
+/// Check that a `#[link_section]` on an export names its section with a
+/// string
+fn check_link_section(attrs: &[Attribute]) -> Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("link_section")) {
+        let value = &attr.meta.require_name_value()?.value;
+        if !matches!(value, Expr::Lit(ExprLit { lit: Lit::Str(_), .. })) {
+            return Err(syn::Error::new_spanned(value, "Expected `#[link_section = \"...\"]`"));
+        }
+    }
+    Ok(())
+}
+
+/// Convert a matrix axis value into a const name fragment: upper-cased, with
+/// anything that is not alphanumeric replaced by `_`
+pub fn const_name_part(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// Extract the predicates of `#[cfg(...)]` attributes
+fn cfg_predicates(attrs: &[Attribute]) -> Result<Vec<TokenStream>> {
+    attrs
+        .iter()
+        .map(|attr| -> Result<TokenStream> { Ok(attr.meta.require_list()?.tokens.clone()) })
+        .collect()
 }
 
-/// Validate that the block follows the rules
-fn validate_block(block: &CompositionBlock) -> Result<()> {
+/// Validate that the block follows the rules, returning the let binding names
+fn validate_statements(statements: &[Statement]) -> Result<HashSet<String>> {
     let mut defined_names = HashSet::new();
     let mut let_names = HashSet::new();
+    // Let bindings whose every definition so far is cfg-gated
+    let mut cfg_lets = HashSet::new();
     
-    for statement in &block.statements {
+    for statement in statements {
         match statement {
-            Statement::Let { name, value } => {
-                // Check for duplicate names
-                if !defined_names.insert(name.to_string()) {
+            Statement::Let { attrs, name, value } => {
+                // Check for duplicate names, allowing alternative cfg-gated
+                // definitions of the same let binding
+                let gated = !attrs.is_empty();
+                if defined_names.insert(name.to_string()) {
+                    if gated {
+                        cfg_lets.insert(name.to_string());
+                    }
+                } else if !(gated && cfg_lets.contains(&name.to_string())) {
                     return Err(syn::Error::new_spanned(
                         name,
                         format!("Duplicate definition of '{}'", name),
@@ -65,10 +389,65 @@ fn validate_block(block: &CompositionBlock) -> Result<()> {
                 // Const can reference any let binding (they're all defined by now)
                 validate_references(value, &let_names)?;
             }
+            Statement::Enum { name, variants, .. } => {
+                // Check for duplicate names
+                if !defined_names.insert(name.to_string()) {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        format!("Duplicate definition of '{}'", name),
+                    ));
+                }
+                
+                let mut variant_names = HashSet::new();
+                for variant in variants {
+                    if !variant_names.insert(variant.name.to_string()) {
+                        return Err(syn::Error::new_spanned(
+                            &variant.name,
+                            format!("Duplicate variant '{}' in enum '{}'", variant.name, name),
+                        ));
+                    }
+                    validate_references(&variant.value, &let_names)?;
+                }
+            }
+            Statement::Fn { name, params, value, .. } => {
+                if !defined_names.insert(name.to_string()) {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        format!("Duplicate definition of '{}'", name),
+                    ));
+                }
+                
+                // Parameters are visible to the template alongside let bindings
+                let mut visible = let_names.clone();
+                for param in params {
+                    if !visible.insert(param.to_string()) && !let_names.contains(&param.to_string()) {
+                        return Err(syn::Error::new_spanned(
+                            param,
+                            format!("Duplicate parameter '{}'", param),
+                        ));
+                    }
+                }
+                validate_references(value, &visible)?;
+            }
+            Statement::Matrix { spec, .. } => {
+                // Axes are visible to the matrix template alongside let bindings
+                let mut axes = HashSet::new();
+                for (axis, _) in &spec.axes {
+                    if !axes.insert(axis.to_string()) {
+                        return Err(syn::Error::new_spanned(
+                            axis,
+                            format!("Duplicate matrix axis '{}'", axis),
+                        ));
+                    }
+                }
+                let mut visible = let_names.clone();
+                visible.extend(axes);
+                validate_references(&spec.value, &visible)?;
+            }
         }
     }
     
-    Ok(())
+    Ok(let_names)
 }
 
 /// Validate that a template call only references defined variables
@@ -76,9 +455,9 @@ fn validate_references(call: &TemplateCall, defined: &HashSet<String>) -> Result
     for (_, value) in &call.params {
         match value {
             ParamValue::Variable(name) => {
-                if !defined.contains(name) {
-                    return Err(syn::Error::new(
-                        proc_macro2::Span::call_site(),
+                if !defined.contains(&name.to_string()) {
+                    return Err(syn::Error::new_spanned(
+                        name,
                         format!("Undefined variable: '{}'", name),
                     ));
                 }
@@ -86,53 +465,119 @@ fn validate_references(call: &TemplateCall, defined: &HashSet<String>) -> Result
             ParamValue::Nested(nested) => {
                 validate_references(nested, defined)?;
             }
-            ParamValue::Literal(_) => {}
+            // Identifiers in transforms name themselves without a binding
+            ParamValue::Literal(_) | ParamValue::Transform(_) => {}
         }
     }
     Ok(())
 }
 
+/// Resolve a parameter value into its values under each combination of cfg
+/// predicates
+///
+/// Literals keep their type, bindings and nested calls are strings.
+fn resolve_param(value: &ParamValue, scope: &Scope) -> Result<Vec<(Vec<TokenStream>, Value)>> {
+    Ok(match value {
+        ParamValue::Literal(value) => vec![(Vec::new(), value.clone())],
+        ParamValue::Variable(name) => {
+            scope.get_local(&name.to_string())
+                .ok_or_else(|| syn::Error::new_spanned(
+                    name,
+                    format!("Undefined variable: '{}'", name),
+                ))?
+                .iter()
+                .map(|variant| (variant.cfgs.clone(), variant.value.clone().into()))
+                .collect()
+        }
+        ParamValue::Nested(nested) => {
+            // Recursively process nested template call
+            process_template_call(nested, scope)?
+                .into_iter()
+                .map(|variant| (variant.cfgs, variant.value.into()))
+                .collect()
+        }
+        ParamValue::Transform(transform) => {
+            let values = match &*transform.value {
+                // Without a binding, an identifier stands for its own name
+                ParamValue::Variable(name) if scope.get_local(&name.to_string()).is_none() => {
+                    vec![(Vec::new(), Value::String(name.unraw().to_string()))]
+                }
+                value => resolve_param(value, scope)?,
+            };
+            values
+                .into_iter()
+                .map(|(cfgs, value)| {
+                    crate::engines::filters::apply(transform.filter, None, &value)
+                        .map(|value| (cfgs, value))
+                        .map_err(|e| syn::Error::new(transform.span, e))
+                })
+                .collect::<Result<_>>()?
+        }
+    })
+}
+
 /// Process a template call, resolving all variables and nested calls
-fn process_template_call(call: &TemplateCall, scope: &Scope) -> Result<String> {
+///
+/// Returns one variant per combination of cfg-gated let bindings referenced
+/// by the call; calls without such references produce a single variant.
+fn process_template_call(call: &TemplateCall, scope: &Scope) -> Result<Vec<Variant>> {
     // First, determine if this is an inline template or a registry lookup
-    let (template_string, engine_name) = match &call.source {
+    let (template_string, engine_name, options) = match &call.source {
         TemplateSource::Name(name) => {
             // Try to find it in the registry
-            let templates = templates::load_templates();
-            if let Some(template) = templates.get(name) {
-                // Found in registry, use its template and engine
+            if let Some(template) = templates::get(name) {
+                // Found in registry, use its template, engine and options
+                templates::record_usage(name);
                 let template_str = template.template.clone();
                 let engine = template.engine.as_deref().unwrap_or("simple").to_string();
-                (template_str, engine)
+                (template_str, engine, Options::from_template(&template).merge(&call.options))
             } else {
                 // Not in registry, treat as inline template with simple engine
-                (name.clone(), "simple".to_string())
+                (name.clone(), "simple".to_string(), call.options.clone())
             }
         }
+        TemplateSource::Literal(value) => {
+            // Plain strings are used as-is, never substituted
+            return Ok(vec![Variant::new(value.clone())]);
+        }
     };
     
-    // Process parameters, resolving variables and nested calls
-    let mut resolved_params = std::collections::HashMap::new();
+    // Process parameters, resolving variables and nested calls into every
+    // combination of cfg predicates and parameter values
+    let mut combinations = vec![(Vec::new(), HashMap::new())];
     for (key, value) in &call.params {
-        let resolved_value = match value {
-            ParamValue::Literal(s) => s.clone(),
-            ParamValue::Variable(name) => {
-                scope.get_local(name)
-                    .ok_or_else(|| syn::Error::new(
-                        proc_macro2::Span::call_site(),
-                        format!("Undefined variable: '{}'", name),
-                    ))?
-                    .clone()
-            }
-            ParamValue::Nested(nested) => {
-                // Recursively process nested template call
-                process_template_call(nested, scope)?
+        let variants = resolve_param(value, scope)?;
+        
+        let mut next = Vec::with_capacity(combinations.len() * variants.len());
+        for (cfgs, params) in &combinations {
+            for (variant_cfgs, value) in &variants {
+                let mut cfgs: Vec<TokenStream> = cfgs.clone();
+                cfgs.extend(variant_cfgs.iter().cloned());
+                let mut params: HashMap<String, Value> = params.clone();
+                params.insert(key.clone(), value.clone());
+                next.push((cfgs, params));
             }
-        };
-        resolved_params.insert(key.clone(), resolved_value);
+        }
+        combinations = next;
     }
     
-    // Process the template with the resolved parameters
-    crate::engines::process(&engine_name, &template_string, &resolved_params)
-        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))
+    // Process the template with each set of resolved parameters
+    combinations
+        .into_iter()
+        .map(|(cfgs, params)| -> Result<Variant> {
+            let value = match crate::engines::process(&engine_name, &template_string, &params, &options) {
+                Ok(value) => value,
+                // Stub out renders that fail for lack of a registry
+                Err(_) if !templates::available() => template_string.clone(),
+                Err(e) => return Err(syn::Error::new(call.span, e)),
+            };
+            if let TemplateSource::Name(name) = &call.source
+                && let Some(template) = templates::get(name)
+            {
+                crate::validate::check(name, &template, &value, &params)
+                    .map_err(|e| syn::Error::new(call.span, e))?;
+            }
+            Ok(Variant { cfgs, value })
+        })
+        .collect()
 }
\ No newline at end of file
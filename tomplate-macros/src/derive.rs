@@ -0,0 +1,51 @@
+//! `#[derive(Tomplate)]` for enums whose variants map to templates.
+
+use crate::{block, parser};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Result};
+
+/// Generate `as_str()` for an enum, rendering the `#[tomplate(...)]` call on
+/// each variant at compile time
+pub fn derive_tomplate(input: DeriveInput) -> Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "#[derive(Tomplate)] is only supported on enums",
+        ));
+    };
+    
+    let mut arms = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        let mut attrs = variant.attrs.iter().filter(|attr| attr.path().is_ident("tomplate"));
+        let attr = attrs.next().ok_or_else(|| {
+            syn::Error::new_spanned(
+                &variant.ident,
+                format!("Variant '{}' needs a #[tomplate(\"template\", ...)] attribute", variant.ident),
+            )
+        })?;
+        if let Some(duplicate) = attrs.next() {
+            return Err(syn::Error::new_spanned(duplicate, "Duplicate #[tomplate(...)] attribute"));
+        }
+        
+        let call = parser::parse_template_args(attr.meta.require_list()?.tokens.clone())?;
+        let value = block::render_call(&call)?;
+        
+        // `{ .. }` matches unit, tuple and struct variants alike
+        let ident = &variant.ident;
+        arms.push(quote! { Self::#ident { .. } => #value });
+    }
+    
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Returns the rendered template for this variant.
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
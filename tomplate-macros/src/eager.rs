@@ -1,69 +1,309 @@
-use proc_macro2::{TokenStream, TokenTree, Group, Ident};
+use proc_macro2::{Delimiter, TokenStream, TokenTree, Group, Ident, Spacing, Span};
 use quote::quote;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use syn::parse::Parser;
 
+/// Configuration for eager expansion
+#[derive(Default)]
+pub struct EagerConfig {
+    /// Project-local macros treated as aliases of `tomplate!`
+    aliases: HashSet<String>,
+}
+
+impl EagerConfig {
+    /// Build the configuration from the environment and any leading
+    /// `#[expand(...)]` attributes, returning it with the remaining input.
+    ///
+    /// Aliases are read from the comma-separated `TOMPLATE_EAGER_MACROS`
+    /// variable set by `tomplate-build`, and from attributes such as
+    /// `#[expand(my_query, my_other_query)]`.
+    pub fn from_input(input: TokenStream) -> syn::Result<(Self, TokenStream)> {
+        let mut config = EagerConfig::from_env();
+        
+        let tokens: Vec<TokenTree> = input.into_iter().collect();
+        let mut index = 0;
+        while let (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(attr))) =
+            (tokens.get(index), tokens.get(index + 1))
+        {
+            if pound.as_char() != '#' || attr.delimiter() != Delimiter::Bracket {
+                break;
+            }
+            let Ok(meta) = syn::parse2::<syn::Meta>(attr.stream()) else {
+                break;
+            };
+            if !meta.path().is_ident("expand") {
+                break;
+            }
+            
+            config.add_aliases(&meta)?;
+            index += 2;
+        }
+        
+        Ok((config, tokens.into_iter().skip(index).collect()))
+    }
+    
+    /// Build the configuration for the `#[eager(...)]` attribute form, whose
+    /// arguments may contain `expand(...)` lists.
+    pub fn from_attribute_args(args: TokenStream) -> syn::Result<Self> {
+        let mut config = EagerConfig::from_env();
+        
+        let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
+        for meta in parser.parse2(args)? {
+            if !meta.path().is_ident("expand") {
+                return Err(syn::Error::new_spanned(
+                    meta.path(),
+                    "Expected `expand(...)`",
+                ));
+            }
+            config.add_aliases(&meta)?;
+        }
+        
+        Ok(config)
+    }
+    
+    /// Read aliases from the `TOMPLATE_EAGER_MACROS` environment variable
+    fn from_env() -> Self {
+        let mut config = EagerConfig::default();
+        
+        if let Ok(names) = std::env::var("TOMPLATE_EAGER_MACROS") {
+            config.aliases.extend(
+                names.split(',').map(str::trim).filter(|name| !name.is_empty()).map(String::from),
+            );
+        }
+        
+        config
+    }
+    
+    /// Add the macro names listed in an `expand(...)` meta item
+    fn add_aliases(&mut self, meta: &syn::Meta) -> syn::Result<()> {
+        let names = meta.require_list()?.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+        )?;
+        for name in names {
+            let ident = name
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .ok_or_else(|| syn::Error::new_spanned(&name, "Expected macro name"))?;
+            self.aliases.insert(ident);
+        }
+        
+        Ok(())
+    }
+}
+
 /// Process a TokenStream, eagerly evaluating tomplate! and concat! macros
-pub fn process_eager(input: TokenStream) -> syn::Result<TokenStream> {
+pub fn process_eager(input: TokenStream, config: &EagerConfig) -> syn::Result<TokenStream> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
     let mut output = TokenStream::new();
-    let mut tokens = input.into_iter().peekable();
+    let mut index = 0;
     
-    while let Some(token) = tokens.next() {
-        match token {
-            // Check for macro invocations
-            TokenTree::Ident(ident) if is_evaluatable_macro(&ident) => {
-                // Peek at the next token to see if it's a macro invocation
-                if let Some(TokenTree::Punct(punct)) = tokens.peek() {
-                    if punct.as_char() == '!' {
-                        // Consume the '!'
-                        tokens.next();
-                        
-                        // Next should be the macro arguments in a Group
-                        if let Some(TokenTree::Group(group)) = tokens.next() {
-                            // Process the macro invocation
-                            let result = evaluate_macro(&ident, group)?;
-                            output.extend(result);
-                        } else {
-                            // Not a macro invocation, restore tokens
-                            output.extend(quote! { #ident ! });
-                        }
-                    } else {
-                        // Not a macro invocation
-                        output.extend(quote! { #ident });
-                    }
-                } else {
-                    // Not a macro invocation
-                    output.extend(quote! { #ident });
-                }
+    while index < tokens.len() {
+        // Check for macro invocations, including path-qualified ones
+        if let Some((name, args, next)) = match_macro_call(&tokens, index, config) {
+            if is_format_macro(&name) {
+                // Keep the invocation, folding literal arguments into the format string
+                output.extend(tokens[index..next - 1].iter().cloned());
+                let args = evaluate_format(&name, args, config)?;
+                output.extend(std::iter::once(TokenTree::Group(args)));
+            } else {
+                // Point downstream diagnostics at the original invocation
+                let span = name.span().join(args.span()).unwrap_or_else(|| args.span());
+                let result = evaluate_macro(&name, args, config)?;
+                output.extend(respan(result, span));
             }
+            index = next;
+            continue;
+        }
+        
+        match &tokens[index] {
             // Recursively process groups
             TokenTree::Group(group) => {
-                let processed = process_eager(group.stream())?;
-                let new_group = Group::new(group.delimiter(), processed);
+                let processed = process_eager(group.stream(), config)?;
+                let mut new_group = Group::new(group.delimiter(), processed);
+                new_group.set_span(group.span());
                 output.extend(std::iter::once(TokenTree::Group(new_group)));
             }
             // Pass through other tokens unchanged
             other => {
-                output.extend(std::iter::once(other));
+                output.extend(std::iter::once(other.clone()));
             }
         }
+        index += 1;
     }
     
     Ok(output)
 }
 
+/// sqlx macros whose query is logged for offline data checks
+const SQLX_MACROS: &[&str] = &[
+    "query",
+    "query_as",
+    "query_scalar",
+    "query_unchecked",
+    "query_as_unchecked",
+    "query_scalar_unchecked",
+];
+
+/// Append the query strings of sqlx macro calls in the expanded output to the
+/// log named by `TOMPLATE_SQLX_LOG`, one `[call site, query]` JSON array per
+/// line
+pub fn record_sqlx_queries(output: &TokenStream) {
+    let Ok(path) = std::env::var("TOMPLATE_SQLX_LOG") else {
+        return;
+    };
+    
+    let mut queries = Vec::new();
+    collect_sqlx_queries(output.clone(), &mut queries);
+    if queries.is_empty() {
+        return;
+    }
+    
+    let span = proc_macro::Span::call_site();
+    let mut entries = String::new();
+    for (index, query) in queries.iter().enumerate() {
+        let site = format!("{}:{}:{}", span.file(), span.line(), index);
+        if let Ok(entry) = serde_json::to_string(&(site, query)) {
+            entries.push_str(&entry);
+            entries.push('\n');
+        }
+    }
+    if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+        let _ = std::io::Write::write_all(&mut file, entries.as_bytes());
+    }
+}
+
+/// Collect the first string literal argument of each sqlx macro call
+fn collect_sqlx_queries(tokens: TokenStream, queries: &mut Vec<String>) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (index, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Ident(ident) if SQLX_MACROS.contains(&ident.to_string().as_str()) => {
+                if let (Some(TokenTree::Punct(bang)), Some(TokenTree::Group(args))) =
+                    (tokens.get(index + 1), tokens.get(index + 2))
+                    && bang.as_char() == '!'
+                    && let Some(query) = args.stream().into_iter().find_map(|token| match token {
+                        TokenTree::Literal(lit) => syn::parse2::<syn::LitStr>(TokenTree::Literal(lit).into()).ok(),
+                        _ => None,
+                    })
+                {
+                    queries.push(query.value());
+                }
+            }
+            TokenTree::Group(group) => collect_sqlx_queries(group.stream(), queries),
+            _ => {}
+        }
+    }
+}
+
+/// Wrap the output in a block when it is a single expression, so the macro
+/// evaluates to that expression's value in expression position, including
+/// bodies ending in `?` or `.await`.
+///
+/// Bare macro calls are left alone since they may expand to items.
+pub fn wrap_expression(output: TokenStream) -> TokenStream {
+    match syn::parse2::<syn::Expr>(output.clone()) {
+        Ok(syn::Expr::Macro(_)) | Err(_) => output,
+        Ok(_) => quote! { { #output } },
+    }
+}
+
+/// Give every token of an expansion the span of the invocation it replaces
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                token = TokenTree::Group(Group::new(group.delimiter(), respan(group.stream(), span)));
+            }
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+/// Match an invocation of an evaluatable macro starting at `start`.
+///
+/// Accepts bare and path-qualified forms such as `tomplate!(...)`,
+/// `tomplate::tomplate!(...)`, `crate::tomplate!(...)` or `::core::concat!(...)`,
+/// matching on the last path segment. Returns the macro name, its arguments,
+/// and the index of the first token after the invocation.
+fn match_macro_call(
+    tokens: &[TokenTree],
+    start: usize,
+    config: &EagerConfig,
+) -> Option<(Ident, Group, usize)> {
+    let mut index = start;
+    
+    // Optional leading `::`
+    if is_path_separator(tokens, index) {
+        index += 2;
+    }
+    
+    // Path segments separated by `::`
+    let name = loop {
+        let TokenTree::Ident(ident) = tokens.get(index)? else {
+            return None;
+        };
+        index += 1;
+        if is_path_separator(tokens, index) {
+            index += 2;
+        } else {
+            break ident.clone();
+        }
+    };
+    
+    if !is_evaluatable_macro(&name, config) {
+        return None;
+    }
+    
+    match (tokens.get(index), tokens.get(index + 1)) {
+        (Some(TokenTree::Punct(bang)), Some(TokenTree::Group(group))) if bang.as_char() == '!' => {
+            Some((name, group.clone(), index + 2))
+        }
+        _ => None,
+    }
+}
+
+/// Check whether the tokens at `index` form a `::` path separator
+fn is_path_separator(tokens: &[TokenTree], index: usize) -> bool {
+    matches!(
+        (tokens.get(index), tokens.get(index + 1)),
+        (Some(TokenTree::Punct(first)), Some(TokenTree::Punct(second)))
+            if first.as_char() == ':' && first.spacing() == Spacing::Joint && second.as_char() == ':'
+    )
+}
+
 /// Check if an identifier is a macro we want to evaluate
-fn is_evaluatable_macro(ident: &Ident) -> bool {
+fn is_evaluatable_macro(ident: &Ident, config: &EagerConfig) -> bool {
     let name = ident.to_string();
-    name == "tomplate" || name == "concat"
+    matches!(
+        name.as_str(),
+        "tomplate" | "concat" | "env" | "option_env" | "stringify" | "include_str"
+    ) || is_format_macro(ident) || config.aliases.contains(&name)
+}
+
+/// Check if an identifier is a std formatting macro
+fn is_format_macro(ident: &Ident) -> bool {
+    matches!(
+        ident.to_string().as_str(),
+        "format" | "format_args" | "print" | "println" | "eprint" | "eprintln" | "write" | "writeln"
+    )
 }
 
 /// Evaluate a macro invocation and return the result
-fn evaluate_macro(name: &Ident, args: Group) -> syn::Result<TokenStream> {
+fn evaluate_macro(name: &Ident, args: Group, config: &EagerConfig) -> syn::Result<TokenStream> {
     let macro_name = name.to_string();
     
     match macro_name.as_str() {
         "tomplate" => evaluate_tomplate(args.stream()),
-        "concat" => evaluate_concat(args.stream()),
+        "concat" => evaluate_concat(args.stream(), config),
+        "env" => evaluate_env(args.stream()),
+        "option_env" => evaluate_option_env(args.stream()),
+        "stringify" => evaluate_stringify(args.stream()),
+        "include_str" => evaluate_include_str(name, args.stream(), config),
+        alias if config.aliases.contains(alias) => evaluate_tomplate(args.stream()),
         _ => {
             // Should not happen due to is_evaluatable_macro check
             Ok(quote! { #name ! #args })
@@ -76,59 +316,351 @@ fn evaluate_tomplate(input: TokenStream) -> syn::Result<TokenStream> {
     // Parse the tomplate input
     let tomplate_input = syn::parse2::<crate::TomplateInput>(input)?;
     
-    // Process the template using the existing logic
-    let result = crate::process_template(tomplate_input)?;
-    
-    // The result is already a string literal token
-    Ok(result)
+    // Render to a string literal; `#[cfg]` parameters, which would need a
+    // block, are rejected since the outer macro expects a literal
+    let result = crate::invocation::render(tomplate_input)?;
+    Ok(quote! { #result })
 }
 
 /// Evaluate a concat! macro call
-fn evaluate_concat(input: TokenStream) -> syn::Result<TokenStream> {
+///
+/// Mirrors std `concat!`: string, char, integer, float and bool literals
+/// (optionally negated numbers) are concatenated as written, minus any type
+/// suffix. Other tokens are rejected, and invocations of macros that can't be
+/// evaluated eagerly leave the `concat!` call in place.
+fn evaluate_concat(input: TokenStream, config: &EagerConfig) -> syn::Result<TokenStream> {
     // First, recursively process the input to expand any nested tomplate! calls
-    let processed_input = process_eager(input)?;
+    let processed_input = process_eager(input, config)?;
     
-    let parser = |input: syn::parse::ParseStream| -> syn::Result<Vec<String>> {
-        let mut parts = Vec::new();
-        
-        while !input.is_empty() {
-            // Try to parse a string literal
-            if let Ok(lit) = input.parse::<syn::LitStr>() {
-                parts.push(lit.value());
-            } 
-            // Try to parse other literals and convert to string
-            else if let Ok(lit) = input.parse::<syn::LitInt>() {
-                parts.push(lit.to_string());
-            }
-            else if let Ok(lit) = input.parse::<syn::LitFloat>() {
-                parts.push(lit.to_string());
-            }
-            else if let Ok(lit) = input.parse::<syn::LitBool>() {
-                parts.push(lit.value.to_string());
-            }
-            else {
-                // If we can't parse as a literal, skip the token
-                // This handles cases where macros have been expanded
-                input.step(|cursor| {
-                    if let Some((_, rest)) = cursor.token_tree() {
-                        Ok(((), rest))
-                    } else {
-                        Err(cursor.error("Unexpected end of input"))
-                    }
-                })?;
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    let args = parser.parse2(processed_input.clone())?;
+    
+    // Macros such as line!() are left for the real concat! to evaluate
+    if args.iter().any(|arg| matches!(arg, syn::Expr::Macro(_))) {
+        return Ok(quote! { ::core::concat!(#processed_input) });
+    }
+    
+    let mut concatenated = String::new();
+    for arg in &args {
+        concat_literal(arg, &mut concatenated)?;
+    }
+    
+    Ok(quote! { #concatenated })
+}
+
+/// Append the text of a concat! argument
+fn concat_literal(expr: &syn::Expr, output: &mut String) -> syn::Result<()> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => match lit {
+            syn::Lit::Str(lit) => output.push_str(&lit.value()),
+            syn::Lit::Char(lit) => output.push(lit.value()),
+            syn::Lit::Int(lit) => output.push_str(lit.base10_digits()),
+            syn::Lit::Float(lit) => output.push_str(lit.base10_digits()),
+            syn::Lit::Bool(lit) => output.push_str(if lit.value { "true" } else { "false" }),
+            syn::Lit::ByteStr(_) => {
+                return Err(syn::Error::new_spanned(lit, "cannot concatenate a byte string literal"));
             }
-            
-            // Skip optional comma
-            if input.peek(syn::Token![,]) {
-                input.parse::<syn::Token![,]>()?;
+            syn::Lit::Byte(_) => {
+                return Err(syn::Error::new_spanned(lit, "cannot concatenate a byte literal"));
+            }
+            syn::Lit::CStr(_) => {
+                return Err(syn::Error::new_spanned(lit, "cannot concatenate a C string literal"));
             }
+            _ => return Err(syn::Error::new_spanned(lit, "unsupported literal in concat!")),
+        },
+        // Negative numbers, e.g. concat!(-1)
+        syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr: inner, .. })
+            if matches!(
+                &**inner,
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(_) | syn::Lit::Float(_), .. })
+            ) =>
+        {
+            output.push('-');
+            concat_literal(inner, output)?;
         }
-        
-        Ok(parts)
+        // Invisible groups around literals passed through macro_rules
+        syn::Expr::Group(group) => concat_literal(&group.expr, output)?,
+        other => return Err(syn::Error::new_spanned(other, "expected a literal")),
+    }
+    
+    Ok(())
+}
+
+/// Evaluate an env! macro call
+fn evaluate_env(input: TokenStream) -> syn::Result<TokenStream> {
+    let parser = syn::punctuated::Punctuated::<syn::LitStr, syn::Token![,]>::parse_terminated;
+    let args = parser.parse2(input.clone())?;
+    
+    let mut args = args.iter();
+    let Some(name) = args.next() else {
+        return Err(syn::Error::new_spanned(input, "env! takes 1 or 2 arguments"));
     };
+    let message = args.next();
+    if args.next().is_some() {
+        return Err(syn::Error::new_spanned(input, "env! takes 1 or 2 arguments"));
+    }
     
-    let parts = parser.parse2(processed_input)?;
-    let concatenated = parts.join("");
+    match std::env::var(name.value()) {
+        Ok(value) => Ok(quote! { #value }),
+        Err(_) => {
+            let message = message.map(|m| m.value()).unwrap_or_else(|| {
+                format!("environment variable `{}` not defined at compile time", name.value())
+            });
+            Err(syn::Error::new_spanned(name, message))
+        }
+    }
+}
+
+/// Evaluate an option_env! macro call
+fn evaluate_option_env(input: TokenStream) -> syn::Result<TokenStream> {
+    let name = syn::parse2::<syn::LitStr>(input)?;
     
-    Ok(quote! { #concatenated })
-}
\ No newline at end of file
+    match std::env::var(name.value()) {
+        Ok(value) => Ok(quote! { ::core::option::Option::Some(#value) }),
+        Err(_) => Ok(quote! { ::core::option::Option::None::<&'static str> }),
+    }
+}
+
+/// Evaluate a stringify! macro call
+fn evaluate_stringify(input: TokenStream) -> syn::Result<TokenStream> {
+    // Like std's stringify!, inner macros are not expanded
+    let text = input.to_string();
+    Ok(quote! { #text })
+}
+
+/// Evaluate an include_str! macro call
+///
+/// Relative paths are resolved against the directory of the invoking file,
+/// falling back to the crate root when the file is unknown.
+fn evaluate_include_str(name: &Ident, input: TokenStream, config: &EagerConfig) -> syn::Result<TokenStream> {
+    // Expand nested macros so paths like concat!(env!("OUT_DIR"), "/q.sql") work
+    let processed_input = process_eager(input, config)?;
+    let path = syn::parse2::<syn::LitStr>(processed_input)?;
+    
+    let relative = PathBuf::from(path.value());
+    let resolved = if relative.is_absolute() {
+        relative
+    } else {
+        let base = name
+            .span()
+            .unwrap()
+            .local_file()
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+            .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+            .unwrap_or_default();
+        base.join(relative)
+    };
+    
+    let contents = std::fs::read_to_string(&resolved).map_err(|e| {
+        syn::Error::new_spanned(&path, format!("couldn't read `{}`: {}", resolved.display(), e))
+    })?;
+    // The string is inlined, so rustc no longer sees the file
+    tomplate_engines::templates::track_file(&resolved);
+    Ok(quote! { #contents })
+}
+
+/// A piece of a parsed format string
+enum FormatPiece {
+    /// Literal text, with `{{` and `}}` already unescaped
+    Text(String),
+    /// A `{arg:spec}` placeholder
+    Placeholder { arg: FormatArg, spec: String },
+}
+
+/// The argument a format placeholder refers to
+enum FormatArg {
+    Positional(usize),
+    Named(String),
+}
+
+/// Parse a format string into pieces.
+///
+/// Returns `None` for malformed strings and for specs that consume extra
+/// arguments (`{:1$}`, `{:.*}`), which are left for the real macro to handle.
+fn parse_format_string(format: &str) -> Option<Vec<FormatPiece>> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut next_positional = 0;
+    let mut chars = format.chars().peekable();
+    
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        ch => inner.push(ch),
+                    }
+                }
+                
+                let (arg, spec) = match inner.split_once(':') {
+                    Some((arg, spec)) => (arg.trim(), spec.to_string()),
+                    None => (inner.trim(), String::new()),
+                };
+                if spec.contains('$') || spec.contains('*') {
+                    return None;
+                }
+                
+                let arg = if arg.is_empty() {
+                    next_positional += 1;
+                    FormatArg::Positional(next_positional - 1)
+                } else if let Ok(index) = arg.parse::<usize>() {
+                    FormatArg::Positional(index)
+                } else {
+                    FormatArg::Named(arg.to_string())
+                };
+                
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(FormatPiece::Placeholder { arg, spec });
+            }
+            '}' => return None,
+            ch => text.push(ch),
+        }
+    }
+    
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+    Some(pieces)
+}
+
+/// Evaluate the arguments of a format!-style macro call
+///
+/// Nested `tomplate!` calls are expanded, and every string literal argument
+/// used by a placeholder without a format spec is folded into the format
+/// string. Runtime arguments are left alone and renumbered as needed.
+fn evaluate_format(name: &Ident, args: Group, config: &EagerConfig) -> syn::Result<Group> {
+    let processed_input = process_eager(args.stream(), config)?;
+    let with_stream = |stream: TokenStream| {
+        let mut group = Group::new(args.delimiter(), stream);
+        group.set_span(args.span());
+        group
+    };
+    
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    let exprs: Vec<syn::Expr> = parser.parse2(processed_input.clone())?.into_iter().collect();
+    
+    // write!/writeln! take a destination before the format string
+    let takes_destination = matches!(name.to_string().as_str(), "write" | "writeln");
+    let format_index = usize::from(takes_destination);
+    let Some(format) = exprs.get(format_index).and_then(string_literal) else {
+        return Ok(with_stream(processed_input));
+    };
+    let Some(pieces) = parse_format_string(&format.value()) else {
+        return Ok(with_stream(processed_input));
+    };
+    
+    // Split the remaining arguments into positional and named ones
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    for expr in exprs.iter().skip(format_index + 1) {
+        match expr {
+            syn::Expr::Assign(assign) => match &*assign.left {
+                syn::Expr::Path(path) if path.path.get_ident().is_some() => {
+                    named.push((path.path.segments[0].ident.clone(), (*assign.right).clone()));
+                }
+                _ => return Ok(with_stream(processed_input)),
+            },
+            other => positional.push(other.clone()),
+        }
+    }
+    
+    let lookup = |arg: &FormatArg| match arg {
+        FormatArg::Positional(index) => positional.get(*index),
+        FormatArg::Named(arg_name) => named
+            .iter()
+            .find(|(ident, _)| ident == arg_name)
+            .map(|(_, expr)| expr),
+    };
+    
+    // Decide which placeholders are inlined, and which arguments stay in use
+    let inlined: Vec<Option<String>> = pieces
+        .iter()
+        .map(|piece| match piece {
+            FormatPiece::Placeholder { arg, spec } if spec.is_empty() => {
+                lookup(arg).and_then(string_literal).map(|lit| lit.value())
+            }
+            _ => None,
+        })
+        .collect();
+    let mut used_positional = vec![false; positional.len()];
+    let mut used_named = HashSet::new();
+    for (piece, inline) in pieces.iter().zip(&inlined) {
+        if let (FormatPiece::Placeholder { arg, .. }, None) = (piece, inline) {
+            match arg {
+                FormatArg::Positional(index) => {
+                    if let Some(used) = used_positional.get_mut(*index) {
+                        *used = true;
+                    }
+                }
+                FormatArg::Named(arg_name) => {
+                    used_named.insert(arg_name.clone());
+                }
+            }
+        }
+    }
+    
+    // Renumber the positional arguments that are still referenced
+    let mut new_index = Vec::with_capacity(positional.len());
+    let mut kept = Vec::new();
+    for (expr, used) in positional.iter().zip(&used_positional) {
+        new_index.push(kept.len());
+        if *used {
+            kept.push(expr);
+        }
+    }
+    
+    let mut new_format = String::new();
+    for (piece, inline) in pieces.iter().zip(&inlined) {
+        match (piece, inline) {
+            (_, Some(text)) | (FormatPiece::Text(text), None) => {
+                new_format.push_str(&text.replace('{', "{{").replace('}', "}}"));
+            }
+            (FormatPiece::Placeholder { arg, spec }, None) => {
+                new_format.push('{');
+                match arg {
+                    FormatArg::Positional(index) => {
+                        let index = new_index.get(*index).copied().unwrap_or(*index);
+                        new_format.push_str(&index.to_string());
+                    }
+                    FormatArg::Named(arg_name) => new_format.push_str(arg_name),
+                }
+                if !spec.is_empty() {
+                    new_format.push(':');
+                    new_format.push_str(spec);
+                }
+                new_format.push('}');
+            }
+        }
+    }
+    
+    let format = syn::LitStr::new(&new_format, format.span());
+    let named_args = named
+        .iter()
+        .filter(|(ident, _)| used_named.contains(&ident.to_string()))
+        .map(|(ident, expr)| quote! { #ident = #expr });
+    let destination = exprs.first().filter(|_| takes_destination).map(|dest| quote! { #dest, });
+    
+    Ok(with_stream(quote! {
+        #destination #format #(, #kept)* #(, #named_args)*
+    }))
+}
+
+/// Get the string literal of an expression, if it is one
+fn string_literal(expr: &syn::Expr) -> Option<&syn::LitStr> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Some(lit),
+        _ => None,
+    }
+}
@@ -0,0 +1,165 @@
+//! Functions that fill runtime arguments into templates rendered at compile
+//! time, e.g. `tomplate_fn!(pub fn select_user)` or a `fn` statement in a
+//! composition block.
+//!
+//! The template is rendered with a unique marker for each parameter, then
+//! split at the markers into a `format!` call. Rendering checks the template
+//! and parameter names at compile time, while the values are supplied at
+//! runtime.
+
+use crate::engines::{self, Escape, Options};
+use crate::templates;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use serde_json::Value;
+use std::collections::HashMap;
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Ident, LitStr, Result, Token, Visibility,
+};
+
+/// Delimits the marker standing in for a parameter during rendering
+pub const MARKER: char = '\u{1}';
+
+/// A `tomplate_fn!` invocation: `#[attrs] vis fn name = "template";`
+pub struct TemplateFn {
+    pub attrs: Vec<Attribute>,
+    pub vis: Visibility,
+    pub name: Ident,
+    /// Registry template, defaulting to the function name
+    pub template: Option<LitStr>,
+}
+
+impl Parse for TemplateFn {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse::<Visibility>()?;
+        input.parse::<Token![fn]>()?;
+        let name = input.parse::<Ident>()?;
+        
+        let template = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse::<LitStr>()?)
+        } else {
+            None
+        };
+        
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        }
+        if !input.is_empty() {
+            return Err(input.error("Expected `fn name` or `fn name = \"template\"`"));
+        }
+        
+        Ok(TemplateFn { attrs, vis, name, template })
+    }
+}
+
+/// Process a `tomplate_fn!` invocation into a function taking the template's
+/// declared params
+pub fn process_template_fn(spec: TemplateFn) -> Result<TokenStream> {
+    let (template_name, span) = match &spec.template {
+        Some(template) => (template.value(), template.span()),
+        None => (spec.name.to_string(), spec.name.span()),
+    };
+    
+    let template = templates::get(&template_name).ok_or_else(|| {
+        syn::Error::new(span, format!("Template not found in registry: {}", template_name))
+    })?;
+    templates::record_usage(&template_name);
+    
+    let params = template
+        .params
+        .iter()
+        .map(|param| match syn::parse_str::<Ident>(param) {
+            Ok(_) => Ok(Ident::new(param, span)),
+            Err(_) => Err(syn::Error::new(
+                span,
+                format!("Param '{}' is not a valid argument name", param),
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    let engine = template.engine.as_deref().unwrap_or("simple");
+    let options = Options::from_template(&template);
+    check_escape(&options, span)?;
+    
+    let markers = params
+        .iter()
+        .map(|param| (param.to_string(), Value::String(marker(&param.to_string()))))
+        .collect::<HashMap<_, _>>();
+    let rendered = engines::process(engine, &template.template, &markers, &options)
+        .map_err(|e| syn::Error::new(span, e))?;
+    let body = format_body(&rendered, &params, span)?;
+    
+    Ok(generate(&spec.attrs, &spec.vis, &spec.name, &params, body))
+}
+
+/// Reject escape modes, since escaping would have to happen at runtime,
+/// which plain `format!` can't do
+pub fn check_escape(options: &Options, span: Span) -> Result<()> {
+    if options.escaping() != Escape::None {
+        return Err(syn::Error::new(
+            span,
+            format!("Template functions don't support the '{}' escape mode", options.escaping()),
+        ));
+    }
+    Ok(())
+}
+
+/// Turn a template rendered with parameter markers into a `format!`
+/// expression over the parameters
+pub fn format_body(rendered: &str, params: &[Ident], span: Span) -> Result<TokenStream> {
+    // Split the output at the markers, escaping braces in the literal text
+    let mut format = String::with_capacity(rendered.len());
+    let mut used = Vec::new();
+    let mut rest = rendered;
+    while let Some(start) = rest.find(MARKER) {
+        format.push_str(&rest[..start].replace('{', "{{").replace('}', "}}"));
+        let after = &rest[start + 1..];
+        let param = after
+            .find(MARKER)
+            .map(|end| &after[..end])
+            .and_then(|name| params.iter().find(|param| *param == name))
+            .ok_or_else(|| {
+                syn::Error::new(
+                    span,
+                    "A parameter is transformed by the template, so it can't be supplied at runtime",
+                )
+            })?;
+        
+        format.push('{');
+        format.push_str(&param.to_string());
+        format.push('}');
+        if !used.contains(param) {
+            used.push(param.clone());
+        }
+        rest = &after[param.to_string().len() + 1..];
+    }
+    format.push_str(&rest.replace('{', "{{").replace('}', "}}"));
+    
+    let format = LitStr::new(&format, span);
+    Ok(quote! { ::std::format!(#format, #(#used = #used),*) })
+}
+
+/// Generate `fn name(a: &str, b: &str) -> String { body }`
+pub fn generate(
+    attrs: &[Attribute],
+    vis: &Visibility,
+    name: &Ident,
+    params: &[Ident],
+    body: TokenStream,
+) -> TokenStream {
+    quote! {
+        #(#attrs)*
+        #[allow(unused_variables)]
+        #vis fn #name(#(#params: &str),*) -> ::std::string::String {
+            #body
+        }
+    }
+}
+
+/// The marker standing in for a parameter while rendering
+pub fn marker(name: &str) -> String {
+    format!("{MARKER}{name}{MARKER}")
+}
@@ -0,0 +1,60 @@
+//! `tomplate_join!`, rendering several templates into one literal.
+
+use crate::TomplateInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, LitStr, Result, Token,
+};
+use tomplate_engines::invocation;
+
+/// Arguments of a `tomplate_join!`
+pub struct JoinSpec {
+    separator: LitStr,
+    /// The calls to render, in order
+    pieces: Vec<TomplateInput>,
+}
+
+impl Parse for JoinSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let separator = input.parse()?;
+        let mut pieces = Vec::new();
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            for piece in Punctuated::<Expr, Token![,]>::parse_terminated(input)? {
+                pieces.push(parse_piece(piece)?);
+            }
+        }
+        Ok(JoinSpec { separator, pieces })
+    }
+}
+
+/// Parse a piece: a `tomplate!` call, or a template name or inline
+/// template without parameters
+fn parse_piece(piece: Expr) -> Result<TomplateInput> {
+    match piece {
+        Expr::Macro(mac) if mac.mac.path.segments.last().is_some_and(|segment| segment.ident == "tomplate") => {
+            mac.mac.parse_body()
+        }
+        Expr::Lit(_) => syn::parse2(quote! { #piece }),
+        _ => Err(syn::Error::new_spanned(
+            piece,
+            "Expected a tomplate! call or a template name",
+        )),
+    }
+}
+
+/// Render each piece and join the outputs with the separator
+///
+/// Errors point at the piece that failed.
+pub fn process_join(spec: JoinSpec) -> Result<TokenStream> {
+    let rendered = spec
+        .pieces
+        .into_iter()
+        .map(invocation::render)
+        .collect::<Result<Vec<_>>>()?;
+    let joined = rendered.join(&spec.separator.value());
+    Ok(quote! { #joined })
+}
@@ -35,7 +35,7 @@
 //! ```rust,ignore
 //! tomplate! {
 //!     // Local variables (not exported)
-//!     let common_fields = tomplate!("id, name, email");
+//!     let common_fields = "id, name, email";
 //!     let active_filter = tomplate!("status = 'active'");
 //!     
 //!     // Exported constants (available outside block)
@@ -52,6 +52,36 @@
 //! }
 //! ```
 //!
+//! Blocks can also generate an enum with one variant per template, along with
+//! `as_str()` and `name()` accessors:
+//!
+//! ```rust,ignore
+//! tomplate! {
+//!     #[derive(Debug, Clone, Copy)]
+//!     pub enum Queries {
+//!         GetUser = tomplate!("SELECT * FROM users WHERE id = $1"),
+//!         CountUsers = tomplate!("SELECT COUNT(*) FROM users"),
+//!     }
+//! }
+//!
+//! assert_eq!(Queries::CountUsers.name(), "CountUsers");
+//! ```
+//!
+//! `fn` statements generate functions instead of constants. The template is
+//! rendered at compile time, and the parameters are filled in at runtime:
+//!
+//! ```rust,ignore
+//! tomplate! {
+//!     let columns = "id, name";
+//!     pub fn find_by(column) = tomplate!(
+//!         "SELECT {columns} FROM users WHERE {column} = $1",
+//!         columns = columns
+//!     );
+//! }
+//!
+//! assert_eq!(find_by("email"), "SELECT id, name FROM users WHERE email = $1");
+//! ```
+//!
 //! ### `tomplate_eager!` - Eager Macro Expansion
 //!
 //! Eagerly expands nested `tomplate!` and `concat!` macros before passing to outer macros:
@@ -94,9 +124,30 @@
 //! Templates accept various parameter types:
 //!
 //! - **String literals**: `"value"`
-//! - **Numbers**: `42`, `3.14`
+//! - **Numbers**: `42`, `3.14`, `-1`
 //! - **Booleans**: `true`, `false`
+//! - **Lists**: `["id", "name"]`, joined with `, ` by the simple engine and
+//!   passed as arrays to the other engines
+//! - **Structured values**: `json!({"name": "Alice", "roles": ["admin"]})`,
+//!   or JSON and TOML text given as `json!("...")` and `toml!("...")`
+//! - **Snippet files**: `file("snippets/footer.sql")`, the file's contents,
+//!   read relative to the registry file defining the template so the call
+//!   site doesn't matter. The build script reruns when the file changes.
 //! - **Nested templates**: `tomplate!("other_template", ...)`
+//! - **Template references**: `@user_fields`, shorthand for
+//!   `tomplate!("user_fields")` when the template takes no parameters
+//! - **Transforms**: `upper(...)`, `lower(...)`, `snake_case(...)`,
+//!   `kebab_case(...)`, `camel_case(...)`, `pascal_case(...)` and
+//!   `screaming_snake_case(...)` around a literal, a list, another transform
+//!   or a bare identifier, which stands for its own name, so
+//!   `name = snake_case(MY_IDENT_STR)` passes `"my_ident_str"`. Inside
+//!   `tomplate_block!`, an identifier bound by `let` stands for its value.
+//!
+//! Numbers and booleans keep their type when passed to the Handlebars, Tera
+//! and MiniJinja engines, so `{{#if enabled}}` is false for `enabled = false`
+//! and comparisons like `count > 5` work as expected. Structured values become
+//! objects the engines can loop over, and the simple engine can reach into
+//! them with dotted placeholders such as `{user.name}` or `{user.roles.0}`.
 //!
 //! ```rust,ignore
 //! const EXAMPLE: &str = tomplate!("template_name",
@@ -112,10 +163,25 @@
 //!
 //! Templates can use different engines based on the `engine` field in TOML:
 //!
-//! - **simple** (default): Basic `{variable}` substitution
+//! - **simple** (default): Basic `{variable}` substitution, with built-in
+//!   filters such as `{name|upper}`, `{table|snake}` or `{cols|join:, }`
 //! - **handlebars**: Full Handlebars with conditionals, loops, helpers
 //! - **tera**: Jinja2-like with filters and control structures
 //! - **minijinja**: Lightweight Jinja2 implementation
+//! - **fluent**: Localized messages with plural and select rules, rendered
+//!   for the template's `locale` or a call's `#[locale = "..."]`
+//!
+//! All engines but fluent share the SQL quoting helpers `sql_str`,
+//! `sql_ident` and `sql_in`, e.g. `{name|sql_str}`, `{{sql_ident table}}` or
+//! `{{ ids | sql_in }}`, the `json` helper that escapes a value for use
+//! inside a JSON string literal and the `shell` helper that quotes it as
+//! POSIX shell words.
+//!
+//! Handlebars, Tera and MiniJinja templates select plural forms by the CLDR
+//! rules of their `locale`, e.g. `{{plural count one="item" other="items"}}`
+//! or `{{ count | plural(one="item", other="items") }}`, and format numbers
+//! and dates for it with `format_number`, `currency` and `format_date`, e.g.
+//! `{{ price | currency("EUR") }}`.
 //!
 //! The engine is determined at build time from the template definition.
 //!
@@ -130,16 +196,25 @@
 //!
 //! This ensures zero runtime overhead and compile-time validation of templates.
 
+mod assert;
 mod block;
+mod derive;
 mod eager;
-mod engines;
-mod parser;
+mod function;
+mod join;
+mod locales;
+#[cfg(feature = "phf")]
+mod map;
 mod scope;
-mod templates;
+mod select;
+mod testing;
+
+use tomplate_engines::invocation::{self, TomplateInput};
+use tomplate_engines::{engines, parser, profile, templates, validate};
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{punctuated::Punctuated, Expr, Lit, Token, ExprMacro};
+use tomplate_build::types::Placeholders;
 
 /// Process templates at compile time with zero runtime overhead.
 ///
@@ -183,11 +258,56 @@ use syn::{punctuated::Punctuated, Expr, Lit, Token, ExprMacro};
 /// println!("{}", USER_FIELDS);
 /// ```
 ///
+/// ## Expression Block
+///
+/// Use helper bindings when only a single resulting string is needed:
+///
+/// ```rust,ignore
+/// const QUERY: &str = tomplate!({
+///     let fields = "id, name";
+///     tomplate!("SELECT {fields} FROM users", fields = fields)
+/// });
+/// ```
+///
 /// ## Parameters
 ///
-/// - First argument: Template name (from registry) or inline template string
+/// - First argument: Template name (from registry) or inline template string,
+///   which may also be built with `concat!`, `env!` and `stringify!`, e.g.
+///   `concat!("get_user_", env!("DB_DIALECT"))` to pick a template by an
+///   environment variable set for the compiler
 /// - Named parameters: `key = value` pairs for template variables
-/// - Values can be literals or nested `tomplate!` calls
+/// - Parameters marked `#[cfg(...)]`, e.g. `#[cfg(feature = "audit")]
+///   audit_cols = "created_by,"`, are passed only when the predicate holds;
+///   the call then expands to a block rather than a literal
+/// - Dotted names like `user.name = "Alice"` fill `{user.name}` placeholders,
+///   and become nested objects for the Handlebars, Tera and MiniJinja engines
+/// - Values can be literals, lists of literals such as `["id", "name"]`,
+///   structured `json!({...})` / `toml!("...")` values, snippet files read
+///   with `file("path")` relative to the template's registry file, or nested
+///   `tomplate!` calls, written `@name` for templates without parameters
+/// - Values can be wrapped in case transforms applied at expansion time,
+///   e.g. `table = upper("users")` or `name = snake_case(MY_IDENT_STR)`
+/// - Leading attributes set rendering options: `#[indent]` re-indents
+///   multi-line values to the column of their placeholder, `#[dedent]`
+///   strips the template's common indentation, `#[trim]` trims the output,
+///   `#[partial]` keeps placeholders that have no parameter and `#[strict]`
+///   rejects undefined variables in Handlebars and MiniJinja templates, while
+///   `#[locale = "de"]` picks the locale for the Fluent engine and
+///   `#[escape = "html"]`, `#[escape = "shell"]` or `#[escape = "none"]`
+///   overrides the template's escaping.
+///   `#[postprocess = "markdown"]` converts the output from Markdown to HTML.
+///   `#[trim_blocks]`, `#[lstrip_blocks]` and `#[keep_trailing_newline]`
+///   control whitespace around Tera and MiniJinja block tags
+/// - Output modifiers before the name, as in `tomplate!(dedent trim
+///   "long_template", ...)`, apply to the rendered output however the
+///   template is written: `dedent` strips the indentation common to its
+///   lines and `trim` its surrounding whitespace
+/// - In blocks, `let` bindings may also be plain strings used verbatim
+/// - In blocks, `let` bindings may carry `#[cfg(...)]` attributes; consts that
+///   use them are emitted once per cfg combination
+/// - In blocks, `#[link_section = ".name"]` on a `const` export emits a
+///   `static NAME: [u8; N]` holding the rendered bytes in that linker
+///   section instead, e.g. for configuration blobs in firmware
 ///
 /// ## Template Resolution
 ///
@@ -216,6 +336,18 @@ use syn::{punctuated::Punctuated, Expr, Lit, Token, ExprMacro};
 /// ```
 #[proc_macro]
 pub fn tomplate(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate!");
+    // A single braced group is an expression block producing one value
+    if is_expression_block(&input) {
+        return match syn::parse::<parser::ExpressionBlock>(input) {
+            Ok(block) => match block::process_expression_block(block) {
+                Ok(output) => output.into(),
+                Err(err) => err.to_compile_error().into(),
+            },
+            Err(err) => err.to_compile_error().into(),
+        };
+    }
+    
     // Try to parse as a composition block first
     let input_clone = input.clone();
     match syn::parse::<parser::CompositionBlock>(input_clone) {
@@ -244,152 +376,586 @@ pub fn tomplate(input: TokenStream) -> TokenStream {
     }
 }
 
-struct TomplateInput {
-    template_name: String,
-    params: Vec<(String, ParamValue)>,
+/// Check whether the macro input is a single `{ ... }` group
+fn is_expression_block(input: &TokenStream) -> bool {
+    let mut tokens = proc_macro2::TokenStream::from(input.clone()).into_iter();
+    matches!(
+        tokens.next(),
+        Some(proc_macro2::TokenTree::Group(group)) if group.delimiter() == proc_macro2::Delimiter::Brace
+    ) && tokens.next().is_none()
 }
 
-enum ParamValue {
-    Literal(String),
-    Macro(ExprMacro),
+fn process_template(input: TomplateInput) -> syn::Result<proc_macro2::TokenStream> {
+    // `#[cfg(...)]` parameters make one constant per combination, of which
+    // the compiler keeps the one whose cfg holds. Render errors only surface
+    // if their combination is enabled.
+    if !input.conditional.is_empty() {
+        let items = invocation::render_variants(input)?.into_iter().map(|(predicate, result)| {
+            let value = match result {
+                Ok(value) => quote! { #value },
+                Err(err) => err.to_compile_error(),
+            };
+            quote! {
+                #[cfg(#predicate)]
+                const RENDERED: &str = #value;
+            }
+        });
+        return Ok(quote! {
+            {
+                #(#items)*
+                RENDERED
+            }
+        });
+    }
+    
+    let processed = invocation::render(input)?;
+    
+    // Return the processed template as a string literal
+    Ok(quote! {
+        #processed
+    })
 }
 
-impl syn::parse::Parse for TomplateInput {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        // Parse template name
-        let template_name = match input.parse::<Expr>()? {
-            Expr::Lit(lit) => match lit.lit {
-                Lit::Str(s) => s.value(),
-                _ => return Err(syn::Error::new_spanned(lit, "Expected string literal")),
-            },
-            _ => return Err(input.error("Expected template name as string literal")),
-        };
-        
-        let mut params = Vec::new();
-        
-        // Parse optional parameters
-        if input.peek(Token![,]) {
-            input.parse::<Token![,]>()?;
-            
-            let args = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
-            
-            for arg in args {
-                match arg {
-                    Expr::Assign(assign) => {
-                        // Extract parameter name
-                        let param_name = match &*assign.left {
-                            Expr::Path(path) if path.path.segments.len() == 1 => {
-                                path.path.segments[0].ident.to_string()
-                            }
-                            _ => {
-                                return Err(syn::Error::new_spanned(
-                                    assign.left,
-                                    "Expected simple identifier",
-                                ))
-                            }
-                        };
-                        
-                        // Extract parameter value (literal or macro)
-                        let param_value = match &*assign.right {
-                            Expr::Lit(lit) => match &lit.lit {
-                                Lit::Str(s) => ParamValue::Literal(s.value()),
-                                Lit::Int(i) => ParamValue::Literal(i.to_string()),
-                                Lit::Float(f) => ParamValue::Literal(f.to_string()),
-                                Lit::Bool(b) => ParamValue::Literal(b.value.to_string()),
-                                _ => {
-                                    return Err(syn::Error::new_spanned(
-                                        lit,
-                                        "Unsupported literal type",
-                                    ))
-                                }
-                            },
-                            Expr::Macro(macro_expr) => {
-                                // Check if it's a tomplate! macro call
-                                if let Some(ident) = macro_expr.mac.path.get_ident() {
-                                    if ident == "tomplate" {
-                                        ParamValue::Macro(macro_expr.clone())
-                                    } else {
-                                        return Err(syn::Error::new_spanned(
-                                            macro_expr,
-                                            "Only tomplate! macro calls are supported in parameters",
-                                        ))
-                                    }
-                                } else {
-                                    return Err(syn::Error::new_spanned(
-                                        macro_expr,
-                                        "Expected tomplate! macro call",
-                                    ))
-                                }
-                            },
-                            _ => {
-                                return Err(syn::Error::new_spanned(
-                                    assign.right,
-                                    "Expected literal value or tomplate! macro call",
-                                ))
-                            }
-                        };
-                        
-                        params.push((param_name, param_value));
-                    }
-                    _ => {
-                        return Err(syn::Error::new_spanned(
-                            arg,
-                            "Expected key = value syntax",
-                        ))
-                    }
-                }
-            }
+/// Count the bind placeholders in a rendered SQL template.
+///
+/// Takes the same arguments as a direct `tomplate!` call and expands to a
+/// `usize` literal: the highest `$n` placeholder, the number of `?`
+/// placeholders or the number of distinct `:name` placeholders. Fails
+/// compilation if `$n` placeholders skip a number or styles are mixed, so the
+/// count can't disagree with the query.
+///
+/// ```rust,ignore
+/// const GET_USER: &str = tomplate!("select_user", fields = "*", condition = "id = $1 AND org = $2");
+/// const GET_USER_BINDS: usize = tomplate_binds!("select_user", fields = "*", condition = "id = $1 AND org = $2");
+/// ```
+#[proc_macro]
+pub fn tomplate_binds(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_binds!");
+    let input = match syn::parse::<TomplateInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let span = input.span;
+    
+    match invocation::render(input)
+        .and_then(|sql| validate::bind_count(&sql).map_err(|e| syn::Error::new(span, e)))
+    {
+        Ok(count) => {
+            let count = proc_macro2::Literal::usize_suffixed(count);
+            quote! { #count }.into()
         }
-        
-        Ok(TomplateInput {
-            template_name,
-            params,
-        })
+        Err(err) => err.to_compile_error().into(),
     }
 }
 
-fn process_template(input: TomplateInput) -> syn::Result<proc_macro2::TokenStream> {
-    // Get a clone of the cached templates
-    let templates = templates::load_templates();
+/// Render a template and expand to its output compressed.
+///
+/// Takes the same arguments as a direct `tomplate!` call and expands to a
+/// `tomplate::compress::Compressed` holding the output in the LZ4 block
+/// format.
+///
+/// Requires the `compress` feature.
+///
+/// ```rust,ignore
+/// const PAGE: tomplate::compress::Compressed = tomplate_compress!("index_page", title = "Status");
+/// ```
+#[cfg(feature = "compress")]
+#[proc_macro]
+pub fn tomplate_compress(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_compress!");
+    let input = match syn::parse::<TomplateInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    match invocation::render(input) {
+        Ok(output) => {
+            let compressed = lz4_flex::block::compress(output.as_bytes());
+            let bytes = proc_macro2::Literal::byte_string(&compressed);
+            let len = output.len();
+            quote! { ::tomplate::compress::Compressed::new(#bytes, #len) }.into()
+        }
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Render a template whose output is Rust code and expand to that code.
+///
+/// Takes the same arguments as a direct `tomplate!` call. The files generated
+/// by `Builder::render_to_rust` consist of a call to this macro, and like any
+/// code made of items must be included at item level rather than in a
+/// function body.
+///
+/// ```rust,ignore
+/// tomplate_rust! { "status_codes" }
+/// ```
+#[proc_macro]
+pub fn tomplate_rust(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_rust!");
+    let input = match syn::parse::<TomplateInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let span = input.span;
+    let name = input.template_name.clone();
     
-    // Try to find the template in registry, or use as inline template
-    let (template_string, engine_name) = if let Some(template) = templates.get(&input.template_name) {
-        // Found in registry
-        (template.template.clone(), template.engine.as_deref().unwrap_or("simple"))
-    } else {
-        // Not in registry, treat as inline template
-        (input.template_name.clone(), "simple")
+    match invocation::render(input).and_then(|source| {
+        source.parse::<proc_macro2::TokenStream>().map_err(|e| {
+            syn::Error::new(span, format!("Template '{}' did not render valid Rust: {}", name, e))
+        })
+    }) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Render a SQL template in the placeholder style of each SeaORM backend.
+///
+/// Takes the same arguments as a direct `tomplate!` call and expands to a
+/// `tomplate::sea::Sql` with `$n` placeholders for PostgreSQL and `?` for
+/// MySQL and SQLite.
+///
+/// ```rust,ignore
+/// const FIND_USER: tomplate::sea::Sql = tomplate_sea!("select_user", fields = "*", condition = "id = $1");
+/// let statement = FIND_USER.statement(backend, [id.into()]);
+/// ```
+#[proc_macro]
+pub fn tomplate_sea(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_sea!");
+    let input = match syn::parse::<TomplateInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let render = |style| {
+        let mut input = input.clone();
+        input.options.placeholders = Some(style);
+        invocation::render(input)
     };
     
-    // Process parameters, expanding any nested macros
-    let mut params = std::collections::HashMap::new();
-    for (key, value) in input.params {
-        let expanded_value = match value {
-            ParamValue::Literal(s) => s,
-            ParamValue::Macro(macro_expr) => {
-                // Recursively expand the nested tomplate! macro
-                let tokens = macro_expr.mac.tokens.clone();
-                let nested_input = syn::parse2::<TomplateInput>(tokens)?;
-                let nested_result = process_template(nested_input)?;
-                
-                // Extract the string literal from the nested result
-                // The nested result is a quote! { "string" }, so we need to extract the string
-                let token_string = nested_result.to_string();
-                // Remove the quotes from the token string
-                token_string.trim_matches('"').to_string()
+    match (render(Placeholders::Postgres), render(Placeholders::Mysql)) {
+        (Ok(postgres), Ok(positional)) => quote! {
+            ::tomplate::sea::Sql {
+                postgres: #postgres,
+                positional: #positional,
             }
-        };
-        params.insert(key, expanded_value);
+        }
+        .into(),
+        (Err(err), _) | (_, Err(err)) => err.to_compile_error().into(),
+    }
+}
+
+/// Fail compilation unless a rendered template satisfies a predicate.
+///
+/// The predicate is built from `contains("...")`, `starts_with("...")`,
+/// `ends_with("...")` and `matches("regex")`, combined with `!`, `&&` and
+/// `||`. The remaining arguments are a `tomplate!` call. Expands to nothing.
+///
+/// ```rust,ignore
+/// tomplate_assert!(starts_with("SELECT") && !contains(";"), "select_user", fields = "*", condition = "id = $1");
+/// ```
+#[proc_macro]
+pub fn tomplate_assert(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_assert!");
+    match syn::parse::<assert::AssertSpec>(input) {
+        Ok(spec) => match assert::process_assert(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Fail compilation unless a rendered template equals `expected`.
+///
+/// Takes the same arguments as a `tomplate!` call plus an `expected = "..."`
+/// parameter, which is not passed to the template. Expands to nothing.
+///
+/// ```rust,ignore
+/// tomplate_assert_eq!("greeting", name = "World", expected = "Hello World!");
+/// ```
+#[proc_macro]
+pub fn tomplate_assert_eq(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_assert_eq!");
+    match parser::parse_template_args(input.into()) {
+        Ok(call) => match assert::process_assert_eq(call) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Render the template chosen by the enabled features.
+///
+/// Arms map a feature name, or `cfg(...)` with any cfg predicate, to a
+/// template and are tried in order; `_` matches when no other arm does. The
+/// parameters after `;` are passed to whichever template is chosen. Every
+/// template is rendered, and the expansion keeps the one whose cfg holds.
+///
+/// ```rust,ignore
+/// const GET_USER: &str = tomplate_select!(
+///     postgres => "get_user_pg",
+///     sqlite => "get_user_sqlite",
+///     _ => "get_user_generic";
+///     fields = "id, name"
+/// );
+/// ```
+#[proc_macro]
+pub fn tomplate_select(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_select!");
+    match syn::parse::<select::SelectSpec>(input) {
+        Ok(spec) => match select::process_select(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Render several templates and join them with a separator.
+///
+/// The first argument is the separator, the rest are `tomplate!` calls or
+/// template names, rendered in order. Expands to one string literal, and a
+/// failing piece reports its error at that piece.
+///
+/// ```rust,ignore
+/// const REPORT: &str = tomplate_join!(" UNION ALL ",
+///     tomplate!("active_users", since = "2024-01-01"),
+///     tomplate!("invited_users", since = "2024-01-01"),
+///     "admin_users",
+/// );
+/// ```
+#[proc_macro]
+pub fn tomplate_join(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_join!");
+    match syn::parse::<join::JoinSpec>(input) {
+        Ok(spec) => match join::process_join(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Generate one constant per combination of parameter values.
+///
+/// The first argument is a const name pattern, the second a `tomplate!` call,
+/// followed by one or more axes of literal values. Axes are available to the
+/// template as variables. In the name pattern, values are upper-cased and any
+/// character that is not alphanumeric is replaced by `_`.
+///
+/// ```rust,ignore
+/// tomplate_matrix!(
+///     "COUNT_{dialect}_{table}",
+///     tomplate!("SELECT COUNT(*) FROM {table} -- {dialect}", table = table, dialect = dialect),
+///     dialect = ["postgres", "sqlite"],
+///     table = ["users", "posts"],
+/// );
+///
+/// // Generates COUNT_POSTGRES_USERS, COUNT_POSTGRES_POSTS,
+/// // COUNT_SQLITE_USERS and COUNT_SQLITE_POSTS
+/// ```
+///
+/// The same form can be used as a statement inside a composition block, where
+/// the template may also reference the block's `let` bindings.
+#[proc_macro]
+pub fn tomplate_matrix(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_matrix!");
+    match syn::parse::<parser::MatrixSpec>(input) {
+        Ok(spec) => match block::process_matrix(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Generate code for every registry template matching a glob or prefix.
+///
+/// Each matching template is rendered without parameters. With a const name
+/// pattern, one constant is generated per template, where `{name}` is the
+/// template name upper-cased with any character that is not alphanumeric
+/// replaced by `_`:
+///
+/// ```rust,ignore
+/// tomplate_for_each!("migrations/*", "MIGRATION_{name}");
+///
+/// // Generates MIGRATION_MIGRATIONS_001_CREATE_USERS, ...
+/// ```
+///
+/// With a closure-like body, the body is repeated for each template, with
+/// its two parameters replaced by the template name and rendered output as
+/// string literals, wherever those identifiers appear in it. A bracketed
+/// body collects the repetitions into an array:
+///
+/// ```rust,ignore
+/// const MIGRATIONS: &[(&str, &str)] =
+///     &tomplate_for_each!("migrations/*", |name, sql| [(name, sql)]);
+///
+/// fn register_reports(registry: &mut Reports) {
+///     tomplate_for_each!("report_", |title, body| {
+///         registry.add(title, body);
+///     });
+/// }
+/// ```
+///
+/// Templates are visited in sorted name order.
+#[proc_macro]
+pub fn tomplate_for_each(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_for_each!");
+    match syn::parse::<parser::ForEachSpec>(input) {
+        Ok(spec) => match block::process_for_each(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Embed the template registry for runtime access.
+///
+/// Expands to a `&'static tomplate::runtime::Registry` parsed on first use
+/// from the amalgamated registries, which are embedded with `include_str!`.
+/// Requires the `runtime` feature of `tomplate`.
+///
+/// ```rust,ignore
+/// for name in tomplate::registry!().names() {
+///     println!("{name}");
+/// }
+/// ```
+#[proc_macro]
+pub fn registry(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "registry! takes no arguments")
+            .to_compile_error()
+            .into();
     }
     
-    // Process the template with the appropriate engine
-    let processed = engines::process(engine_name, &template_string, &params)
-        .map_err(|e| syn::Error::new(proc_macro2::Span::call_site(), e))?;
+    let Ok(path) = std::env::var("TOMPLATE_TEMPLATES_PATH") else {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "TOMPLATE_TEMPLATES_PATH not set. Make sure you have a build.rs that uses tomplate-build",
+        )
+        .to_compile_error()
+        .into();
+    };
     
-    // Return the processed template as a string literal
-    Ok(quote! {
-        #processed
-    })
+    let paths = std::env::split_paths(&path).map(|path| path.display().to_string());
+    quote! {
+        {
+            static REGISTRY: ::std::sync::LazyLock<::tomplate::runtime::Registry> =
+                ::std::sync::LazyLock::new(|| {
+                    ::tomplate::runtime::Registry::parse(&[#(include_str!(#paths)),*])
+                });
+            &*REGISTRY
+        }
+    }
+    .into()
+}
+
+/// Derive `as_str()` for an enum whose variants map to templates.
+///
+/// Each variant carries a `#[tomplate(...)]` attribute taking the same
+/// arguments as `tomplate!`. The templates are rendered at compile time and
+/// `as_str()` returns the rendered string for a variant:
+///
+/// ```rust,ignore
+/// #[derive(Tomplate)]
+/// enum Query {
+///     #[tomplate("select_user", fields = "id, name", condition = "id = $1")]
+///     UserById,
+///     #[tomplate("SELECT COUNT(*) FROM {table}", table = "users")]
+///     CountUsers,
+/// }
+///
+/// assert_eq!(Query::CountUsers.as_str(), "SELECT COUNT(*) FROM users");
+/// ```
+///
+/// Variants may have fields, which are ignored.
+#[proc_macro_derive(Tomplate, attributes(tomplate))]
+pub fn derive_tomplate(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("derive(Tomplate)");
+    match syn::parse::<syn::DeriveInput>(input) {
+        Ok(input) => match derive::derive_tomplate(input) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Fill an inline module with a constant for every matching registry template.
+///
+/// Templates are selected by name prefix or glob and rendered without
+/// parameters. With `prefix`, const names are the template names without the
+/// prefix; with `pattern`, they are the full names. Either way names are
+/// upper-cased with any character that is not alphanumeric replaced by `_`.
+///
+/// ```rust,ignore
+/// #[tomplate::consts(prefix = "email_")]
+/// pub mod emails {}
+///
+/// // [email_welcome] becomes emails::WELCOME
+/// println!("{}", emails::WELCOME);
+///
+/// #[tomplate::consts(pattern = "migrations/*")]
+/// mod migrations {}
+/// ```
+///
+/// Items already in the module are kept.
+#[proc_macro_attribute]
+pub fn consts(args: TokenStream, item: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("#[consts]");
+    match block::process_consts(args.into(), item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Fill an inline module with a string catalog, one module per locale.
+///
+/// Registry templates named `<prefix><locale>/<key>` are rendered without
+/// parameters, for their locale unless they set their own `locale`, and
+/// become consts in a module per locale. The module also gets a `Locale`
+/// enum, a `Message` enum with a variant per key, and `get(locale, message)`.
+/// Every locale must have every message, unless `fallback` names a locale
+/// whose messages stand in for missing ones.
+///
+/// ```rust,ignore
+/// // [i18n/en/greeting], [i18n/de/greeting], ...
+/// #[tomplate::locales(prefix = "i18n/", fallback = "en")]
+/// pub mod i18n {}
+///
+/// assert_eq!(i18n::de::GREETING, "Hallo!");
+/// assert_eq!(i18n::get(i18n::Locale::De, i18n::Message::Greeting), "Hallo!");
+/// ```
+#[proc_macro_attribute]
+pub fn locales(args: TokenStream, item: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("#[locales]");
+    match locales::process_locales(args.into(), item.into()) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Build a perfect-hash map from names to rendered templates.
+///
+/// Expands to a `tomplate::phf::Map<&'static str, &'static str>` whose hash
+/// is computed at compile time, for lookups by name without a hand-written
+/// `match`. Given a glob or prefix, the map holds every matching registry
+/// template rendered without parameters; given entries, it holds each
+/// `tomplate!` call under its key:
+///
+/// ```rust,ignore
+/// static MIGRATIONS: tomplate::phf::Map<&str, &str> = tomplate_map!("migrations/*");
+///
+/// static QUERIES: tomplate::phf::Map<&str, &str> = tomplate_map! {
+///     "user" => tomplate!("select_user", fields = "*", condition = "id = $1"),
+///     "count" => tomplate!("SELECT COUNT(*) FROM users"),
+/// };
+///
+/// assert_eq!(QUERIES.get("count"), Some(&"SELECT COUNT(*) FROM users"));
+/// ```
+///
+/// Requires the `phf` feature.
+#[cfg(feature = "phf")]
+#[proc_macro]
+pub fn tomplate_map(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_map!");
+    match syn::parse::<map::MapSpec>(input) {
+        Ok(spec) => match map::process_map(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Embed the registry's templates for rendering with runtime parameters in
+/// tests.
+///
+/// Expands to a `&'static tomplate::testing::Templates`. Each template is
+/// rendered with markers for its declared `params`, as for `tomplate_fn!`.
+///
+/// ```rust,ignore
+/// let templates = tomplate::testing::templates!();
+/// assert_eq!(templates.render("greeting", &[("name", "World")]).unwrap(), "Hello World!");
+/// ```
+#[proc_macro]
+pub fn test_templates(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("templates!");
+    if !input.is_empty() {
+        return syn::Error::new(proc_macro2::Span::call_site(), "templates! takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    testing::process_templates().into()
+}
+
+/// Generate a function that fills runtime arguments into a registry template.
+///
+/// The template must declare its parameters with `params = [...]`, which
+/// become `&str` arguments in the same order. The template is rendered at
+/// compile time, so its structure and parameter names are checked as usual,
+/// and the function body is a single `format!` call:
+///
+/// ```toml
+/// [select_user]
+/// template = "SELECT {fields} FROM users WHERE {condition}"
+/// params = ["fields", "condition"]
+/// ```
+///
+/// ```rust,ignore
+/// tomplate_fn!(pub fn select_user);
+/// tomplate_fn!(fn find_user = "select_user");
+///
+/// assert_eq!(select_user("id", "id = 1"), "SELECT id FROM users WHERE id = 1");
+/// ```
+///
+/// Arguments are inserted as text, so parameters must be used verbatim:
+/// filters applied to them or escape modes on the template are rejected.
+#[proc_macro]
+pub fn tomplate_fn(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_fn!");
+    match syn::parse::<function::TemplateFn>(input) {
+        Ok(spec) => match function::process_template_fn(spec) {
+            Ok(output) => output.into(),
+            Err(err) => err.to_compile_error().into(),
+        },
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// List the registry template names matching a glob or prefix.
+///
+/// Expands to a `&'static [&'static str]` of the matching names in sorted
+/// order. A pattern containing `*`, `?` or `[` is a glob, anything else
+/// matches names starting with it.
+///
+/// ```rust,ignore
+/// const MIGRATIONS: &[&str] = tomplate_names!("migrations/*");
+///
+/// for name in tomplate_names!("report_") {
+///     println!("{name}");
+/// }
+/// ```
+#[proc_macro]
+pub fn tomplate_names(input: TokenStream) -> TokenStream {
+    let pattern = match syn::parse::<syn::LitStr>(input) {
+        Ok(pattern) => pattern,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    
+    match templates::matching_names(&pattern.value()) {
+        Ok(names) => quote! {
+            {
+                const NAMES: &[&str] = &[#(#names),*];
+                NAMES
+            }
+        }
+        .into(),
+        Err(e) => syn::Error::new(pattern.span(), e).to_compile_error().into(),
+    }
 }
 
 /// Eagerly expand `tomplate!` and `concat!` macros within a token stream.
@@ -427,6 +993,28 @@ fn process_template(input: TomplateInput) -> syn::Result<proc_macro2::TokenStrea
 ///
 /// - `tomplate!` - Expands template macros
 /// - `concat!` - Expands string concatenation
+/// - `env!` / `option_env!` - Read compile-time environment variables
+/// - `stringify!` - Converts tokens to a string literal
+/// - `include_str!` - Reads a file relative to the invoking source file
+/// - `format!`, `println!`, `write!` and friends - String literal arguments,
+///   including expanded `tomplate!` calls, are folded into the format string
+///   while runtime arguments are left alone
+///
+/// Path-qualified invocations such as `tomplate::tomplate!(...)` or
+/// `core::concat!(...)` are recognized by their last path segment.
+///
+/// Project-local wrappers around `tomplate!` can be expanded too, either by
+/// listing them in a leading `#[expand(...)]` attribute or through
+/// `Builder::eager_macros` in the build script:
+///
+/// ```rust,ignore
+/// tomplate_eager! {
+///     #[expand(query_template)]
+///     sqlx::query!(query_template!("select_user", id = "5"))
+///         .fetch_one(&pool)
+///         .await?
+/// }
+/// ```
 ///
 /// ## Examples
 ///
@@ -475,6 +1063,18 @@ fn process_template(input: TomplateInput) -> syn::Result<proc_macro2::TokenStrea
 /// }
 /// ```
 ///
+/// ### In Expression Position
+///
+/// When the body is a single expression, the macro evaluates to its value:
+///
+/// ```rust,ignore
+/// let user = tomplate_eager!(
+///     sqlx::query!(tomplate!("select_user", id = "5"))
+///         .fetch_one(&pool)
+///         .await?
+/// );
+/// ```
+///
 /// ## How It Works
 ///
 /// 1. Recursively walks through the provided token stream
@@ -486,10 +1086,53 @@ fn process_template(input: TomplateInput) -> syn::Result<proc_macro2::TokenStrea
 /// All processing happens at compile time with zero runtime overhead.
 #[proc_macro]
 pub fn tomplate_eager(input: TokenStream) -> TokenStream {
+    let _profile = profile::expansion("tomplate_eager!");
     let input = proc_macro2::TokenStream::from(input);
     
-    match eager::process_eager(input) {
+    let result = eager::EagerConfig::from_input(input)
+        .and_then(|(config, body)| eager::process_eager(body, &config))
+        .inspect(eager::record_sqlx_queries)
+        .map(eager::wrap_expression);
+    match result {
         Ok(output) => output.into(),
         Err(err) => err.to_compile_error().into(),
     }
-}
\ No newline at end of file
+}
+
+/// Attribute form of [`tomplate_eager!`](macro@tomplate_eager).
+///
+/// Applies the same eager expansion to the body of the annotated item, which
+/// can be a function, an impl block, a module, or any other item. Unlike
+/// wrapping code in `tomplate_eager! { ... }`, this keeps the item readable to
+/// rustfmt and IDE tooling.
+///
+/// A function-like macro and an attribute macro cannot share a name, so the
+/// attribute is exported as `eager` and used as `#[tomplate::eager]`.
+///
+/// ```rust,ignore
+/// #[tomplate::eager]
+/// async fn find_user(pool: &PgPool, id: i32) -> sqlx::Result<User> {
+///     sqlx::query_as!(User, tomplate!("select_user", id = "$1"), id)
+///         .fetch_one(pool)
+///         .await
+/// }
+///
+/// // Wrapper macros can be listed as in the function-like form
+/// #[tomplate::eager(expand(query_template))]
+/// mod queries {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn eager(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = proc_macro2::TokenStream::from(args);
+    let item = proc_macro2::TokenStream::from(item);
+    
+    let result = eager::EagerConfig::from_attribute_args(args)
+        .and_then(|config| eager::process_eager(item, &config))
+        .inspect(eager::record_sqlx_queries);
+    match result {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
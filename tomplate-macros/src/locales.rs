@@ -0,0 +1,207 @@
+//! `#[tomplate::locales]`, a string catalog with one module per locale.
+
+use crate::block::{self, const_name_part};
+use crate::engines::{filters, Options};
+use crate::parser::{TemplateCall, TemplateSource};
+use crate::templates;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{Expr, ExprLit, Ident, Lit, LitStr, Result};
+
+/// Arguments of `#[tomplate::locales(...)]`
+struct LocalesArgs {
+    /// Prefix of the catalog's template names, e.g. `"i18n/"`
+    prefix: LitStr,
+    /// Locale whose messages stand in for missing translations
+    fallback: Option<LitStr>,
+}
+
+fn parse_args(args: TokenStream) -> Result<LocalesArgs> {
+    let parser = syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated;
+    let mut prefix = None;
+    let mut fallback = None;
+    for meta in syn::parse::Parser::parse2(parser, args)? {
+        let meta = meta.require_name_value()?;
+        let Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) = &meta.value else {
+            return Err(syn::Error::new_spanned(&meta.value, "Expected a string literal"));
+        };
+        match meta.path.require_ident()?.to_string().as_str() {
+            "prefix" => prefix = Some(value.clone()),
+            "fallback" => fallback = Some(value.clone()),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &meta.path,
+                    "Expected `prefix = \"...\"` or `fallback = \"...\"`",
+                ))
+            }
+        }
+    }
+    let prefix = prefix.ok_or_else(|| {
+        syn::Error::new(proc_macro2::Span::call_site(), "Expected `prefix = \"...\"`")
+    })?;
+    Ok(LocalesArgs { prefix, fallback })
+}
+
+/// Process a `#[tomplate::locales(prefix = "...")]` module
+///
+/// Templates named `<prefix><locale>/<key>` are rendered without parameters,
+/// for their locale unless they set one, and become `pub const`s in a module
+/// per locale. A `Locale` and a `Message` enum and a `get` function look
+/// messages up by locale.
+pub fn process_locales(args: TokenStream, item: TokenStream) -> Result<TokenStream> {
+    let args = parse_args(args)?;
+    let prefix = args.prefix.value();
+
+    let mut module = syn::parse2::<syn::ItemMod>(item)?;
+    let Some((_, items)) = &mut module.content else {
+        return Err(syn::Error::new_spanned(
+            &module,
+            "#[tomplate::locales] needs an inline module, e.g. `mod i18n {}`",
+        ));
+    };
+
+    // Rendered messages by locale, then by key
+    let mut catalog: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let names = templates::matching_names(&prefix).map_err(|e| syn::Error::new_spanned(&args.prefix, e))?;
+    for name in names {
+        let Some((locale, key)) = name[prefix.len()..].split_once('/') else {
+            continue;
+        };
+        let mut options = Options::default();
+        if templates::get(&name).is_some_and(|template| template.locale.is_none()) {
+            options.locale = Some(locale.to_string());
+        }
+        let call = TemplateCall {
+            source: TemplateSource::Name(name.clone()),
+            params: Vec::new(),
+            options,
+            span: args.prefix.span(),
+        };
+        let value = block::render_call(&call)
+            .map_err(|e| syn::Error::new_spanned(&args.prefix, format!("In template '{}': {}", name, e)))?;
+        catalog.entry(locale.to_string()).or_default().insert(key.to_string(), value);
+    }
+    if catalog.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &args.prefix,
+            format!("No templates named '{}<locale>/<key>' found", prefix),
+        ));
+    }
+
+    let keys: BTreeSet<&String> = catalog.values().flat_map(BTreeMap::keys).collect();
+    let fallback = match &args.fallback {
+        Some(fallback) if !catalog.contains_key(&fallback.value()) => {
+            return Err(syn::Error::new_spanned(
+                fallback,
+                format!("No templates for the fallback locale '{}'", fallback.value()),
+            ));
+        }
+        Some(fallback) => Some(fallback.value()),
+        None => None,
+    };
+
+    let ident = |text: String, kind: &str| {
+        syn::parse_str::<Ident>(&text)
+            .map_err(|_| syn::Error::new_spanned(&args.prefix, format!("'{}' is not a valid {} name", text, kind)))
+    };
+
+    let mut locale_variants = Vec::new();
+    let mut code_arms = Vec::new();
+    let mut from_code_arms = Vec::new();
+    let mut get_arms = Vec::new();
+    for (locale, messages) in &catalog {
+        let module_name = ident(filters::words(locale).join("_").to_lowercase(), "module")?;
+        let variant = ident(filters::camel_case(locale, true), "variant")?;
+
+        let mut consts = Vec::new();
+        for (key, value) in messages {
+            let const_name = ident(const_name_part(key), "const")?;
+            let template = format!("{}{}/{}", prefix, locale, key);
+            consts.push(quote! {
+                #[doc = concat!("Rendered `", #template, "` template.")]
+                pub const #const_name: &str = #value;
+            });
+        }
+        items.push(syn::parse_quote! {
+            #[doc = concat!("Messages for the `", #locale, "` locale.")]
+            pub mod #module_name {
+                #(#consts)*
+            }
+        });
+
+        for key in &keys {
+            let message = ident(filters::camel_case(key, true), "variant")?;
+            let const_name = ident(const_name_part(key), "const")?;
+            let source = if messages.contains_key(*key) {
+                module_name.clone()
+            } else if let Some(fallback) = &fallback {
+                ident(filters::words(fallback).join("_").to_lowercase(), "module")?
+            } else {
+                return Err(syn::Error::new_spanned(
+                    &args.prefix,
+                    format!(
+                        "Message '{}' is missing for locale '{}'; add a template named '{}{}/{}' or set `fallback`",
+                        key, locale, prefix, locale, key
+                    ),
+                ));
+            };
+            get_arms.push(quote! { (Locale::#variant, Message::#message) => #source::#const_name });
+        }
+
+        locale_variants.push(variant.clone());
+        code_arms.push(quote! { Locale::#variant => #locale });
+        from_code_arms.push(quote! { #locale => Some(Locale::#variant) });
+    }
+
+    let message_variants = keys
+        .iter()
+        .map(|key| ident(filters::camel_case(key, true), "variant"))
+        .collect::<Result<Vec<_>>>()?;
+
+    items.push(syn::parse_quote! {
+        /// A locale of the catalog.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Locale {
+            #(#locale_variants,)*
+        }
+    });
+    items.push(syn::parse_quote! {
+        impl Locale {
+            /// Every locale of the catalog.
+            pub const ALL: &'static [Locale] = &[#(Locale::#locale_variants),*];
+
+            /// Returns the locale's code, e.g. `"de"`.
+            pub const fn code(self) -> &'static str {
+                match self {
+                    #(#code_arms,)*
+                }
+            }
+
+            /// Returns the locale with the given code, if the catalog has it.
+            pub fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    #(#from_code_arms,)*
+                    _ => None,
+                }
+            }
+        }
+    });
+    items.push(syn::parse_quote! {
+        /// A message of the catalog.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Message {
+            #(#message_variants,)*
+        }
+    });
+    items.push(syn::parse_quote! {
+        /// Returns a message in a locale.
+        pub const fn get(locale: Locale, message: Message) -> &'static str {
+            match (locale, message) {
+                #(#get_arms,)*
+            }
+        }
+    });
+
+    Ok(quote! { #module })
+}
@@ -0,0 +1,76 @@
+//! `tomplate_map!`, a perfect-hash map from names to rendered templates.
+
+use crate::{block, parser};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    ExprMacro, LitStr, Result, Token,
+};
+
+/// Entries of a `tomplate_map!`
+pub enum MapSpec {
+    /// Every registry template matching a glob or prefix, by its name
+    Pattern(LitStr),
+    /// Explicit `"key" => tomplate!(...)` entries
+    Entries(Vec<(LitStr, ExprMacro)>),
+}
+
+impl Parse for MapSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(LitStr) && !input.peek2(Token![=>]) {
+            let pattern = input.parse::<LitStr>()?;
+            if !input.is_empty() {
+                return Err(input.error("Expected a single pattern or `\"key\" => tomplate!(...)` entries"));
+            }
+            return Ok(MapSpec::Pattern(pattern));
+        }
+        
+        let entries = Punctuated::<(LitStr, ExprMacro), Token![,]>::parse_terminated_with(input, |input| {
+            let key = input.parse::<LitStr>()?;
+            input.parse::<Token![=>]>()?;
+            Ok((key, input.parse::<ExprMacro>()?))
+        })?;
+        Ok(MapSpec::Entries(entries.into_iter().collect()))
+    }
+}
+
+/// Render the entries and generate a `phf::Map` literal over them
+pub fn process_map(spec: MapSpec) -> Result<TokenStream> {
+    let entries: Vec<(String, String)> = match spec {
+        MapSpec::Pattern(pattern) => block::render_matching(&pattern)?,
+        MapSpec::Entries(entries) => {
+            let mut rendered = Vec::with_capacity(entries.len());
+            for (key, mac) in entries {
+                if !mac.mac.path.is_ident("tomplate") {
+                    return Err(syn::Error::new_spanned(mac, "Expected 'tomplate!' macro call"));
+                }
+                if rendered.iter().any(|(existing, _)| *existing == key.value()) {
+                    return Err(syn::Error::new_spanned(key, "Duplicate key"));
+                }
+                let call = parser::parse_template_args(mac.mac.tokens)?;
+                rendered.push((key.value(), block::render_call(&call)?));
+            }
+            rendered
+        }
+    };
+    
+    let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    let state = phf_generator::generate_hash(&keys);
+    
+    let key = state.key;
+    let disps = state.disps.iter().map(|(d1, d2)| quote! { (#d1, #d2) });
+    let entries = state.map.iter().map(|&index| {
+        let (key, value) = &entries[index];
+        quote! { (#key, #value) }
+    });
+    
+    Ok(quote! {
+        ::tomplate::phf::Map {
+            key: #key,
+            disps: &[#(#disps),*],
+            entries: &[#(#entries),*],
+        }
+    })
+}
@@ -1,14 +1,40 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::quote;
 use std::collections::HashMap;
-use syn::Attribute;
+use syn::{Attribute, Ident, Meta, Visibility};
+
+/// A resolved value together with the cfg predicates it depends on
+///
+/// Values that reference `#[cfg(...)]`-gated let bindings resolve to one
+/// variant per combination of those bindings.
+#[derive(Clone)]
+pub struct Variant {
+    /// Predicates from `#[cfg(...)]` attributes that must all hold
+    pub cfgs: Vec<TokenStream>,
+    /// Resolved template value
+    pub value: String,
+}
+
+impl Variant {
+    /// Create an unconditional variant
+    pub fn new(value: String) -> Self {
+        Variant {
+            cfgs: Vec::new(),
+            value,
+        }
+    }
+}
 
 /// Scope for tracking let bindings and const exports in a composition block
 pub struct Scope {
     /// Local let bindings - only visible within the block
-    locals: HashMap<String, String>,
+    locals: HashMap<String, Vec<Variant>>,
     /// Exported const declarations - visible outside the block
     exports: Vec<Export>,
+    /// Generated enums mapping variants to templates
+    enums: Vec<EnumExport>,
+    /// Generated functions taking runtime arguments
+    functions: Vec<FunctionExport>,
 }
 
 /// An exported const declaration
@@ -16,9 +42,47 @@ struct Export {
     /// Attributes like #[cfg(...)]
     attrs: Vec<Attribute>,
     /// Name of the const
-    name: String,
+    name: Ident,
     /// Resolved template value
-    value: String,
+    value: Variant,
+}
+
+/// A generated enum declaration
+struct EnumExport {
+    /// Attributes like #[derive(...)]
+    attrs: Vec<Attribute>,
+    /// Visibility of the enum
+    vis: Visibility,
+    /// Name of the enum
+    name: Ident,
+    /// Variants in declaration order
+    entries: Vec<EnumEntry>,
+}
+
+/// A generated function filling runtime arguments into a template
+struct FunctionExport {
+    /// Attributes like doc comments
+    attrs: Vec<Attribute>,
+    /// Visibility of the function
+    vis: Visibility,
+    /// Name of the function
+    name: Ident,
+    /// Parameters, each taken as `&str`
+    params: Vec<Ident>,
+    /// Predicates from cfg-gated let bindings the template references
+    cfgs: Vec<TokenStream>,
+    /// `format!` expression producing the result
+    body: TokenStream,
+}
+
+/// A variant of a generated enum
+pub struct EnumEntry {
+    /// Attributes on the variant
+    pub attrs: Vec<Attribute>,
+    /// Name of the variant
+    pub name: Ident,
+    /// Resolved template values
+    pub values: Vec<Variant>,
 }
 
 impl Scope {
@@ -27,40 +91,176 @@ impl Scope {
         Scope {
             locals: HashMap::new(),
             exports: Vec::new(),
+            enums: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+    
+    /// Create a scope sharing this scope's let bindings but none of its exports
+    pub fn child(&self) -> Self {
+        Scope {
+            locals: self.locals.clone(),
+            exports: Vec::new(),
+            enums: Vec::new(),
+            functions: Vec::new(),
         }
     }
     
-    /// Add a local let binding
-    pub fn add_local(&mut self, name: String, value: String) {
-        self.locals.insert(name, value);
+    /// Set a local binding, shadowing any earlier definitions of the same name
+    pub fn set_local(&mut self, name: String, value: Variant) {
+        self.locals.insert(name, vec![value]);
+    }
+    
+    /// Add a local let binding, keeping earlier cfg-gated variants of the same name
+    pub fn add_local(&mut self, name: String, value: Variant) {
+        self.locals.entry(name).or_default().push(value);
     }
     
-    /// Get a local binding by name
-    pub fn get_local(&self, name: &str) -> Option<&String> {
-        self.locals.get(name)
+    /// Get all variants of a local binding by name
+    pub fn get_local(&self, name: &str) -> Option<&[Variant]> {
+        self.locals.get(name).map(Vec::as_slice)
     }
     
     /// Add an exported const declaration
-    pub fn add_export(&mut self, attrs: Vec<Attribute>, name: String, value: String) {
+    pub fn add_export(&mut self, attrs: Vec<Attribute>, name: Ident, value: Variant) {
         self.exports.push(Export { attrs, name, value });
     }
     
+    /// Add a generated enum declaration
+    pub fn add_enum(&mut self, attrs: Vec<Attribute>, vis: Visibility, name: Ident, entries: Vec<EnumEntry>) {
+        self.enums.push(EnumExport { attrs, vis, name, entries });
+    }
+    
+    /// Add a generated function
+    pub fn add_function(
+        &mut self,
+        attrs: Vec<Attribute>,
+        vis: Visibility,
+        name: Ident,
+        params: Vec<Ident>,
+        cfgs: Vec<TokenStream>,
+        body: TokenStream,
+    ) {
+        self.functions.push(FunctionExport { attrs, vis, name, params, cfgs, body });
+    }
+    
     /// Generate the output TokenStream with all const declarations
     pub fn generate_output(&self) -> TokenStream {
         let mut output = TokenStream::new();
         
         for export in &self.exports {
-            let name = syn::Ident::new(&export.name, proc_macro2::Span::call_site());
-            let value = &export.value;
+            let name = &export.name;
+            let value = &export.value.value;
             let attrs = &export.attrs;
+            let cfgs = &export.value.cfgs;
+            
+            // Variants of cfg-gated let bindings carry their predicates along
+            let cfg_attr = cfg_attr(cfgs);
+            
+            // A `#[link_section]` export becomes a static holding the bytes,
+            // so the rendered data itself lands in the section
+            let (sections, attrs): (Vec<&Attribute>, Vec<&Attribute>) =
+                attrs.iter().partition(|attr| attr.path().is_ident("link_section"));
+            if let Some(section) = sections.first()
+                && let Meta::NameValue(section) = &section.meta
+            {
+                let section = &section.value;
+                let len = value.len();
+                let bytes = Literal::byte_string(value.as_bytes());
+                output.extend(quote! {
+                    #(#attrs)*
+                    #cfg_attr
+                    #[unsafe(link_section = #section)]
+                    #[used]
+                    static #name: [u8; #len] = *#bytes;
+                });
+                continue;
+            }
             
             // Generate: #[attrs] const NAME: &str = "value";
             output.extend(quote! {
                 #(#attrs)*
+                #cfg_attr
                 const #name: &str = #value;
             });
         }
         
+        for export in &self.enums {
+            let name = &export.name;
+            let vis = &export.vis;
+            let attrs = &export.attrs;
+            
+            let mut variants = Vec::new();
+            let mut str_arms = Vec::new();
+            let mut name_arms = Vec::new();
+            for entry in &export.entries {
+                let ident = &entry.name;
+                let variant_attrs = &entry.attrs;
+                let label = ident.to_string();
+                // Only cfg attributes apply to the match arms
+                let cfg_attrs: Vec<_> = variant_attrs
+                    .iter()
+                    .filter(|attr| attr.path().is_ident("cfg"))
+                    .collect();
+                
+                variants.push(quote! { #(#variant_attrs)* #ident });
+                name_arms.push(quote! { #(#cfg_attrs)* Self::#ident => #label });
+                for value in &entry.values {
+                    let cfg_attr = cfg_attr(&value.cfgs);
+                    let text = &value.value;
+                    str_arms.push(quote! { #(#cfg_attrs)* #cfg_attr Self::#ident => #text });
+                }
+            }
+            
+            // Generate: enum Name { A, B } plus as_str()/name() accessors
+            output.extend(quote! {
+                #(#attrs)*
+                #vis enum #name {
+                    #(#variants,)*
+                }
+                
+                impl #name {
+                    /// Returns the rendered template for this variant.
+                    pub const fn as_str(&self) -> &'static str {
+                        match self {
+                            #(#str_arms,)*
+                        }
+                    }
+                    
+                    /// Returns the name of this variant.
+                    pub const fn name(&self) -> &'static str {
+                        match self {
+                            #(#name_arms,)*
+                        }
+                    }
+                }
+            });
+        }
+        
+        for function in &self.functions {
+            let mut attrs = function.attrs.clone();
+            if !function.cfgs.is_empty() {
+                let cfgs = &function.cfgs;
+                attrs.push(syn::parse_quote! { #[cfg(all(#(#cfgs),*))] });
+            }
+            output.extend(crate::function::generate(
+                &attrs,
+                &function.vis,
+                &function.name,
+                &function.params,
+                function.body.clone(),
+            ));
+        }
+        
         output
     }
-}
\ No newline at end of file
+}
+
+/// Build a `#[cfg(all(...))]` attribute from predicates, or nothing if there are none
+fn cfg_attr(cfgs: &[TokenStream]) -> TokenStream {
+    if cfgs.is_empty() {
+        TokenStream::new()
+    } else {
+        quote! { #[cfg(all(#(#cfgs),*))] }
+    }
+}
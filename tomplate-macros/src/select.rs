@@ -0,0 +1,137 @@
+//! `tomplate_select!`, rendering one of several templates depending on the
+//! enabled features.
+
+use crate::TomplateInput;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Ident, LitStr, Result, Token,
+};
+use tomplate_engines::invocation;
+
+/// Arguments of a `tomplate_select!`
+pub struct SelectSpec {
+    arms: Vec<Arm>,
+    fallback: Option<LitStr>,
+    /// The parameters after `;`, passed to whichever template is selected
+    params: TokenStream,
+}
+
+/// A `condition => "template"` arm
+struct Arm {
+    /// A cfg predicate, e.g. `feature = "postgres"`
+    predicate: TokenStream,
+    template: LitStr,
+}
+
+impl Parse for SelectSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut arms = Vec::new();
+        let mut fallback = None;
+        while !input.is_empty() && !input.peek(Token![;]) {
+            if fallback.is_some() {
+                return Err(input.error("The `_` arm must be the last"));
+            }
+            if input.peek(Token![_]) {
+                input.parse::<Token![_]>()?;
+                input.parse::<Token![=>]>()?;
+                fallback = Some(input.parse()?);
+            } else {
+                let predicate = parse_condition(input)?;
+                input.parse::<Token![=>]>()?;
+                arms.push(Arm { predicate, template: input.parse()? });
+            }
+            if !input.peek(Token![,]) {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        if arms.is_empty() && fallback.is_none() {
+            return Err(input.error("Expected `feature => \"template\"` arms"));
+        }
+
+        let params = if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+            input.parse()?
+        } else if input.is_empty() {
+            TokenStream::new()
+        } else {
+            return Err(input.error("Expected `;` before the parameters"));
+        };
+        Ok(SelectSpec { arms, fallback, params })
+    }
+}
+
+/// Parse an arm's condition: a feature name, or `cfg(...)` for any other
+/// predicate
+fn parse_condition(input: ParseStream) -> Result<TokenStream> {
+    if input.peek(LitStr) {
+        let feature = input.parse::<LitStr>()?;
+        return Ok(quote! { feature = #feature });
+    }
+    let ident = input.parse::<Ident>()?;
+    if ident == "cfg" && input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in input);
+        return content.parse();
+    }
+    let feature = LitStr::new(&ident.to_string(), ident.span());
+    Ok(quote! { feature = #feature })
+}
+
+/// Render every arm's template and keep the one whose cfg holds
+///
+/// An arm applies when its condition holds and no earlier arm's does, so
+/// arms are tried in order like a `match`.
+pub fn process_select(spec: SelectSpec) -> Result<TokenStream> {
+    let mut items = Vec::new();
+    let mut earlier: Vec<&TokenStream> = Vec::new();
+    for arm in &spec.arms {
+        let value = render(&arm.template, &spec.params)?;
+        let predicate = &arm.predicate;
+        items.push(quote! {
+            #[cfg(all(#predicate, not(any(#(#earlier),*))))]
+            const SELECTED: &str = #value;
+        });
+        earlier.push(predicate);
+    }
+
+    match &spec.fallback {
+        Some(template) => {
+            let value = render(template, &spec.params)?;
+            items.push(quote! {
+                #[cfg(not(any(#(#earlier),*)))]
+                const SELECTED: &str = #value;
+            });
+        }
+        None => {
+            let names: Vec<String> = spec.arms.iter().map(|arm| arm.predicate.to_string()).collect();
+            let message = format!(
+                "tomplate_select!: no arm matches the enabled features; expected one of: {}",
+                names.join(", ")
+            );
+            items.push(quote! {
+                #[cfg(not(any(#(#earlier),*)))]
+                const SELECTED: &str = ::core::compile_error!(#message);
+            });
+        }
+    }
+
+    Ok(quote! {
+        {
+            #(#items)*
+            SELECTED
+        }
+    })
+}
+
+/// Render a template with the shared parameters, as `tomplate!` would
+fn render(template: &LitStr, params: &TokenStream) -> Result<String> {
+    let tokens = if params.is_empty() {
+        quote! { #template }
+    } else {
+        quote! { #template, #params }
+    };
+    invocation::render(syn::parse2::<TomplateInput>(tokens)?)
+}
@@ -0,0 +1,94 @@
+//! `templates!` for `tomplate::testing`, embedding every registry template
+//! in a form that can be rendered with runtime parameters.
+//!
+//! Like template functions, each template is rendered with a marker for each
+//! declared param and split at the markers, so tests render exactly what the
+//! engines produce at compile time.
+
+use crate::engines::{self, Options};
+use crate::{function, templates};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Generate a `tomplate::testing::Templates` over the registry
+pub fn process_templates() -> TokenStream {
+    let compiled = templates::names().into_iter().filter_map(|name| {
+        let template = templates::get(&name)?;
+        let params = &template.params;
+        let output = match pieces(&template.template, template.engine.as_deref(), params, &Options::from_template(&template)) {
+            Ok(pieces) => {
+                let pieces = pieces.into_iter().map(|piece| match piece {
+                    Piece::Text(text) => quote! { ::tomplate::testing::Piece::Text(#text) },
+                    Piece::Param(index) => quote! { ::tomplate::testing::Piece::Param(#index) },
+                });
+                quote! { ::core::result::Result::Ok(&[#(#pieces),*]) }
+            }
+            Err(e) => quote! { ::core::result::Result::Err(#e) },
+        };
+        Some(quote! {
+            ::tomplate::testing::Compiled {
+                name: #name,
+                params: &[#(#params),*],
+                output: #output,
+            }
+        })
+    });
+    
+    quote! {
+        {
+            static TEMPLATES: ::tomplate::testing::Templates =
+                ::tomplate::testing::Templates::new(&[#(#compiled),*]);
+            &TEMPLATES
+        }
+    }
+}
+
+/// A part of a rendered template
+enum Piece {
+    Text(String),
+    Param(usize),
+}
+
+/// Render a template with markers for its params and split it at them
+fn pieces(template: &str, engine: Option<&str>, params: &[String], options: &Options) -> Result<Vec<Piece>, String> {
+    function::check_escape(options, Span::call_site()).map_err(|e| e.to_string())?;
+    
+    let markers: Vec<String> = params.iter().map(|param| function::marker(param)).collect();
+    let values = params
+        .iter()
+        .zip(&markers)
+        .map(|(param, marker)| (param.clone(), Value::String(marker.clone())))
+        .collect::<HashMap<_, _>>();
+    let rendered = engines::process(engine.unwrap_or("simple"), template, &values, options)?;
+    
+    if rendered.matches(function::MARKER).count() != 2 * rendered_markers(&rendered, &markers) {
+        return Err("A parameter is transformed by the template, so it can't be supplied at runtime".to_string());
+    }
+    
+    let mut pieces = Vec::new();
+    let mut rest = rendered.as_str();
+    while let Some((start, index)) = markers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, marker)| rest.find(marker.as_str()).map(|start| (start, index)))
+        .min()
+    {
+        if start > 0 {
+            pieces.push(Piece::Text(rest[..start].to_string()));
+        }
+        pieces.push(Piece::Param(index));
+        rest = &rest[start + markers[index].len()..];
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest.to_string()));
+    }
+    
+    Ok(pieces)
+}
+
+/// Count the intact markers in rendered output
+fn rendered_markers(rendered: &str, markers: &[String]) -> usize {
+    markers.iter().map(|marker| rendered.matches(marker.as_str()).count()).sum()
+}
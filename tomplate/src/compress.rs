@@ -0,0 +1,115 @@
+//! Rendered templates embedded compressed.
+//!
+//! With the `compress` feature, [`tomplate_compress!`](crate::tomplate_compress)
+//! renders a template like `tomplate!` and embeds the output compressed in
+//! the LZ4 block format, for large outputs such as HTML pages or seed SQL
+//! that would otherwise grow the binary. [`Compressed::decompress_into`]
+//! restores the text into a caller-provided buffer, without allocating.
+
+/// A rendered template, compressed at compile time.
+///
+/// Obtained with [`tomplate_compress!`](crate::tomplate_compress).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// const PAGE: tomplate::compress::Compressed = tomplate::tomplate_compress!("index_page",
+///     title = "Status"
+/// );
+///
+/// let mut buf = [0u8; PAGE.len()];
+/// let html: &str = PAGE.decompress_into(&mut buf);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compressed {
+    bytes: &'static [u8],
+    len: usize,
+}
+
+impl Compressed {
+    /// Wraps compressed bytes and the length of the text they decompress to.
+    ///
+    /// Used by the code [`tomplate_compress!`](crate::tomplate_compress)
+    /// expands to.
+    #[doc(hidden)]
+    pub const fn new(bytes: &'static [u8], len: usize) -> Self {
+        Compressed { bytes, len }
+    }
+
+    /// Returns the length in bytes of the decompressed text.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the decompressed text is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the compressed bytes.
+    pub const fn as_bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+
+    /// Decompresses the text into the start of `buf` and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than [`len`](Self::len).
+    pub fn decompress_into<'a>(&self, buf: &'a mut [u8]) -> &'a str {
+        assert!(
+            buf.len() >= self.len,
+            "buffer of {} bytes is too small for {} decompressed bytes",
+            buf.len(),
+            self.len
+        );
+        let out = &mut buf[..self.len];
+        decompress_block(self.bytes, out);
+        core::str::from_utf8(out).expect("compressed template is not UTF-8")
+    }
+}
+
+/// Decode an LZ4 block into `out`, which holds exactly the decoded length
+fn decompress_block(input: &[u8], out: &mut [u8]) {
+    let mut pos = 0;
+    let mut written = 0;
+    while pos < input.len() {
+        let token = input[pos];
+        pos += 1;
+
+        // Literals, copied as they are
+        let literals = read_length(input, &mut pos, (token >> 4) as usize);
+        out[written..written + literals].copy_from_slice(&input[pos..pos + literals]);
+        pos += literals;
+        written += literals;
+
+        // The last sequence has no match
+        if pos >= input.len() {
+            break;
+        }
+
+        // A match repeats earlier output, and may overlap what it writes
+        let offset = u16::from_le_bytes([input[pos], input[pos + 1]]) as usize;
+        pos += 2;
+        let length = read_length(input, &mut pos, (token & 0x0f) as usize) + 4;
+        for _ in 0..length {
+            out[written] = out[written - offset];
+            written += 1;
+        }
+    }
+}
+
+/// Read a length whose 4-bit part is extended by bytes of 255
+fn read_length(input: &[u8], pos: &mut usize, mut length: usize) -> usize {
+    if length == 15 {
+        loop {
+            let byte = input[*pos];
+            *pos += 1;
+            length += byte as usize;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    length
+}
@@ -1,15 +1,16 @@
 #![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
 //! # Tomplate: TOML-Based Compile-Time Template Composition
 //!
 //! Tomplate is a powerful compile-time template engine for Rust that processes templates
 //! at compile time, resulting in zero runtime overhead. Templates are defined in TOML files
-//! and can use various template engines including Handlebars, Tera, and MiniJinja.
+//! and can use various template engines including Handlebars, Tera, MiniJinja, and Fluent.
 //!
 //! ## Features
 //!
 //! - **Zero Runtime Overhead**: All template processing happens at compile time
-//! - **Multiple Template Engines**: Choose from Simple, Handlebars, Tera, or MiniJinja
+//! - **Multiple Template Engines**: Choose from Simple, Handlebars, Tera, MiniJinja, or Fluent
 //! - **Composition Blocks**: Build complex templates from reusable parts
 //! - **Inline Templates**: Use template strings directly without registry
 //! - **Eager Evaluation**: Solve macro expansion order issues with `tomplate_eager!`
@@ -247,12 +248,90 @@
 //! engine = "handlebars"
 //! ```
 //!
+//! ### Validating Output
+//!
+//! A `validate` table declares checks on a template's rendered output.
+//! Output that fails them is a compile error at the call site, which catches
+//! mixed-up parameters before the code ever runs:
+//!
+//! ```toml
+//! [user_query.validate]
+//! regex = "^SELECT "
+//! format = "sql"          # Requires the `sqlparser` feature
+//! dialect = "postgres"    # Optional, generic SQL by default
+//! max_len = 4096          # Maximum length in bytes
+//! non_empty = true        # Reject empty or whitespace-only output
+//! binds = true            # Require $1..$n without gaps, no mixed styles
+//! ```
+//!
+//! `validate = "sql"` is shorthand for a table with only a format. The
+//! formats are:
+//!
+//! - `"sql"`: SQL statements, requires the `sqlparser` feature
+//! - `"json"`: A JSON document
+//! - `"yaml"`: One or more YAML documents, requires the `yaml` feature
+//! - `"toml"`: A TOML document
+//! - `"html"`, `"xml"`: Markup with balanced tags and quoted attribute values
+//! - `"graphql"`: A GraphQL query, validated against the schema set with
+//!   `Builder::graphql_schema`; requires the `graphql` feature
+//! - `"wgsl"`: A WGSL shader module, parsed and validated with naga; requires
+//!   the `naga` feature
+//!
+//! ### Bind Placeholders
+//!
+//! SQL templates can be written once and rewritten to the placeholder style
+//! of each database with `placeholders = "postgres"` (`$1`), `"mysql"` (`?`)
+//! or `"named"` (`:name`), per template or per call:
+//!
+//! ```rust,ignore
+//! const MYSQL: &str = tomplate!(#[placeholders = "mysql"] "select_user",
+//!     fields = "*",
+//!     condition = "id = $1 AND org = $2"
+//! );
+//! // SELECT * FROM users WHERE id = ? AND org = ?
+//! ```
+//!
+//! ### Post-processing
+//!
+//! `postprocess` converts the rendered output before it is validated and
+//! embedded, per template or per call with `#[postprocess = "..."]`:
+//!
+//! ```toml
+//! [help_page]
+//! template = """
+//! # {command}
+//!
+//! Usage: `{usage}`
+//! """
+//! postprocess = "markdown"  # Requires the `markdown` feature
+//! ```
+//!
+//! - `"markdown"`: Markdown to HTML, with tables, strikethrough, footnotes
+//!   and task lists
+//! - `"html-minify"`, `"css-minify"`, `"js-minify"`: Remove comments and
+//!   whitespace from web assets composed from templates, so they don't grow
+//!   the binary. Names and values are never rewritten
+//!
 //! ## Feature Flags
 //!
 //! - `build`: Enables the build-time template discovery (enabled by default)
 //! - `handlebars`: Enables Handlebars template engine
 //! - `tera`: Enables Tera template engine
 //! - `minijinja`: Enables MiniJinja template engine
+//! - `fluent`: Enables the Fluent localization engine
+//! - `handlebars-scripts`: Enables Rhai script helpers for Handlebars
+//! - `runtime`: Embeds the template registry for inspection at runtime with
+//!   [`registry!`]
+//! - `phf`: Enables perfect-hash lookup tables with [`tomplate_map!`]
+//! - `sqlparser`: Enables `validate = "sql"` syntax checks of rendered SQL
+//! - `yaml`: Enables `validate = "yaml"` checks of rendered YAML
+//! - `graphql`: Enables `validate = "graphql"` checks of rendered queries
+//! - `naga`: Enables `validate = "wgsl"` checks of rendered shaders
+//! - `markdown`: Enables `postprocess = "markdown"` conversion of rendered
+//!   Markdown to HTML
+//! - `sea-orm`: Enables [`tomplate_sea!`] for raw SeaORM statements
+//! - `testing`: Enables the [`testing`] helpers for rendering templates in
+//!   unit tests and compile tests of template calls
 
 /// The main template macro for compile-time template processing.
 ///
@@ -293,6 +372,143 @@
 /// - Nested `tomplate!` calls for composition
 pub use tomplate_macros::tomplate;
 
+/// Generates one constant per combination of parameter values.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_matrix;
+///
+/// tomplate_matrix!(
+///     "QUERY_{dialect}_{table}",
+///     tomplate!("SELECT * FROM {table} -- {dialect}", table = table, dialect = dialect),
+///     dialect = ["postgres", "sqlite"],
+///     table = ["users", "posts"],
+/// );
+///
+/// assert_eq!(QUERY_SQLITE_POSTS, "SELECT * FROM posts -- sqlite");
+/// ```
+///
+/// Axis values are upper-cased in the generated names, and characters that are
+/// not alphanumeric become `_`. The same form can be used as a statement
+/// inside a `tomplate!` composition block.
+pub use tomplate_macros::tomplate_matrix;
+
+/// Lists the registry template names matching a glob or prefix.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_names;
+///
+/// const MIGRATIONS: &[&str] = tomplate_names!("migrations/*");
+/// assert!(MIGRATIONS.contains(&"migrations/001_create_users"));
+/// ```
+///
+/// Names are sorted. A pattern containing `*`, `?` or `[` is matched as a
+/// glob, any other pattern as a name prefix.
+pub use tomplate_macros::tomplate_names;
+
+/// Generates a function that fills runtime arguments into a registry template.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_fn;
+///
+/// // [select_user]
+/// // template = "SELECT {fields} FROM users WHERE {condition}"
+/// // params = ["fields", "condition"]
+/// tomplate_fn!(pub fn select_user);
+///
+/// assert_eq!(select_user("id", "id = 1"), "SELECT id FROM users WHERE id = 1");
+/// ```
+///
+/// The template's declared `params` become `&str` arguments. It is rendered
+/// at compile time to check its structure, and the function body is a single
+/// `format!` call. `fn name = "template"` picks a template by another name.
+pub use tomplate_macros::tomplate_fn;
+
+/// Derives `as_str()` for an enum whose variants map to templates.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::Tomplate;
+///
+/// #[derive(Tomplate)]
+/// enum Query {
+///     #[tomplate("select_user", fields = "id, name", condition = "id = $1")]
+///     UserById,
+///     #[tomplate("SELECT COUNT(*) FROM {table}", table = "users")]
+///     CountUsers,
+/// }
+///
+/// assert_eq!(Query::CountUsers.as_str(), "SELECT COUNT(*) FROM users");
+/// ```
+///
+/// Each `#[tomplate(...)]` attribute takes the same arguments as
+/// [`tomplate!`], and is rendered at compile time.
+pub use tomplate_macros::Tomplate;
+
+/// Fills an inline module with a constant for every matching registry template.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// // [email_welcome] and [email_reset] become WELCOME and RESET
+/// #[tomplate::consts(prefix = "email_")]
+/// pub mod emails {}
+///
+/// assert!(emails::WELCOME.starts_with("Welcome"));
+/// ```
+///
+/// Templates are rendered without parameters. `pattern = "..."` selects
+/// templates by glob instead, keeping their full names.
+pub use tomplate_macros::consts;
+
+/// Fills an inline module with a string catalog, one module per locale.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// // [i18n/en/greeting], [i18n/de/greeting] and [i18n/en/farewell]
+/// #[tomplate::locales(prefix = "i18n/", fallback = "en")]
+/// pub mod i18n {}
+///
+/// assert_eq!(i18n::de::GREETING, "Hallo!");
+///
+/// let locale = i18n::Locale::from_code("de").unwrap_or(i18n::Locale::En);
+/// println!("{}", i18n::get(locale, i18n::Message::Farewell));
+/// ```
+///
+/// Templates named `<prefix><locale>/<key>` are rendered without parameters,
+/// for their locale, so Fluent templates pick that locale's plural rules.
+/// Each locale becomes a module of consts, and `Locale`, `Message` and
+/// `get` look messages up at runtime. A message missing in a locale fails
+/// compilation unless `fallback` names the locale to use instead.
+pub use tomplate_macros::locales;
+
+/// Generates code for every registry template matching a glob or prefix.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_for_each;
+///
+/// // One constant per template: MIGRATION_MIGRATIONS_001_INIT, ...
+/// tomplate_for_each!("migrations/*", "MIGRATION_{name}");
+///
+/// // A table of names and rendered templates
+/// const MIGRATIONS: &[(&str, &str)] =
+///     &tomplate_for_each!("migrations/*", |name, sql| [(name, sql)]);
+/// ```
+///
+/// Templates are rendered without parameters and visited in sorted order.
+/// A braced body is repeated once per template, a bracketed body becomes an
+/// array of the repetitions.
+pub use tomplate_macros::tomplate_for_each;
+
 /// Eagerly evaluates `tomplate!` and `concat!` macros within a token stream.
 ///
 /// This macro solves the problem where outer macros (like `sqlx::query!`) expect
@@ -338,12 +554,266 @@ pub use tomplate_macros::tomplate;
 /// the modified token stream to the compiler.
 pub use tomplate_macros::tomplate_eager;
 
+/// Attribute form of [`tomplate_eager!`], applied to a whole item.
+///
+/// Use it on functions, impl blocks, or modules to eagerly expand every
+/// `tomplate!` call in the item body without wrapping it in a macro block.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// #[tomplate::eager]
+/// async fn find_user(pool: &PgPool) -> sqlx::Result<User> {
+///     sqlx::query_as!(User, tomplate!("select_user", id = "5"))
+///         .fetch_one(pool)
+///         .await
+/// }
+/// ```
+///
+/// Wrapper macros around `tomplate!` can be listed with
+/// `#[tomplate::eager(expand(my_macro))]`.
+pub use tomplate_macros::eager;
+
+#[cfg(any(feature = "runtime", feature = "sea-orm", feature = "testing"))]
+extern crate std;
+
+#[cfg(feature = "runtime")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime")))]
+pub mod runtime;
+
+/// Embeds the crate's template registry and returns it.
+///
+/// Expands to a `&'static` [`runtime::Registry`] holding every template the
+/// build script discovered, with its name, source, engine and metadata. The
+/// registry file is embedded into the binary and parsed on first use.
+///
+/// This is a macro rather than a function because the registry belongs to
+/// the crate being compiled, which is only known at its own expansion site.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let registry = tomplate::registry!();
+///
+/// let template = registry.get("select_user").unwrap();
+/// assert_eq!(template.engine.as_deref(), Some("simple"));
+/// println!("{} templates", registry.len());
+/// ```
+#[cfg(feature = "runtime")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime")))]
+pub use tomplate_macros::registry;
+
+/// Renders a template whose output is Rust code and expands to that code.
+///
+/// # Examples
+///
+/// ```toml
+/// [status_codes]
+/// engine = "tera"
+/// template = """
+/// pub enum Status {
+/// {% for code in [200, 404, 500] %}    S{{ code }} = {{ code }},
+/// {% endfor %}}
+/// """
+/// ```
+///
+/// ```rust,ignore
+/// tomplate::tomplate_rust! { "status_codes" }
+///
+/// assert_eq!(Status::S404 as i32, 404);
+/// ```
+///
+/// `Builder::render_to_rust` writes files containing these calls to
+/// `OUT_DIR` for use with `include!`. Compilation fails if the output is not
+/// valid Rust tokens.
+pub use tomplate_macros::tomplate_rust;
+
+/// Counts the bind placeholders in a rendered SQL template.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::{tomplate, tomplate_binds};
+///
+/// const GET_USER: &str = tomplate!("select_user", fields = "*", condition = "id = $1 AND org = $2");
+/// const GET_USER_BINDS: usize = tomplate_binds!("select_user", fields = "*", condition = "id = $1 AND org = $2");
+///
+/// assert_eq!(GET_USER_BINDS, 2);
+/// ```
+///
+/// Compilation fails if `$n` placeholders skip a number or are mixed with
+/// `?` or `:name`. Templates can check the same with `binds = true` in their
+/// `validate` table.
+pub use tomplate_macros::tomplate_binds;
+
+/// Fails compilation unless a rendered template satisfies a predicate.
+///
+/// Predicates are `contains`, `starts_with`, `ends_with` and `matches`
+/// (a regex), each taking a string literal and combined with `!`, `&&` and
+/// `||`. The macro expands to nothing, so it can sit next to the constant it
+/// checks.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::{tomplate, tomplate_assert};
+///
+/// const GET_USER: &str = tomplate!("select_user", fields = "*", condition = "id = $1");
+/// tomplate_assert!(starts_with("SELECT") && contains("WHERE"), "select_user", fields = "*", condition = "id = $1");
+/// ```
+pub use tomplate_macros::tomplate_assert;
+
+/// Fails compilation unless a rendered template equals its `expected`
+/// parameter.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_assert_eq;
+///
+/// tomplate_assert_eq!(
+///     "select_user",
+///     fields = "id, name",
+///     condition = "id = $1",
+///     expected = "SELECT id, name FROM users WHERE id = $1"
+/// );
+/// ```
+///
+/// On mismatch the error shows both strings and the byte where they diverge.
+pub use tomplate_macros::tomplate_assert_eq;
+
+/// Renders the template chosen by the enabled features.
+///
+/// Each arm maps a feature, or `cfg(...)` with any cfg predicate, to a
+/// template. Arms are tried in order and `_` matches when none of the others
+/// do; without `_`, compilation fails when no arm matches. The parameters
+/// after `;` are passed to the chosen template.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_select;
+///
+/// const GET_USER: &str = tomplate_select!(
+///     postgres => "get_user_pg",
+///     cfg(all(feature = "sqlite", not(feature = "postgres"))) => "get_user_sqlite",
+///     _ => "get_user_generic";
+///     fields = "id, name",
+///     condition = "id = ?"
+/// );
+/// ```
+pub use tomplate_macros::tomplate_select;
+
+/// Renders several templates and joins them with a separator into one
+/// string literal.
+///
+/// Each piece after the separator is a `tomplate!` call or a template name
+/// without parameters. Unlike nesting `concat!` in [`tomplate_eager!`], an
+/// error in a piece is reported at that piece.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::{tomplate, tomplate_join};
+///
+/// const ALL_USERS: &str = tomplate_join!(" UNION ALL ",
+///     tomplate!("select_user", fields = "id", condition = "active"),
+///     tomplate!("select_user", fields = "id", condition = "invited"),
+/// );
+/// ```
+pub use tomplate_macros::tomplate_join;
+
+/// The [`phf`](https://docs.rs/phf) crate, whose `Map` type
+/// [`tomplate_map!`] expands to.
+#[cfg(feature = "phf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "phf")))]
+pub use phf;
+
+/// Builds a perfect-hash map from names to rendered templates.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_map;
+///
+/// // Every registry template matching the pattern, by name
+/// static MIGRATIONS: tomplate::phf::Map<&str, &str> = tomplate_map!("migrations/*");
+///
+/// // Explicit entries
+/// static QUERIES: tomplate::phf::Map<&str, &str> = tomplate_map! {
+///     "user" => tomplate!("select_user", fields = "*", condition = "id = $1"),
+///     "count" => tomplate!("SELECT COUNT(*) FROM users"),
+/// };
+///
+/// assert_eq!(QUERIES.get("count"), Some(&"SELECT COUNT(*) FROM users"));
+/// ```
+///
+/// The hash is computed at compile time, so lookups need no hashing setup
+/// at runtime. Templates matched by pattern are rendered without parameters.
+#[cfg(feature = "phf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "phf")))]
+pub use tomplate_macros::tomplate_map;
+
+#[cfg(feature = "compress")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+pub mod compress;
+
+/// Renders a template and embeds the output compressed.
+///
+/// Takes the same arguments as a direct [`tomplate!`] call and expands to a
+/// [`compress::Compressed`], which decompresses into a buffer at runtime.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_compress;
+///
+/// const SEED: tomplate::compress::Compressed = tomplate_compress!("seed_data", rows = 1000);
+///
+/// let mut buf = vec![0u8; SEED.len()];
+/// let sql = SEED.decompress_into(&mut buf);
+/// ```
+#[cfg(feature = "compress")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+pub use tomplate_macros::tomplate_compress;
+
+#[cfg(feature = "sea-orm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sea-orm")))]
+pub mod sea;
+
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+
+/// Renders a SQL template for every SeaORM backend.
+///
+/// Takes the same arguments as a direct `tomplate!` call and expands to a
+/// [`sea::Sql`] holding the query with `$1` placeholders for PostgreSQL and
+/// with `?` for MySQL and SQLite. The template may be written in either
+/// style, or with `:name` placeholders.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tomplate::tomplate_sea;
+///
+/// const FIND_USER: tomplate::sea::Sql = tomplate_sea!("select_user",
+///     fields = "*",
+///     condition = "id = $1 AND org = $2"
+/// );
+///
+/// let statement = FIND_USER.statement(db.get_database_backend(), [id.into(), org.into()]);
+/// ```
+#[cfg(feature = "sea-orm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sea-orm")))]
+pub use tomplate_macros::tomplate_sea;
+
 // Re-export builder utilities for use in build scripts
 #[cfg(feature = "build")]
-#[doc(cfg(feature = "build"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "build")))]
 pub use tomplate_build::Builder;
 
 // Re-export types for convenience
 #[cfg(feature = "build")]
-#[doc(cfg(feature = "build"))]
-pub use tomplate_build::{BuildMode, Engine, Error, Result, Template};
+#[cfg_attr(docsrs, doc(cfg(feature = "build")))]
+pub use tomplate_build::{BuildMode, Delimiters, Engine, EngineConfig, Error, Escape, Format, Placeholders, Result, Route, Template, Validate};
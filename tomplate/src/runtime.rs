@@ -0,0 +1,79 @@
+//! Runtime access to the template registry.
+//!
+//! With the `runtime` feature, [`registry!`](crate::registry) embeds the
+//! amalgamated registry produced by the build script into the binary, so
+//! template names, sources, engines and metadata can be inspected at runtime,
+//! e.g. to show template provenance on a diagnostics page.
+
+use std::collections::BTreeMap;
+use std::string::String;
+
+pub use tomplate_build::Template;
+
+/// The templates of a crate's registry, ordered by name.
+///
+/// Obtained with [`registry!`](crate::registry).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let registry = tomplate::registry!();
+///
+/// for (name, template) in registry.iter() {
+///     println!(
+///         "{name} ({}): {}",
+///         template.engine.as_deref().unwrap_or("simple"),
+///         template.template,
+///     );
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    templates: BTreeMap<String, Template>,
+}
+
+impl Registry {
+    /// Parses layered amalgamated registries, as embedded by
+    /// [`registry!`](crate::registry). Templates in earlier registries take
+    /// precedence.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a registry is not valid TOML, which only happens if the
+    /// file written by the build script was modified.
+    #[doc(hidden)]
+    pub fn parse(layers: &[&str]) -> Self {
+        let mut templates = BTreeMap::new();
+        for toml in layers.iter().rev() {
+            let layer: BTreeMap<String, Template> =
+                toml::from_str(toml).expect("Failed to parse the embedded template registry");
+            templates.extend(layer);
+        }
+        Registry { templates }
+    }
+    
+    /// Returns the template with the given name.
+    pub fn get(&self, name: &str) -> Option<&Template> {
+        self.templates.get(name)
+    }
+    
+    /// Returns the template names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+    
+    /// Returns the templates with their names in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Template)> {
+        self.templates.iter().map(|(name, template)| (name.as_str(), template))
+    }
+    
+    /// Returns the number of templates.
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+    
+    /// Returns `true` if the registry has no templates.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+}
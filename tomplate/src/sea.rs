@@ -0,0 +1,57 @@
+//! Raw SeaORM statements from templates.
+//!
+//! With the `sea-orm` feature, [`tomplate_sea!`](crate::tomplate_sea) renders
+//! a SQL template once with `$1` placeholders and once with `?`, and
+//! [`Sql::statement`] picks the one matching the connection's backend, so a
+//! single template serves every database SeaORM supports.
+
+use sea_orm::{DbBackend, Statement, Value};
+
+/// A SQL template rendered in the placeholder style of each SeaORM backend.
+///
+/// Obtained with [`tomplate_sea!`](crate::tomplate_sea).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr};
+///
+/// const FIND_USER: tomplate::sea::Sql = tomplate::tomplate_sea!("select_user",
+///     fields = "*",
+///     condition = "id = $1"
+/// );
+///
+/// async fn find_user(db: &DatabaseConnection, id: i32) -> Result<(), DbErr> {
+///     let statement = FIND_USER.statement(db.get_database_backend(), [id.into()]);
+///     db.query_one(statement).await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sql {
+    /// The query with `$1`, `$2`, ... placeholders, for PostgreSQL.
+    pub postgres: &'static str,
+
+    /// The query with `?` placeholders, for MySQL and SQLite.
+    pub positional: &'static str,
+}
+
+impl Sql {
+    /// Returns the query in the placeholder style of a backend.
+    pub fn sql(&self, backend: DbBackend) -> &'static str {
+        if backend == DbBackend::Postgres {
+            self.postgres
+        } else {
+            self.positional
+        }
+    }
+
+    /// Builds a statement for a backend with values for the query's
+    /// placeholders, in order.
+    pub fn statement<I>(&self, backend: DbBackend, values: I) -> Statement
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        Statement::from_sql_and_values(backend, self.sql(backend), values)
+    }
+}
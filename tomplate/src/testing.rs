@@ -0,0 +1,156 @@
+//! Helpers for testing templates.
+//!
+//! With the `testing` feature, [`templates!`] embeds the crate's registry
+//! templates so unit tests can render them with runtime parameters, and
+//! [`CompileTests`] runs compile-pass and compile-fail tests of template
+//! calls with [trybuild](https://docs.rs/trybuild).
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn select_user() {
+//!     let templates = tomplate::testing::templates!();
+//!     assert_eq!(
+//!         templates.render("select_user", &[("fields", "id"), ("condition", "id = $1")]),
+//!         Ok("SELECT id FROM users WHERE id = $1".to_string()),
+//!     );
+//! }
+//! ```
+
+use std::format;
+use std::path::Path;
+use std::string::String;
+
+/// Embeds the crate's registry templates and returns them as a
+/// `&'static` [`Templates`].
+///
+/// Each template is rendered at compile time with a marker for each of its
+/// declared `params`, the same way as for [`tomplate_fn!`](crate::tomplate_fn),
+/// so runtime renders match what the engines produce. Templates that
+/// transform a param, e.g. with a filter or a conditional, or that use an
+/// escape mode, can't be rendered this way and return an error.
+pub use tomplate_macros::test_templates as templates;
+
+/// The templates of a crate's registry, obtained with [`templates!`].
+#[derive(Debug)]
+pub struct Templates {
+    templates: &'static [Compiled],
+}
+
+/// A template split at its params, generated by [`templates!`].
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Compiled {
+    pub name: &'static str,
+    pub params: &'static [&'static str],
+    pub output: Result<&'static [Piece], &'static str>,
+}
+
+/// Literal text or a param of a [`Compiled`] template.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum Piece {
+    Text(&'static str),
+    Param(usize),
+}
+
+impl Templates {
+    /// Wraps the templates generated by [`templates!`], sorted by name.
+    #[doc(hidden)]
+    pub const fn new(templates: &'static [Compiled]) -> Self {
+        Templates { templates }
+    }
+
+    /// Renders a template with values for its declared params.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template doesn't exist, a declared param is
+    /// missing or an undeclared one is given, or the template can't be
+    /// rendered with runtime params.
+    pub fn render(&self, name: &str, params: &[(&str, &str)]) -> Result<String, String> {
+        let template = self
+            .templates
+            .binary_search_by(|template| template.name.cmp(name))
+            .map(|index| &self.templates[index])
+            .map_err(|_| format!("Template not found: {}", name))?;
+        let pieces = template.output.map_err(|e| format!("Template '{}': {}", name, e))?;
+
+        if let Some((param, _)) = params.iter().find(|(param, _)| !template.params.iter().any(|declared| declared == param)) {
+            return Err(format!("Template '{}' has no param '{}'", name, param));
+        }
+        let values = template
+            .params
+            .iter()
+            .map(|param| {
+                params
+                    .iter()
+                    .find(|(given, _)| given == param)
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| format!("Template '{}' is missing param '{}'", name, param))
+            })
+            .collect::<Result<std::vec::Vec<&str>, String>>()?;
+
+        let mut rendered = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => rendered.push_str(text),
+                Piece::Param(index) => rendered.push_str(values[*index]),
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Returns the template names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        self.templates.iter().map(|template| template.name)
+    }
+}
+
+/// Compile-pass and compile-fail tests of template calls, run with trybuild.
+///
+/// Each test file is compiled as its own crate, which has no build script, so
+/// the registry path is forwarded to the compiler through the environment.
+/// Expected compiler output for failing files lives next to them in `.stderr`
+/// files; run with `TRYBUILD=overwrite` to write it. The tests run when the
+/// value is dropped.
+///
+/// ```rust,ignore
+/// // tests/compile.rs
+/// #[test]
+/// fn templates() {
+///     // SAFETY: this is the only test in this binary
+///     let tests = unsafe { tomplate::testing::CompileTests::new(env!("TOMPLATE_TEMPLATES_PATH")) };
+///     tests.pass("tests/ui/pass/*.rs");
+///     tests.compile_fail("tests/ui/fail/*.rs");
+/// }
+/// ```
+pub struct CompileTests {
+    cases: trybuild::TestCases,
+}
+
+impl CompileTests {
+    /// Creates the tests against the registry at `templates_path`, usually
+    /// `env!("TOMPLATE_TEMPLATES_PATH")`.
+    ///
+    /// # Safety
+    ///
+    /// Sets `TOMPLATE_TEMPLATES_PATH` in the process environment, so no
+    /// other thread may read or write the environment at the same time. Put
+    /// compile tests in their own test binary with a single test.
+    pub unsafe fn new(templates_path: &str) -> Self {
+        // SAFETY: upheld by the caller
+        unsafe { std::env::set_var("TOMPLATE_TEMPLATES_PATH", templates_path) };
+        CompileTests { cases: trybuild::TestCases::new() }
+    }
+
+    /// Adds files, or glob patterns of files, that must compile.
+    pub fn pass<P: AsRef<Path>>(&self, path: P) {
+        self.cases.pass(path);
+    }
+
+    /// Adds files, or glob patterns of files, that must fail to compile with
+    /// the errors in their `.stderr` files.
+    pub fn compile_fail<P: AsRef<Path>>(&self, path: P) {
+        self.cases.compile_fail(path);
+    }
+}